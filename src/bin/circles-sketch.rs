@@ -1,15 +1,39 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
+use circles_sketch::boolean;
 use circles_sketch::canvas::{
-    embed_html_of_svg_path_with_fourier, html_of_svg_path_with_fourier, points_of_svg_path,
-    svg_path_of_contour,
+    Chain, ChainStyle, ChainTiming, embed_html_of_chains, embed_html_of_svg_path_with_fourier,
+    html_of_chains, html_of_svg_path_with_fourier, subpaths_of_svg_path, svg_path_of_contour,
+};
+use circles_sketch::contour::{
+    Contour, OpenCurve, Orientation, close_open_paths, fourier_decomposition, interpolate,
+};
+use circles_sketch::equation::{CurveForm, EquationCurve, Expr, parse_param};
+use circles_sketch::export::{
+    AnimatedSvgOptions, StaticSvgOptions, SvgLayout, animated_svg, static_svg,
+};
+use circles_sketch::geojson::{GeoOptions, Projection, parse_geojson, parse_selector};
+use circles_sketch::model::{EmbedOptions, HarmonicSteps};
+use circles_sketch::points::{CsvOptions, PointsFormat, read_points};
+use circles_sketch::poster::{ErrorMetric, PosterOptions, poster_png, poster_svg, poster_tiles};
+use circles_sketch::raster::{TraceOptions, read_image, trace_centerlines, trace_image};
+use circles_sketch::render::{
+    Animation, AnimationFormat, AnimationOptions, PaletteMode, encode_animation, parse_byte_size,
+    parse_color,
+};
+use circles_sketch::scene::Scene;
+use circles_sketch::shape::Shape;
+use circles_sketch::stroke_font::{StrokeFont, stroke_text};
+use circles_sketch::svg::{StrokeMode, parse_svg, svg_contours};
+use circles_sketch::text::{
+    Align, FontSource, FontSpec, TextChains, TextLayout, group_glyphs, parse_variation,
+    svg_path_of_text,
 };
-use circles_sketch::contour::{Contour, fourier_decomposition, interpolate};
-use circles_sketch::model::EmbedOptions;
-use circles_sketch::text::svg_path_of_text;
 use clap::{Parser, Subcommand};
 use font_kit::source::SystemSource;
+use tiny_skia::Color;
 
 /// Convert contour data to HTML Fourier visualization
 #[derive(Parser)]
@@ -21,28 +45,89 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Command {
-    /// Generate from a YAML points file
+    /// Generate from a points file (YAML, CSV or JSON)
     Points {
-        /// YAML file containing contour points
+        /// File containing contour points, or `-` for standard input
         file: String,
 
-        /// Config YAML file path (defaults to {stem}-config.yml)
+        /// Input format (guessed from the extension, then the content)
+        #[arg(long, value_enum)]
+        format: Option<PointsFormat>,
+
+        /// CSV column holding x values (header name or 0-based index)
+        #[arg(long)]
+        x_column: Option<String>,
+
+        /// CSV column holding y values (header name or 0-based index)
+        #[arg(long)]
+        y_column: Option<String>,
+
+        /// Config YAML file path (defaults to {stem}-config.yml if it exists)
         #[arg(long)]
         config: Option<String>,
 
-        /// Output file stem (defaults to input file stem)
+        /// Output file stem (defaults to input file stem, or "points" for stdin)
         #[arg(short, long)]
         output: Option<String>,
     },
 
-    /// Generate from a text string rendered with a system font
+    /// Generate from a text string rendered with a system or file font
     Text {
-        /// Text string to render
+        /// Text string to render; a newline or a literal `\n` starts a new
+        /// line
         text: String,
 
         /// Font PostScript name (use `list-fonts` to see available names)
+        #[arg(
+            long,
+            required_unless_present_any = ["font_file", "stroke_font"],
+            conflicts_with_all = ["font_file", "stroke_font"]
+        )]
+        font: Option<String>,
+
+        /// Font file (.ttf, .otf or .ttc collection)
         #[arg(long)]
-        font: String,
+        font_file: Option<String>,
+
+        /// Face index within a font collection
+        #[arg(long, default_value_t = 0, requires = "font_file")]
+        face_index: u32,
+
+        /// Variable-font axis setting TAG=VALUE, e.g. wght=700 (repeatable)
+        #[arg(long = "variation", value_name = "TAG=VALUE")]
+        variations: Vec<String>,
+
+        /// Single-stroke font: `simplex` (built-in) or a Hershey .jhf file
+        #[arg(long, conflicts_with = "font_file")]
+        stroke_font: Option<String>,
+
+        /// Merge overlapping glyph outlines into the outline visible when filled
+        #[arg(long, conflicts_with = "stroke_font")]
+        flatten: bool,
+
+        /// How strokes of a stroke font are joined into a closed contour
+        #[arg(long, value_enum, default_value_t = OpenCurve::Retrace, requires = "stroke_font")]
+        open_curve: OpenCurve,
+
+        /// Distance between baselines of multi-line text, in ems
+        #[arg(long, default_value_t = 1.2)]
+        line_height: f64,
+
+        /// Alignment of multi-line text
+        #[arg(long, value_enum, default_value_t = Align::Left)]
+        align: Align,
+
+        /// Extra space after each character, in ems
+        #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+        letter_spacing: f64,
+
+        /// Draw the whole text with one epicycle chain, or one per glyph or word
+        #[arg(long, value_enum, default_value_t = TextChains::Single)]
+        chains: TextChains,
+
+        /// Whether separate chains draw one after the other or all at once
+        #[arg(long, value_enum, default_value_t = ChainTiming::Sequential)]
+        timing: ChainTiming,
 
         /// Config YAML file path (uses defaults if omitted)
         #[arg(long)]
@@ -58,6 +143,134 @@ enum Command {
         /// SVG file path
         file: String,
 
+        /// Merge overlapping paths into the outline visible when filled
+        #[arg(long)]
+        flatten: bool,
+
+        /// Use paths as they are, turn strokes into outlines of their drawn
+        /// width, or keep their centerlines
+        #[arg(long, value_enum, default_value_t = StrokeMode::Path)]
+        strokes: StrokeMode,
+
+        /// Centerline strokes: how open strokes are closed for the Fourier series
+        #[arg(long, value_enum, default_value_t = OpenCurve::Retrace)]
+        open_curve: OpenCurve,
+
+        /// Config YAML file path (uses defaults if omitted)
+        #[arg(long)]
+        config: Option<String>,
+
+        /// Output file stem (defaults to input file stem)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    /// Generate from parametric x(t)/y(t) or polar r(θ) equations
+    Equation {
+        /// x(t) expression (parametric form, requires --y)
+        #[arg(long, requires = "y", conflicts_with = "r", allow_hyphen_values = true)]
+        x: Option<String>,
+
+        /// y(t) expression (parametric form, requires --x)
+        #[arg(long, requires = "x", allow_hyphen_values = true)]
+        y: Option<String>,
+
+        /// r(θ) expression (polar form; θ may be written t, theta or θ)
+        #[arg(long, required_unless_present = "x", allow_hyphen_values = true)]
+        r: Option<String>,
+
+        /// Start of the parameter range (constant expression)
+        #[arg(long, default_value = "0", allow_hyphen_values = true)]
+        t_min: String,
+
+        /// End of the parameter range (constant expression)
+        #[arg(long, default_value = "2*pi", allow_hyphen_values = true)]
+        t_max: String,
+
+        /// Parameter binding NAME=VALUE, usable in expressions (repeatable)
+        #[arg(short, long = "param")]
+        params: Vec<String>,
+
+        /// Number of samples taken over the parameter range
+        #[arg(long, default_value_t = 1000)]
+        samples: usize,
+
+        /// Config YAML file path (uses defaults if omitted)
+        #[arg(long)]
+        config: Option<String>,
+
+        /// Output file stem (defaults to "equation")
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    /// Generate from a GeoJSON file (polygons and line strings)
+    Geojson {
+        /// GeoJSON file path
+        file: String,
+
+        /// Keep features whose property KEY equals VALUE (repeatable, any match)
+        #[arg(long = "select", value_name = "KEY=VALUE")]
+        select: Vec<String>,
+
+        /// Map projection
+        #[arg(long, value_enum, default_value_t = Projection::Equirectangular)]
+        projection: Projection,
+
+        /// Projection origin as LON,LAT (defaults to the center of the data)
+        #[arg(long, allow_hyphen_values = true)]
+        origin: Option<String>,
+
+        /// Skip polygon holes
+        #[arg(long)]
+        outer_only: bool,
+
+        /// How line strings are closed for the Fourier series
+        #[arg(long, value_enum, default_value_t = OpenCurve::Retrace)]
+        open_curve: OpenCurve,
+
+        /// Config YAML file path (uses defaults if omitted)
+        #[arg(long)]
+        config: Option<String>,
+
+        /// Output file stem (defaults to input file stem)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    /// Generate from a raster image (PNG, PBM/PGM/PPM) by tracing its edges
+    Image {
+        /// Image file path
+        file: String,
+
+        /// Gray level (0-255): darker pixels are foreground
+        #[arg(long, default_value_t = 128)]
+        threshold: u8,
+
+        /// Treat light pixels as foreground
+        #[arg(long)]
+        invert: bool,
+
+        /// Ignore contours enclosing fewer square pixels than this
+        #[arg(long, default_value_t = 16.0)]
+        min_area: f64,
+
+        /// Keep all contours instead of only the largest one
+        #[arg(long)]
+        all: bool,
+
+        /// Trace the centerline (skeleton) of strokes instead of their outline
+        #[arg(long)]
+        centerline: bool,
+
+        /// Centerline mode: ignore strokes shorter than this many pixels
+        #[arg(long, default_value_t = 5.0)]
+        min_length: f64,
+
+        /// Centerline mode: how open strokes are closed for the Fourier series
+        #[arg(long, value_enum, default_value_t = OpenCurve::Retrace)]
+        open_curve: OpenCurve,
+
         /// Config YAML file path (uses defaults if omitted)
         #[arg(long)]
         config: Option<String>,
@@ -67,6 +280,209 @@ enum Command {
         output: Option<String>,
     },
 
+    /// Generate from a built-in shape generator
+    Shape {
+        #[command(subcommand)]
+        shape: Shape,
+
+        /// Number of samples for curved shapes
+        #[arg(long, default_value_t = 1000, global = true)]
+        samples: usize,
+
+        /// Config YAML file path (uses defaults if omitted)
+        #[arg(long, global = true)]
+        config: Option<String>,
+
+        /// Output file stem (defaults to the shape name)
+        #[arg(short, long, global = true)]
+        output: Option<String>,
+    },
+
+    /// Generate from a scene file placing several inputs on one canvas
+    Scene {
+        /// Scene YAML file path
+        file: String,
+
+        /// Config YAML file path (defaults to {stem}-config.yml if it exists)
+        #[arg(long)]
+        config: Option<String>,
+
+        /// Output file stem (defaults to input file stem)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    /// Report the geometry of an input (area, orientation, centroid,
+    /// perimeter, bounding box, self-intersections) without generating pages
+    Inspect {
+        /// Input subcommand and its arguments, e.g. `inspect svg logo.svg`
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        input: Vec<String>,
+    },
+
+    /// Write a static SVG of the contour and its reconstructions at chosen
+    /// harmonic counts, e.g. `static-svg --harmonics 5,20,100 shape heart`
+    StaticSvg {
+        /// Harmonic counts of the reconstructions
+        #[arg(long, value_delimiter = ',', default_values_t = [5, 20, 100])]
+        harmonics: Vec<usize>,
+
+        /// One layer per reconstruction, or one grid tile each
+        #[arg(long, value_enum, default_value_t = SvgLayout::Grid)]
+        layout: SvgLayout,
+
+        /// Tiles per row of the grid
+        #[arg(long)]
+        columns: Option<usize>,
+
+        /// Also draw the epicycle circles at this time, from 0 to 1
+        #[arg(long)]
+        circles_at: Option<f64>,
+
+        /// Width and height of the picture, or of each grid tile, in px
+        #[arg(long, default_value_t = 500.0)]
+        size: f64,
+
+        /// Background color, or `none` for a transparent background
+        #[arg(long, default_value = "black")]
+        background: String,
+
+        /// Input subcommand and its arguments, or a scene
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        input: Vec<String>,
+    },
+
+    /// Write an SVG animated without scripts (SMIL), drawing a fixed number
+    /// of harmonics, e.g. `animated-svg --harmonics 30 text 'Hi'`
+    AnimatedSvg {
+        /// Number of harmonics drawn
+        #[arg(long, default_value_t = 20)]
+        harmonics: usize,
+
+        /// Length of one loop in seconds (defaults to the config's first
+        /// speed, as in the pages)
+        #[arg(long)]
+        duration: Option<f64>,
+
+        /// Width and height of the picture in px
+        #[arg(long, default_value_t = 500.0)]
+        size: f64,
+
+        /// Background color, or `none` for a transparent background
+        #[arg(long, default_value = "black")]
+        background: String,
+
+        /// Input subcommand and its arguments, or a scene
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        input: Vec<String>,
+    },
+
+    /// Render the page animation to numbered PNG frames without a browser,
+    /// e.g. `render --fps 30 --size 800 shape heart`
+    Render {
+        /// Width and height of the frames in px
+        #[arg(long, default_value_t = 500)]
+        size: u32,
+
+        /// Frames per second of animation
+        #[arg(long, default_value_t = 30.0)]
+        fps: f64,
+
+        /// Harmonics schedule as in the pages, `from step to speed ; ...`
+        /// (defaults to the config's steps)
+        #[arg(long)]
+        steps: Option<String>,
+
+        /// Number of loops to render (defaults to the whole schedule)
+        #[arg(long)]
+        loops: Option<usize>,
+
+        /// Background color, or `none` for a transparent background
+        #[arg(long, default_value = "black")]
+        background: String,
+
+        /// Input subcommand and its arguments, or a scene
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        input: Vec<String>,
+    },
+
+    /// Write a looping GIF or APNG of the page animation without a browser,
+    /// e.g. `animation --format apng --max-size 2M shape heart`
+    Animation {
+        /// File format
+        #[arg(long, value_enum, default_value_t = AnimationFormat::Gif)]
+        format: AnimationFormat,
+
+        /// Width and height of the frames in px
+        #[arg(long, default_value_t = 400)]
+        size: u32,
+
+        /// Frames per second of animation
+        #[arg(long, default_value_t = 25.0)]
+        fps: f64,
+
+        /// Harmonics schedule as in the pages, `from step to speed ; ...`
+        /// (defaults to the config's steps)
+        #[arg(long)]
+        steps: Option<String>,
+
+        /// Number of loops to encode (defaults to the whole schedule)
+        #[arg(long)]
+        loops: Option<usize>,
+
+        /// Background color, or `none` for a transparent background
+        #[arg(long, default_value = "black")]
+        background: String,
+
+        /// Palette size from 2 to 256 (GIF default 256; APNG is true color
+        /// unless set)
+        #[arg(long)]
+        colors: Option<usize>,
+
+        /// One palette for the whole animation or one per frame (GIF only)
+        #[arg(long, value_enum, default_value_t = PaletteMode::Global)]
+        palette: PaletteMode,
+
+        /// Shrink the frames until the file fits, e.g. `500K` or `8M`
+        #[arg(long)]
+        max_size: Option<String>,
+
+        /// Input subcommand and its arguments, or a scene
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        input: Vec<String>,
+    },
+
+    /// Write an SVG and a PNG grid of the reconstructions at each harmonic
+    /// count of the schedule, labeled with their error, e.g.
+    /// `poster --steps "1 1 8 3" --metric max shape heart`
+    Poster {
+        /// Harmonics schedule as in the pages, `from step to speed ; ...`
+        /// (defaults to the config's steps)
+        #[arg(long)]
+        steps: Option<String>,
+
+        /// Error shown under each harmonic count, as a percentage of the
+        /// contours' bounding box diagonal
+        #[arg(long, value_enum, default_value_t = ErrorMetric::Rms)]
+        metric: ErrorMetric,
+
+        /// Tiles per row (defaults to a square-ish grid)
+        #[arg(long)]
+        columns: Option<usize>,
+
+        /// Width and height of each tile in px
+        #[arg(long, default_value_t = 300)]
+        size: u32,
+
+        /// Background color, or `none` for a transparent background
+        #[arg(long, default_value = "black")]
+        background: String,
+
+        /// Input subcommand and its arguments, or a scene
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        input: Vec<String>,
+    },
+
     /// List available font PostScript names
     ListFonts,
 
@@ -101,51 +517,577 @@ fn main() {
     let cli = Cli::parse();
 
     match cli.command {
+        Command::Scene {
+            file,
+            config,
+            output,
+        } => {
+            let (chains, timing, opts, stem) =
+                load_scene(&file, config.as_deref(), output.as_deref());
+            write_chains(&chains, timing, &opts, &stem, &command_line);
+        }
+        Command::Inspect { input } => {
+            let args = std::iter::once("circles-sketch".to_string()).chain(input);
+            match Cli::try_parse_from(args) {
+                Ok(cli) if !is_input(&cli.command) => {
+                    eprintln!("inspect: not an input subcommand");
+                    std::process::exit(1);
+                }
+                Ok(cli) => {
+                    let (input, files) = load_input(cli.command);
+                    inspect(input, &files.resolve().0);
+                }
+                Err(e) => e.exit(),
+            }
+        }
+        Command::StaticSvg {
+            harmonics,
+            layout,
+            columns,
+            circles_at,
+            size,
+            background,
+            input,
+        } => {
+            if harmonics.is_empty() || harmonics.contains(&0) {
+                eprintln!("--harmonics: counts must be > 0");
+                std::process::exit(1);
+            }
+            if !(size > 0.0 && size.is_finite()) {
+                eprintln!("--size must be > 0");
+                std::process::exit(1);
+            }
+            if circles_at.is_some_and(|t| !t.is_finite()) {
+                eprintln!("--circles-at must be a number");
+                std::process::exit(1);
+            }
+            let (chains, _, opts, stem) = load_chains("static-svg", input);
+            let svg = static_svg(
+                &chains,
+                &opts,
+                &StaticSvgOptions {
+                    harmonics,
+                    layout,
+                    columns,
+                    circles_at,
+                    size,
+                    background,
+                },
+            );
+            write_output(&format!("{stem}-static.svg"), svg);
+        }
+        Command::AnimatedSvg {
+            harmonics,
+            duration,
+            size,
+            background,
+            input,
+        } => {
+            if harmonics == 0 {
+                eprintln!("--harmonics must be > 0");
+                std::process::exit(1);
+            }
+            if !(size > 0.0 && size.is_finite()) {
+                eprintln!("--size must be > 0");
+                std::process::exit(1);
+            }
+            let (chains, timing, opts, stem) = load_chains("animated-svg", input);
+            // The pages advance by speed / 10 loops per second
+            let speed = opts.steps.ranges.first().map_or(3.0, |r| r.speed);
+            let duration = duration.unwrap_or(10.0 / speed);
+            if !(duration > 0.0 && duration.is_finite()) {
+                eprintln!("--duration must be > 0");
+                std::process::exit(1);
+            }
+            let svg = animated_svg(
+                &chains,
+                timing,
+                &opts,
+                &AnimatedSvgOptions {
+                    harmonics,
+                    duration,
+                    size,
+                    background,
+                },
+            );
+            write_output(&format!("{stem}-animated.svg"), svg);
+        }
+        Command::Render {
+            size,
+            fps,
+            steps,
+            loops,
+            background,
+            input,
+        } => {
+            if size == 0 {
+                eprintln!("--size must be > 0");
+                std::process::exit(1);
+            }
+            if !(fps > 0.0 && fps.is_finite()) {
+                eprintln!("--fps must be > 0");
+                std::process::exit(1);
+            }
+            let steps = steps.map(|s| parse_steps(&s));
+            let background = parse_background(&background);
+            let (chains, timing, opts, stem) = load_chains("render", input);
+            render_frames(
+                &chains,
+                timing,
+                &opts,
+                steps.as_ref(),
+                loops,
+                size,
+                fps,
+                background,
+                &stem,
+            );
+        }
+        Command::Animation {
+            format,
+            size,
+            fps,
+            steps,
+            loops,
+            background,
+            colors,
+            palette,
+            max_size,
+            input,
+        } => {
+            if size == 0 {
+                eprintln!("--size must be > 0");
+                std::process::exit(1);
+            }
+            if !(fps > 0.0 && fps.is_finite()) {
+                eprintln!("--fps must be > 0");
+                std::process::exit(1);
+            }
+            let steps = steps.map(|s| parse_steps(&s));
+            let background = parse_background(&background);
+            let max_bytes = max_size.map(|s| {
+                parse_byte_size(&s).unwrap_or_else(|e| {
+                    eprintln!("--max-size: {e}");
+                    std::process::exit(1);
+                })
+            });
+            let (chains, timing, opts, stem) = load_chains("animation", input);
+            let anim = AnimationOptions {
+                format,
+                size,
+                fps,
+                loops,
+                background,
+                colors,
+                palette,
+                max_bytes,
+            };
+            let (bytes, used) = encode_animation(&chains, timing, &opts, steps.as_ref(), &anim)
+                .unwrap_or_else(|e| {
+                    eprintln!("Error: {e}");
+                    std::process::exit(1);
+                });
+            if used < size {
+                println!("Frames reduced to {used} px to fit in --max-size");
+            }
+            write_output(&format!("{stem}-animation.{}", format.extension()), bytes);
+        }
+        Command::Poster {
+            steps,
+            metric,
+            columns,
+            size,
+            background,
+            input,
+        } => {
+            if size == 0 {
+                eprintln!("--size must be > 0");
+                std::process::exit(1);
+            }
+            if columns == Some(0) {
+                eprintln!("--columns must be > 0");
+                std::process::exit(1);
+            }
+            let steps = steps.map(|s| parse_steps(&s));
+            parse_background(&background);
+            let (chains, _, opts, stem) = load_chains("poster", input);
+            let steps = steps.as_ref().unwrap_or(&opts.steps);
+            let tiles = poster_tiles(&chains, &opts, steps, metric);
+            let poster = PosterOptions {
+                metric,
+                columns,
+                size,
+                background,
+            };
+            let (svg, png) = poster_svg(&chains, &opts, &tiles, &poster)
+                .and_then(|svg| Ok((svg, poster_png(&chains, &opts, &tiles, &poster)?)))
+                .unwrap_or_else(|e| {
+                    eprintln!("Error: {e}");
+                    std::process::exit(1);
+                });
+            write_output(&format!("{stem}-poster.svg"), svg);
+            write_output(&format!("{stem}-poster.png"), png);
+        }
+        Command::ListFonts => {
+            list_fonts();
+        }
+        Command::InitConfig { file } => {
+            init_config(&file);
+        }
+        command => {
+            let (input, files) = load_input(command);
+            let (opts, stem) = files.resolve();
+            match input {
+                Input::Single(contour) => generate(contour, opts, &stem, &command_line),
+                Input::Chains(groups, timing) => {
+                    generate_chains(groups, timing, opts, &stem, &command_line)
+                }
+            }
+        }
+    }
+}
+
+/// Contours read by an input subcommand, before decomposition.
+enum Input {
+    Single(Contour),
+    Chains(ChainGroups, ChainTiming),
+}
+
+/// An input's `--config` and `-o` options, and the names they default to.
+struct InputFiles {
+    config: Option<String>,
+    output: Option<String>,
+    /// Output stem when `-o` is not given
+    stem: String,
+    /// Whether the default config is named after the input file rather
+    /// than after the output stem
+    next_to_input: bool,
+}
+
+impl InputFiles {
+    /// Load the config and return it with the output stem.
+    fn resolve(self) -> (EmbedOptions, String) {
+        let output = self.output.unwrap_or_else(|| self.stem.clone());
+        let named = if self.next_to_input {
+            &self.stem
+        } else {
+            &output
+        };
+        let opts = load_config(self.config.as_deref(), &format!("{named}-config.yml"));
+        (opts, output)
+    }
+}
+
+/// Read the input of `command`, which must be an input subcommand, without
+/// loading its config.
+fn load_input(command: Command) -> (Input, InputFiles) {
+    match command {
         Command::Points {
             file,
+            format,
+            x_column,
+            y_column,
             config,
             output,
         } => {
-            let (contour, opts, stem) = load_points(&file, config.as_deref(), output.as_deref());
-            generate(contour, opts, &stem, &command_line);
+            let csv = CsvOptions { x_column, y_column };
+            let (contour, stem) = load_points(&file, format, &csv);
+            let files = InputFiles {
+                config,
+                output,
+                stem,
+                next_to_input: file != "-",
+            };
+            (Input::Single(contour), files)
         }
         Command::Text {
             text,
             font,
+            font_file,
+            face_index,
+            variations,
+            stroke_font,
+            flatten,
+            open_curve,
+            line_height,
+            align,
+            letter_spacing,
+            chains,
+            timing,
             config,
             output,
         } => {
-            let (contour, opts, stem) =
-                load_text(&text, &font, config.as_deref(), output.as_deref());
-            generate(contour, opts, &stem, &command_line);
+            let font = match stroke_font {
+                Some(name) => TextFont::Stroke(load_stroke_font(&name), open_curve),
+                None => {
+                    let source = match (font, font_file) {
+                        (_, Some(path)) => FontSource::File {
+                            path: path.into(),
+                            index: face_index,
+                        },
+                        (Some(name), None) => FontSource::System(name),
+                        (None, None) => unreachable!("clap requires a font option"),
+                    };
+                    let variations = variations
+                        .iter()
+                        .map(|v| parse_variation(v))
+                        .collect::<Result<_, _>>()
+                        .unwrap_or_else(|e| {
+                            eprintln!("Invalid --variation: {e}");
+                            std::process::exit(1);
+                        });
+                    TextFont::Outline(FontSpec { source, variations }, flatten)
+                }
+            };
+            let layout = TextLayout {
+                line_height,
+                align,
+                letter_spacing,
+            };
+            let (groups, stem) = load_text(&text, &font, &layout, chains);
+            let input = match chains {
+                TextChains::Single => {
+                    let (contour, _) = groups.into_iter().next().expect("one group");
+                    Input::Single(contour)
+                }
+                _ => Input::Chains(groups, timing),
+            };
+            let files = InputFiles {
+                config,
+                output,
+                stem,
+                next_to_input: false,
+            };
+            (input, files)
         }
         Command::Svg {
             file,
+            flatten,
+            strokes,
+            open_curve,
             config,
             output,
         } => {
-            let (contour, opts, stem) = load_svg(&file, config.as_deref(), output.as_deref());
-            generate(contour, opts, &stem, &command_line);
+            let (contour, stem) = load_svg(&file, flatten, strokes, open_curve);
+            let files = InputFiles {
+                config,
+                output,
+                stem,
+                next_to_input: true,
+            };
+            (Input::Single(contour), files)
         }
-        Command::ListFonts => {
-            list_fonts();
+        Command::Equation {
+            x,
+            y,
+            r,
+            t_min,
+            t_max,
+            params,
+            samples,
+            config,
+            output,
+        } => {
+            let form = match (x, y, r) {
+                (Some(x), Some(y), _) => CurveForm::Parametric {
+                    x: parse_expr("x(t)", &x),
+                    y: parse_expr("y(t)", &y),
+                },
+                (_, _, Some(r)) => CurveForm::Polar {
+                    r: parse_expr("r(θ)", &r),
+                },
+                _ => unreachable!("clap enforces --x/--y or --r"),
+            };
+            let contour = load_equation(form, &t_min, &t_max, &params, samples);
+            let files = InputFiles {
+                config,
+                output,
+                stem: "equation".to_string(),
+                next_to_input: false,
+            };
+            (Input::Single(contour), files)
         }
-        Command::InitConfig { file } => {
-            init_config(&file);
+        Command::Geojson {
+            file,
+            select,
+            projection,
+            origin,
+            outer_only,
+            open_curve,
+            config,
+            output,
+        } => {
+            let select = select
+                .iter()
+                .map(|s| parse_selector(s))
+                .collect::<Result<_, _>>()
+                .unwrap_or_else(|e| {
+                    eprintln!("Invalid --select: {e}");
+                    std::process::exit(1);
+                });
+            let origin = origin.map(|o| {
+                o.split_once(',')
+                    .and_then(|(lon, lat)| {
+                        Some((lon.trim().parse().ok()?, lat.trim().parse().ok()?))
+                    })
+                    .unwrap_or_else(|| {
+                        eprintln!("Invalid --origin: expected LON,LAT, got {o}");
+                        std::process::exit(1);
+                    })
+            });
+            let geo = GeoOptions {
+                select,
+                projection,
+                origin,
+                outer_only,
+            };
+            let contour = load_geojson(&file, &geo, open_curve);
+            let files = InputFiles {
+                config,
+                output,
+                stem: stem_of_file(&file),
+                next_to_input: true,
+            };
+            (Input::Single(contour), files)
+        }
+        Command::Image {
+            file,
+            threshold,
+            invert,
+            min_area,
+            all,
+            centerline,
+            min_length,
+            open_curve,
+            config,
+            output,
+        } => {
+            let trace = TraceOptions {
+                threshold,
+                invert,
+                min_area,
+                all,
+                min_length,
+            };
+            let centerline = centerline.then_some(open_curve);
+            let contour = load_image(&file, &trace, centerline);
+            let files = InputFiles {
+                config,
+                output,
+                stem: stem_of_file(&file),
+                next_to_input: true,
+            };
+            (Input::Single(contour), files)
+        }
+        Command::Shape {
+            shape,
+            samples,
+            config,
+            output,
+        } => {
+            let contour = load_shape(&shape, samples);
+            let files = InputFiles {
+                config,
+                output,
+                stem: shape.name().to_string(),
+                next_to_input: false,
+            };
+            (Input::Single(contour), files)
+        }
+        Command::Scene { .. }
+        | Command::Inspect { .. }
+        | Command::StaticSvg { .. }
+        | Command::AnimatedSvg { .. }
+        | Command::Render { .. }
+        | Command::Animation { .. }
+        | Command::Poster { .. }
+        | Command::ListFonts
+        | Command::InitConfig { .. } => {
+            unreachable!("not an input subcommand")
         }
     }
 }
 
-fn generate(mut contour: Contour, opts: EmbedOptions, stem: &str, command: &str) {
+/// Whether `command` reads an input that `load_input` accepts.
+fn is_input(command: &Command) -> bool {
+    !matches!(
+        command,
+        Command::Scene { .. }
+            | Command::Inspect { .. }
+            | Command::StaticSvg { .. }
+            | Command::AnimatedSvg { .. }
+            | Command::Render { .. }
+            | Command::Animation { .. }
+            | Command::Poster { .. }
+            | Command::ListFonts
+            | Command::InitConfig { .. }
+    )
+}
+
+/// Parse the input arguments of an output subcommand such as `static-svg`
+/// (an input subcommand or a scene) into chains, as the pages would draw
+/// them.
+fn load_chains(what: &str, input: Vec<String>) -> (Vec<Chain>, ChainTiming, EmbedOptions, String) {
+    let args = std::iter::once("circles-sketch".to_string()).chain(input);
+    let command = match Cli::try_parse_from(args) {
+        Ok(Cli {
+            command:
+                Command::Scene {
+                    file,
+                    config,
+                    output,
+                },
+        }) => return load_scene(&file, config.as_deref(), output.as_deref()),
+        Ok(cli) if !is_input(&cli.command) => {
+            eprintln!("{what}: not an input subcommand");
+            std::process::exit(1);
+        }
+        Ok(cli) => cli.command,
+        Err(e) => e.exit(),
+    };
+    let (input, files) = load_input(command);
+    let (opts, stem) = files.resolve();
+    let (groups, timing) = match input {
+        Input::Single(contour) => (vec![(contour, (0.0, 0.0))], ChainTiming::Parallel),
+        Input::Chains(groups, timing) => (groups, timing),
+    };
+    (decompose_groups(groups, &opts), timing, opts, stem)
+}
+
+/// Move `groups` to page coordinates and apply the config's preprocessing
+/// steps to them, reporting the total point count before and after each
+/// step.
+fn preprocess(groups: &mut ChainGroups, opts: &EmbedOptions) {
+    for (contour, offset) in groups.iter_mut() {
+        for p in &mut contour.points {
+            *p = opts.page_point(*p);
+        }
+        *offset = opts.page_point(*offset);
+    }
+    let mut contours: Vec<&mut Contour> = groups.iter_mut().map(|(c, _)| c).collect();
+    for step in &opts.preprocess {
+        let before: usize = contours.iter().map(|c| c.points.len()).sum();
+        step.apply_each(&mut contours).unwrap_or_else(|e| {
+            eprintln!("Invalid config: {e}");
+            std::process::exit(1);
+        });
+        let after: usize = contours.iter().map(|c| c.points.len()).sum();
+        println!("{}: {before} → {after} points", step.name());
+    }
+}
+
+fn generate(contour: Contour, opts: EmbedOptions, stem: &str, command: &str) {
     opts.validate().unwrap_or_else(|e| {
         eprintln!("Invalid config: {e}");
         std::process::exit(1);
     });
-    if opts.flip_y {
-        for p in &mut contour.points {
-            p.1 = -p.1;
-        }
+    if contour.points.is_empty() {
+        eprintln!("The input has no points");
+        std::process::exit(1);
     }
+    let mut groups = vec![(contour, (0.0, 0.0))];
+    preprocess(&mut groups, &opts);
+    let (contour, _) = groups.remove(0);
     let num_points = contour.points.len().max(opts.max_harmonics * 2).max(1000);
     let contour = interpolate(&contour, num_points);
     let svg_path = svg_path_of_contour(&contour);
@@ -154,138 +1096,415 @@ fn generate(mut contour: Contour, opts: EmbedOptions, stem: &str, command: &str)
 
     let html =
         html_of_svg_path_with_fourier(&svg_path, &contour.points, Some(&fd), &opts, Some(command));
-    let output_path = format!("{stem}.html");
-    fs::write(&output_path, &html).unwrap_or_else(|e| {
-        eprintln!("Error writing {output_path}: {e}");
-        std::process::exit(1);
-    });
-    println!("Written to {output_path}");
+    write_output(&format!("{stem}.html"), html);
 
     let embed_html =
         embed_html_of_svg_path_with_fourier(&svg_path, &contour.points, Some(&fd), &opts);
-    let embed_path = format!("{stem}-embed.html");
-    fs::write(&embed_path, &embed_html).unwrap_or_else(|e| {
-        eprintln!("Error writing {embed_path}: {e}");
+    write_output(&format!("{stem}-embed.html"), embed_html);
+}
+
+/// Like `generate`, with one epicycle chain per `(contour, offset)` group.
+fn generate_chains(
+    groups: ChainGroups,
+    timing: ChainTiming,
+    opts: EmbedOptions,
+    stem: &str,
+    command: &str,
+) {
+    let chains = decompose_groups(groups, &opts);
+    write_chains(&chains, timing, &opts, stem, command);
+}
+
+/// Validate, preprocess and decompose the groups of an input into chains.
+fn decompose_groups(mut groups: ChainGroups, opts: &EmbedOptions) -> Vec<Chain> {
+    opts.validate().unwrap_or_else(|e| {
+        eprintln!("Invalid config: {e}");
         std::process::exit(1);
     });
-    println!("Written to {embed_path}");
+    if groups.iter().all(|(c, _)| c.points.is_empty()) {
+        eprintln!("The input has no points");
+        std::process::exit(1);
+    }
+    preprocess(&mut groups, opts);
+    groups
+        .into_iter()
+        .map(|(contour, offset)| decompose_chain(contour, offset, ChainStyle::default(), opts))
+        .collect()
 }
 
-fn load_points(
-    file: &str,
-    config: Option<&str>,
-    output: Option<&str>,
-) -> (Contour, EmbedOptions, String) {
-    let input_path = Path::new(file);
-    let yaml = fs::read_to_string(input_path).unwrap_or_else(|e| {
-        eprintln!("Error reading {}: {}", input_path.display(), e);
+/// Interpolate and decompose one chain's contour, as `generate` does.
+fn decompose_chain(
+    contour: Contour,
+    offset: (f64, f64),
+    style: ChainStyle,
+    opts: &EmbedOptions,
+) -> Chain {
+    let num_points = contour.points.len().max(opts.max_harmonics * 2).max(1000);
+    let contour = interpolate(&contour, num_points);
+    let mut fourier = fourier_decomposition(&contour, (num_points / 2).min(500));
+    // Terms are sorted by radius and the page never uses more than
+    // max_harmonics of them
+    fourier.coeffs.truncate(opts.max_harmonics.max(1));
+    Chain {
+        contour,
+        fourier,
+        offset,
+        style,
+    }
+}
+
+fn write_chains(
+    chains: &[Chain],
+    timing: ChainTiming,
+    opts: &EmbedOptions,
+    stem: &str,
+    command: &str,
+) {
+    println!("{} epicycle chains", chains.len());
+
+    let html = html_of_chains(chains, timing, opts, Some(command));
+    write_output(&format!("{stem}.html"), html);
+
+    let embed_html = embed_html_of_chains(chains, timing, opts);
+    write_output(&format!("{stem}-embed.html"), embed_html);
+}
+
+/// Write the frames of `loops` loops of the animation (the whole schedule
+/// by default) to `{stem}-frames/frame-NNNNN.png`.
+#[allow(clippy::too_many_arguments)]
+fn render_frames(
+    chains: &[Chain],
+    timing: ChainTiming,
+    opts: &EmbedOptions,
+    steps: Option<&HarmonicSteps>,
+    loops: Option<usize>,
+    size: u32,
+    fps: f64,
+    background: Color,
+    stem: &str,
+) {
+    opts.validate().unwrap_or_else(|e| {
+        eprintln!("Invalid config: {e}");
         std::process::exit(1);
     });
-    let contour: Contour = serde_yaml::from_str(&yaml).unwrap_or_else(|e| {
-        eprintln!("Error parsing YAML: {e}");
+    let mut animation = Animation::new(chains, timing, opts, steps).unwrap_or_else(|e| {
+        eprintln!("Invalid config: {e}");
+        std::process::exit(1);
+    });
+    let loops = loops.unwrap_or(animation.schedule_len());
+    let dir = format!("{stem}-frames");
+    fs::create_dir_all(&dir).unwrap_or_else(|e| {
+        eprintln!("Error creating {dir}: {e}");
         std::process::exit(1);
     });
+    let mut count = 0;
+    let frames = animation
+        .frames(loops, size, fps, background)
+        .unwrap_or_else(|e| {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        });
+    for pixmap in frames {
+        let png = pixmap.encode_png().unwrap_or_else(|e| {
+            eprintln!("Error encoding frame {count}: {e}");
+            std::process::exit(1);
+        });
+        let path = format!("{dir}/frame-{count:05}.png");
+        fs::write(&path, png).unwrap_or_else(|e| {
+            eprintln!("Error writing {path}: {e}");
+            std::process::exit(1);
+        });
+        count += 1;
+    }
+    println!("Written {count} frames to {dir}");
+}
 
-    let stem = input_path.file_stem().unwrap().to_str().unwrap();
-    let config_path = config.map(|s| s.to_string()).unwrap_or_else(|| {
-        input_path
-            .with_file_name(format!("{stem}-config.yml"))
-            .to_str()
-            .unwrap()
-            .to_string()
+/// Harmonics schedule of a `--steps` option.
+fn parse_steps(s: &str) -> HarmonicSteps {
+    HarmonicSteps::parse(s).unwrap_or_else(|e| {
+        eprintln!("--steps: {e}");
+        std::process::exit(1);
+    })
+}
+
+/// Color of a `--background` option, transparent for `none`.
+fn parse_background(s: &str) -> Color {
+    if s == "none" {
+        return Color::TRANSPARENT;
+    }
+    parse_color(s).unwrap_or_else(|| {
+        eprintln!("--background: unknown color: {s}");
+        std::process::exit(1);
+    })
+}
+
+fn write_output(path: &str, contents: impl AsRef<[u8]>) {
+    fs::write(path, contents).unwrap_or_else(|e| {
+        eprintln!("Error writing {path}: {e}");
+        std::process::exit(1);
     });
-    let config_yaml = fs::read_to_string(&config_path).unwrap_or_else(|e| {
-        eprintln!("Error reading {config_path}: {e}");
+    println!("Written to {path}");
+}
+
+/// Print the geometry of each contour of `input`, after the config's
+/// preprocessing steps.
+fn inspect(input: Input, opts: &EmbedOptions) {
+    opts.validate().unwrap_or_else(|e| {
+        eprintln!("Invalid config: {e}");
         std::process::exit(1);
     });
-    let opts: EmbedOptions = serde_yaml::from_str(&config_yaml).unwrap_or_else(|e| {
-        eprintln!("Error parsing {config_path}: {e}");
+    let mut groups = match input {
+        Input::Single(contour) => vec![(contour, (0.0, 0.0))],
+        Input::Chains(groups, _) => groups,
+    };
+    preprocess(&mut groups, opts);
+    for (i, (contour, (dx, dy))) in groups.iter().enumerate() {
+        if groups.len() > 1 {
+            println!("chain {i} at ({dx:.3}, {dy:.3}):");
+        } else {
+            println!("contour:");
+        }
+        println!("  points: {}", contour.points.len());
+        let Some(bbox) = contour.bounding_box() else {
+            continue;
+        };
+        println!(
+            "  bounding box: ({:.3}, {:.3}) – ({:.3}, {:.3}), {:.3} × {:.3}",
+            bbox.min.0,
+            bbox.min.1,
+            bbox.max.0,
+            bbox.max.1,
+            bbox.width(),
+            bbox.height()
+        );
+        let orientation = match contour.orientation() {
+            Some(Orientation::CounterClockwise) => "counter-clockwise",
+            Some(Orientation::Clockwise) => "clockwise",
+            None => "none",
+        };
+        println!("  signed area: {:.3}", contour.signed_area());
+        println!("  orientation: {orientation}");
+        if let Some((cx, cy)) = contour.centroid() {
+            println!("  centroid: ({cx:.3}, {cy:.3})");
+        }
+        println!("  perimeter: {:.3}", contour.perimeter());
+        let crossings = contour.self_intersections();
+        println!("  self-intersections: {}", crossings.len());
+        for c in crossings.iter().take(5) {
+            println!(
+                "    segments {} and {} at ({:.3}, {:.3})",
+                c.segments.0, c.segments.1, c.point.0, c.point.1
+            );
+        }
+        if crossings.len() > 5 {
+            println!("    ...");
+        }
+    }
+}
+
+fn load_points(file: &str, format: Option<PointsFormat>, csv: &CsvOptions) -> (Contour, String) {
+    let contour = read_points(file, format, csv).unwrap_or_else(|e| {
+        eprintln!("Error reading points: {e}");
         std::process::exit(1);
     });
+    let stem = if file == "-" {
+        "points".to_string()
+    } else {
+        stem_of_file(file)
+    };
+    (contour, stem)
+}
 
-    let output_stem = output
-        .map(|s| s.to_string())
-        .unwrap_or_else(|| input_path.with_extension("").to_str().unwrap().to_string());
+/// The path of `file` without its extension.
+fn stem_of_file(file: &str) -> String {
+    Path::new(file)
+        .with_extension("")
+        .to_str()
+        .unwrap()
+        .to_string()
+}
 
-    (contour, opts, output_stem)
+/// Contours to animate as separate epicycle chains, each with its offset.
+type ChainGroups = Vec<(Contour, (f64, f64))>;
+
+/// Font for the `text` subcommand: filled outlines, optionally flattened to
+/// their visible outline, or single strokes.
+enum TextFont {
+    Outline(FontSpec, bool),
+    Stroke(StrokeFont, OpenCurve),
+}
+
+fn load_stroke_font(name: &str) -> StrokeFont {
+    if name == "simplex" {
+        return StrokeFont::builtin();
+    }
+    StrokeFont::load(Path::new(name)).unwrap_or_else(|e| {
+        eprintln!("Error loading stroke font: {e}");
+        std::process::exit(1);
+    })
 }
 
 fn load_text(
     text: &str,
-    font: &str,
-    config: Option<&str>,
-    output: Option<&str>,
-) -> (Contour, EmbedOptions, String) {
-    let svg_path = svg_path_of_text(text, font);
-    let points = points_of_svg_path(&svg_path);
-    let contour = Contour { points };
-
-    let output_stem = output.map(|s| s.to_string()).unwrap_or_else(|| {
-        text.to_lowercase()
-            .replace(' ', "-")
-            .replace(|c: char| !c.is_alphanumeric() && c != '-', "")
-    });
+    font: &TextFont,
+    layout: &TextLayout,
+    split: TextChains,
+) -> (ChainGroups, String) {
+    // Accept a literal `\n` as a line break, for shells without $'...'
+    let text = &text.replace("\\n", "\n");
+    let (groups, missing) = match font {
+        TextFont::Outline(font, flatten) => {
+            let outline = svg_path_of_text(text, font, layout).unwrap_or_else(|e| {
+                eprintln!("Error rendering text: {e}");
+                std::process::exit(1);
+            });
+            let groups = match split {
+                TextChains::Single => {
+                    let paths = subpaths_of_svg_path(&outline.path);
+                    vec![(join_outlines(paths, *flatten), (0.0, 0.0))]
+                }
+                _ => group_glyphs(&outline.glyphs, split)
+                    .into_iter()
+                    .map(|(paths, origin)| (join_outlines(paths, *flatten), origin))
+                    .collect(),
+            };
+            (groups, outline.missing)
+        }
+        TextFont::Stroke(font, open_curve) => {
+            let strokes = stroke_text(text, font, layout);
+            let groups = match split {
+                TextChains::Single => {
+                    vec![(close_open_paths(&strokes.strokes, *open_curve), (0.0, 0.0))]
+                }
+                _ => group_glyphs(&strokes.glyphs, split)
+                    .into_iter()
+                    .map(|(paths, origin)| (close_open_paths(&paths, *open_curve), origin))
+                    .collect(),
+            };
+            (groups, strokes.missing)
+        }
+    };
+    if !missing.is_empty() {
+        let chars: String = missing.iter().collect();
+        eprintln!("Warning: no glyph in the font for: {chars}");
+    }
 
-    let config_path = config
-        .map(|s| s.to_string())
-        .unwrap_or_else(|| format!("{output_stem}-config.yml"));
-    let opts = if Path::new(&config_path).exists() {
-        let config_yaml = fs::read_to_string(&config_path).unwrap_or_else(|e| {
-            eprintln!("Error reading {config_path}: {e}");
-            std::process::exit(1);
-        });
-        serde_yaml::from_str(&config_yaml).unwrap_or_else(|e| {
-            eprintln!("Error parsing {config_path}: {e}");
-            std::process::exit(1);
-        })
-    } else if config.is_some() {
-        eprintln!("Config file not found: {config_path}");
-        std::process::exit(1);
+    let stem = text
+        .to_lowercase()
+        .replace([' ', '\n'], "-")
+        .replace(|c: char| !c.is_alphanumeric() && c != '-', "");
+
+    (groups, stem)
+}
+
+/// Closed outlines as one contour, flattened to the visible outline first if
+/// asked.
+fn join_outlines(paths: Vec<Contour>, flatten: bool) -> Contour {
+    let paths = if flatten {
+        boolean::flatten(&paths)
     } else {
-        EmbedOptions::default()
+        paths
     };
-
-    (contour, opts, output_stem)
+    Contour {
+        points: paths.into_iter().flat_map(|c| c.points).collect(),
+    }
 }
 
 fn load_svg(
     file: &str,
-    config: Option<&str>,
-    output: Option<&str>,
-) -> (Contour, EmbedOptions, String) {
+    flatten: bool,
+    strokes: StrokeMode,
+    open_curve: OpenCurve,
+) -> (Contour, String) {
     let input_path = Path::new(file);
     let svg_content = fs::read_to_string(input_path).unwrap_or_else(|e| {
         eprintln!("Error reading {}: {}", input_path.display(), e);
         std::process::exit(1);
     });
 
-    // Extract all d="..." attributes from <path> elements
-    let mut all_points = Vec::new();
-    for caps in svg_content.match_indices(" d=\"") {
-        let start = caps.0 + 4;
-        if let Some(end) = svg_content[start..].find('"') {
-            let d = &svg_content[start..start + end];
-            all_points.extend(points_of_svg_path(d));
-        }
+    let items = parse_svg(&svg_content);
+    let (outlines, centerlines) = svg_contours(&items, strokes);
+    let mut contour = join_outlines(outlines, flatten);
+    if !centerlines.is_empty() {
+        contour
+            .points
+            .extend(close_open_paths(&centerlines, open_curve).points);
     }
-
-    if all_points.is_empty() {
+    if contour.points.is_empty() {
         eprintln!("No path data found in {}", input_path.display());
         std::process::exit(1);
     }
 
-    let contour = Contour { points: all_points };
+    (contour, stem_of_file(file))
+}
+
+/// Read a scene and decompose each of its shapes into placed, styled chains.
+fn load_scene(
+    file: &str,
+    config: Option<&str>,
+    output: Option<&str>,
+) -> (Vec<Chain>, ChainTiming, EmbedOptions, String) {
+    let input_path = Path::new(file);
+    let text = fs::read_to_string(input_path).unwrap_or_else(|e| {
+        eprintln!("Error reading {}: {}", input_path.display(), e);
+        std::process::exit(1);
+    });
+    let scene = Scene::parse(&text).unwrap_or_else(|e| {
+        eprintln!("Invalid scene: {e}");
+        std::process::exit(1);
+    });
 
     let stem = input_path.file_stem().unwrap().to_str().unwrap();
-    let config_path = config.map(|s| s.to_string()).unwrap_or_else(|| {
-        input_path
-            .with_file_name(format!("{stem}-config.yml"))
-            .to_str()
-            .unwrap()
-            .to_string()
+    let default_config = input_path.with_file_name(format!("{stem}-config.yml"));
+    let opts = load_config(config, default_config.to_str().unwrap());
+    opts.validate().unwrap_or_else(|e| {
+        eprintln!("Invalid config: {e}");
+        std::process::exit(1);
     });
-    let opts = if Path::new(&config_path).exists() {
-        let config_yaml = fs::read_to_string(&config_path).unwrap_or_else(|e| {
+
+    let mut chains = Vec::new();
+    for (i, shape) in scene.shapes.iter().enumerate() {
+        let args = std::iter::once("circles-sketch".to_string()).chain(shape.args());
+        let command = match Cli::try_parse_from(args) {
+            Ok(cli) if !is_input(&cli.command) => {
+                eprintln!("Invalid scene: shapes[{i}]: not an input subcommand");
+                std::process::exit(1);
+            }
+            Ok(cli) => cli.command,
+            Err(e) => {
+                eprintln!("Invalid scene: shapes[{i}]: {e}");
+                std::process::exit(1);
+            }
+        };
+        let (input, files) = load_input(command);
+        // The scene's own options name the config and the output
+        if files.config.is_some() || files.output.is_some() {
+            eprintln!("Invalid scene: shapes[{i}]: --config and -o are not allowed in a shape");
+            std::process::exit(1);
+        }
+        let mut groups = match input {
+            Input::Single(contour) => vec![(contour, (0.0, 0.0))],
+            Input::Chains(groups, _) => groups,
+        };
+        preprocess(&mut groups, &opts);
+        for (contour, offset) in shape.place(groups) {
+            chains.push(decompose_chain(contour, offset, shape.style(), &opts));
+        }
+    }
+
+    let output_stem = output
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| input_path.with_extension("").to_str().unwrap().to_string());
+
+    (chains, scene.timing, opts, output_stem)
+}
+
+/// Load the config at `config`, or at `default_path` if that file exists,
+/// falling back to the default options.
+fn load_config(config: Option<&str>, default_path: &str) -> EmbedOptions {
+    let config_path = config.unwrap_or(default_path);
+    if Path::new(config_path).exists() {
+        let config_yaml = fs::read_to_string(config_path).unwrap_or_else(|e| {
             eprintln!("Error reading {config_path}: {e}");
             std::process::exit(1);
         });
@@ -298,13 +1517,118 @@ fn load_svg(
         std::process::exit(1);
     } else {
         EmbedOptions::default()
+    }
+}
+
+fn parse_expr(what: &str, s: &str) -> Expr {
+    Expr::parse(s).unwrap_or_else(|e| {
+        eprintln!("Error parsing {what}: {e}");
+        std::process::exit(1);
+    })
+}
+
+fn load_equation(
+    form: CurveForm,
+    t_min: &str,
+    t_max: &str,
+    params: &[String],
+    samples: usize,
+) -> Contour {
+    let params: HashMap<String, f64> = params
+        .iter()
+        .map(|p| {
+            parse_param(p).unwrap_or_else(|e| {
+                eprintln!("Error parsing parameter: {e}");
+                std::process::exit(1);
+            })
+        })
+        .collect();
+    let bound = |what: &str, s: &str| {
+        let e = parse_expr(what, s);
+        e.check_bound(&params).unwrap_or_else(|err| {
+            eprintln!("Error in {what}: {err}");
+            std::process::exit(1);
+        });
+        e.eval(&params)
     };
+    let t_min = bound("--t-min", t_min);
+    let t_max = bound("--t-max", t_max);
 
-    let output_stem = output
-        .map(|s| s.to_string())
-        .unwrap_or_else(|| input_path.with_extension("").to_str().unwrap().to_string());
+    let curve = EquationCurve::new(form, t_min, t_max, params).unwrap_or_else(|e| {
+        eprintln!("Invalid equation: {e}");
+        std::process::exit(1);
+    });
+    curve.sample(samples.max(2)).unwrap_or_else(|e| {
+        eprintln!("Invalid equation: {e}");
+        std::process::exit(1);
+    })
+}
+
+fn load_geojson(file: &str, geo: &GeoOptions, open_curve: OpenCurve) -> Contour {
+    let input_path = Path::new(file);
+    let text = fs::read_to_string(input_path).unwrap_or_else(|e| {
+        eprintln!("Error reading {}: {}", input_path.display(), e);
+        std::process::exit(1);
+    });
+    let parts = parse_geojson(&text, geo).unwrap_or_else(|e| {
+        eprintln!("Error parsing {}: {e}", input_path.display());
+        std::process::exit(1);
+    });
+    if parts.is_empty() {
+        eprintln!("No matching geometry found in {}", input_path.display());
+        std::process::exit(1);
+    }
+    println!("Read {} part(s)", parts.len());
+
+    let mut points = Vec::new();
+    for part in &parts {
+        if part.closed {
+            points.extend_from_slice(&part.contour.points);
+        } else {
+            points.extend(close_open_paths(std::slice::from_ref(&part.contour), open_curve).points);
+        }
+    }
+    Contour { points }
+}
 
-    (contour, opts, output_stem)
+/// With `centerline`, strokes are traced along the skeleton and closed with
+/// the given strategy; otherwise region outlines are traced.
+fn load_image(file: &str, trace: &TraceOptions, centerline: Option<OpenCurve>) -> Contour {
+    let input_path = Path::new(file);
+    let image = read_image(input_path).unwrap_or_else(|e| {
+        eprintln!("Error reading image: {e}");
+        std::process::exit(1);
+    });
+    let contours = match centerline {
+        Some(_) => trace_centerlines(&image, trace),
+        None => trace_image(&image, trace),
+    };
+    if contours.is_empty() {
+        eprintln!(
+            "No contour found in {} (try --threshold, --invert or --min-area)",
+            input_path.display()
+        );
+        std::process::exit(1);
+    }
+    println!(
+        "Traced {} contour(s), {} points",
+        contours.len(),
+        contours.iter().map(|c| c.points.len()).sum::<usize>()
+    );
+    match centerline {
+        Some(strategy) => close_open_paths(&contours, strategy),
+        None => Contour {
+            points: contours.into_iter().flat_map(|c| c.points).collect(),
+        },
+    }
+}
+
+fn load_shape(shape: &Shape, samples: usize) -> Contour {
+    shape.validate().unwrap_or_else(|e| {
+        eprintln!("Invalid shape: {e}");
+        std::process::exit(1);
+    });
+    shape.contour(samples)
 }
 
 fn init_config(file: &str) {
@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+
+use crate::contour::{BoundingBox, Contour};
+
+/// Boolean operation on the regions filled by two sets of contours.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum BooleanOp {
+    /// Covered by either operand
+    Union,
+    /// Covered by both operands
+    Intersection,
+    /// Covered by the first operand and not the second
+    Difference,
+    /// Covered by exactly one operand
+    Xor,
+}
+
+impl BooleanOp {
+    fn keeps(self, a: bool, b: bool) -> bool {
+        match self {
+            BooleanOp::Union => a || b,
+            BooleanOp::Intersection => a && b,
+            BooleanOp::Difference => a && !b,
+            BooleanOp::Xor => a != b,
+        }
+    }
+}
+
+type Point = (f64, f64);
+
+fn sub(a: Point, b: Point) -> Point {
+    (a.0 - b.0, a.1 - b.1)
+}
+
+fn cross(a: Point, b: Point) -> f64 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+fn dot(a: Point, b: Point) -> f64 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+/// Distances below this fraction of the operands' extent count as zero.
+const TOLERANCE: f64 = 1e-9;
+
+/// An edge of an operand's contour.
+struct Edge {
+    from: Point,
+    to: Point,
+    operand: usize,
+}
+
+/// Record the points where edges `e` and `f` touch: an end point of one
+/// lying on the other (T-junctions and collinear overlaps), or a proper
+/// crossing.
+fn touch_points(e: &Edge, f: &Edge, tolerance: f64, at_e: &mut Vec<Point>, at_f: &mut Vec<Point>) {
+    let on = |p: Point, edge: &Edge| {
+        let r = sub(edge.to, edge.from);
+        let len2 = dot(r, r);
+        let t = dot(sub(p, edge.from), r) / len2;
+        let inside = t > 0.0 && t < 1.0;
+        let away = |q: Point| (p.0 - q.0).hypot(p.1 - q.1) > tolerance;
+        inside
+            && cross(r, sub(p, edge.from)).abs() <= tolerance * len2.sqrt()
+            && away(edge.from)
+            && away(edge.to)
+    };
+    let mut touched = false;
+    for p in [f.from, f.to] {
+        if on(p, e) {
+            at_e.push(p);
+            touched = true;
+        }
+    }
+    for p in [e.from, e.to] {
+        if on(p, f) {
+            at_f.push(p);
+            touched = true;
+        }
+    }
+    if touched {
+        return;
+    }
+    // Signed distances of each edge's end points from the other's line
+    let side = |edge: &Edge, p: Point| {
+        let r = sub(edge.to, edge.from);
+        cross(r, sub(p, edge.from)) / dot(r, r).sqrt()
+    };
+    let (d0, d1) = (side(e, f.from), side(e, f.to));
+    let (d2, d3) = (side(f, e.from), side(f, e.to));
+    let strictly_apart =
+        |a: f64, b: f64| (a > tolerance && b < -tolerance) || (a < -tolerance && b > tolerance);
+    if strictly_apart(d0, d1) && strictly_apart(d2, d3) {
+        let t = d0 / (d0 - d1);
+        let x = (
+            f.from.0 + (f.to.0 - f.from.0) * t,
+            f.from.1 + (f.to.1 - f.from.1) * t,
+        );
+        at_e.push(x);
+        at_f.push(x);
+    }
+}
+
+/// Split `edges` wherever they touch, returning for each edge its points
+/// from start to end.
+fn split_edges(edges: &[Edge], tolerance: f64) -> Vec<Vec<Point>> {
+    let mut splits: Vec<Vec<Point>> = vec![vec![]; edges.len()];
+    let min_x = |e: &Edge| e.from.0.min(e.to.0);
+    let max_x = |e: &Edge| e.from.0.max(e.to.0);
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    order.sort_by(|&i, &j| min_x(&edges[i]).total_cmp(&min_x(&edges[j])));
+    // Sweep along x, comparing each edge with those starting within its span
+    for (n, &i) in order.iter().enumerate() {
+        let e = &edges[i];
+        let (e_lo, e_hi) = (e.from.1.min(e.to.1), e.from.1.max(e.to.1));
+        for &j in &order[n + 1..] {
+            let f = &edges[j];
+            if min_x(f) > max_x(e) + tolerance {
+                break;
+            }
+            if f.from.1.max(f.to.1) < e_lo - tolerance || f.from.1.min(f.to.1) > e_hi + tolerance {
+                continue;
+            }
+            let (mut at_e, mut at_f) = (vec![], vec![]);
+            touch_points(e, f, tolerance, &mut at_e, &mut at_f);
+            splits[i].extend(at_e);
+            splits[j].extend(at_f);
+        }
+    }
+    edges
+        .iter()
+        .zip(splits)
+        .map(|(e, mut points)| {
+            let r = sub(e.to, e.from);
+            points.sort_by(|&p, &q| dot(sub(p, e.from), r).total_cmp(&dot(sub(q, e.from), r)));
+            points.insert(0, e.from);
+            points.push(e.to);
+            points
+        })
+        .collect()
+}
+
+/// The split edges of both operands, merged where they coincide: each
+/// segment joins two vertices and counts, per operand, how many times it is
+/// traversed from its first vertex to its second minus the other way.
+struct Arrangement {
+    vertices: Vec<Point>,
+    segments: Vec<(usize, usize, [i32; 2])>,
+}
+
+impl Arrangement {
+    fn new(edges: &[Edge], tolerance: f64) -> Arrangement {
+        let mut vertices = Vec::new();
+        let mut ids: HashMap<(i64, i64), usize> = HashMap::new();
+        let mut vertex = |p: Point| {
+            let key = (
+                (p.0 / tolerance).round() as i64,
+                (p.1 / tolerance).round() as i64,
+            );
+            *ids.entry(key).or_insert_with(|| {
+                vertices.push(p);
+                vertices.len() - 1
+            })
+        };
+        let mut counts: HashMap<(usize, usize), [i32; 2]> = HashMap::new();
+        for (edge, points) in edges.iter().zip(split_edges(edges, tolerance)) {
+            for pair in points.windows(2) {
+                let (u, v) = (vertex(pair[0]), vertex(pair[1]));
+                if u != v {
+                    let count = &mut counts.entry((u.min(v), u.max(v))).or_default()[edge.operand];
+                    *count += if u < v { 1 } else { -1 };
+                }
+            }
+        }
+        let mut segments: Vec<_> = counts
+            .into_iter()
+            .filter(|(_, c)| *c != [0, 0])
+            .map(|((u, v), c)| (u, v, c))
+            .collect();
+        segments.sort_by_key(|&(u, v, _)| (u, v));
+        Arrangement { vertices, segments }
+    }
+
+    fn ends(&self, i: usize) -> (Point, Point) {
+        let (u, v, _) = self.segments[i];
+        (self.vertices[u], self.vertices[v])
+    }
+}
+
+/// Segments grouped by the horizontal (or vertical) bands they span, to
+/// find those crossing a horizontal (or vertical) ray.
+struct Bands {
+    axis: usize,
+    min: f64,
+    size: f64,
+    bands: Vec<Vec<usize>>,
+}
+
+impl Bands {
+    fn new(arrangement: &Arrangement, axis: usize) -> Bands {
+        let coord = |p: Point| if axis == 0 { p.0 } else { p.1 };
+        let n = arrangement.segments.len();
+        let count = ((n as f64).sqrt() as usize).max(1);
+        let (min, max) = arrangement
+            .vertices
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &p| {
+                (lo.min(coord(p)), hi.max(coord(p)))
+            });
+        let size = ((max - min) / count as f64).max(f64::MIN_POSITIVE);
+        let mut bands = Bands {
+            axis,
+            min,
+            size,
+            bands: vec![vec![]; count],
+        };
+        for i in 0..n {
+            let (a, b) = arrangement.ends(i);
+            let (lo, hi) = (coord(a).min(coord(b)), coord(a).max(coord(b)));
+            for band in bands.band(lo)..=bands.band(hi) {
+                bands.bands[band].push(i);
+            }
+        }
+        bands
+    }
+
+    fn band(&self, at: f64) -> usize {
+        (((at - self.min) / self.size) as usize).min(self.bands.len() - 1)
+    }
+
+    fn at(&self, p: Point) -> &[usize] {
+        &self.bands[self.band(if self.axis == 0 { p.0 } else { p.1 })]
+    }
+}
+
+/// Winding numbers of both operands just left of segment `i` (left of the
+/// direction from its first vertex to its second), by casting a ray from
+/// its midpoint away from it.
+fn winding_left(arrangement: &Arrangement, bands: &[Bands; 2], i: usize) -> [i32; 2] {
+    let (u, v) = arrangement.ends(i);
+    let m = ((u.0 + v.0) / 2.0, (u.1 + v.1) / 2.0);
+    let normal = (u.1 - v.1, v.0 - u.0);
+    // An axis-aligned ray into the left side never meets the segment's line
+    // again; rays along x use bands in y, and the other way round
+    let (d, candidates) = if normal.0.abs() >= normal.1.abs() {
+        ((normal.0.signum(), 0.0), bands[1].at(m))
+    } else {
+        ((0.0, normal.1.signum()), bands[0].at(m))
+    };
+    let mut winding = [0, 0];
+    for &j in candidates {
+        if j == i {
+            continue;
+        }
+        let (a, b) = arrangement.ends(j);
+        let (sa, sb) = (cross(d, sub(a, m)), cross(d, sub(b, m)));
+        if (sa <= 0.0) == (sb <= 0.0) {
+            continue;
+        }
+        let (ta, tb) = (dot(d, sub(a, m)), dot(d, sub(b, m)));
+        if ta + (tb - ta) * sa / (sa - sb) <= 0.0 {
+            continue;
+        }
+        let sign = if sb > 0.0 { 1 } else { -1 };
+        let counts = arrangement.segments[j].2;
+        winding[0] += sign * counts[0];
+        winding[1] += sign * counts[1];
+    }
+    winding
+}
+
+/// Join directed boundary segments into closed contours, turning as far
+/// left as possible where several meet so that loops touching at a point
+/// stay separate.
+fn stitch(vertices: &[Point], boundary: &[(usize, usize)]) -> Vec<Contour> {
+    let mut leaving: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (n, &(from, _)) in boundary.iter().enumerate() {
+        leaving.entry(from).or_default().push(n);
+    }
+    let mut used = vec![false; boundary.len()];
+    let mut contours = Vec::new();
+    for start in 0..boundary.len() {
+        if used[start] {
+            continue;
+        }
+        let first = boundary[start].0;
+        let mut points = vec![vertices[first]];
+        let mut current = start;
+        loop {
+            used[current] = true;
+            let (from, to) = boundary[current];
+            points.push(vertices[to]);
+            if to == first {
+                break;
+            }
+            let incoming = sub(vertices[to], vertices[from]);
+            let turn = |n: &usize| {
+                let outgoing = sub(vertices[boundary[*n].1], vertices[to]);
+                cross(incoming, outgoing).atan2(dot(incoming, outgoing))
+            };
+            let next = leaving[&to]
+                .iter()
+                .filter(|&&n| !used[n])
+                .max_by(|a, b| turn(a).total_cmp(&turn(b)));
+            match next {
+                Some(&n) => current = n,
+                None => break,
+            }
+        }
+        if points.len() >= 4 {
+            contours.push(Contour { points });
+        }
+    }
+    contours
+}
+
+/// Longest edge of the result, as a fraction of the operands' extent.
+/// Straight runs are split so that points stay evenly spread along the
+/// outline, which keeps long edges from looking like jumps between subpaths.
+const MAX_EDGE: f64 = 1.0 / 256.0;
+
+fn subdivide(points: &[(f64, f64)], max_len: f64) -> Vec<(f64, f64)> {
+    let mut out = Vec::with_capacity(points.len());
+    for w in points.windows(2) {
+        let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+        let n = ((x1 - x0).hypot(y1 - y0) / max_len).ceil().max(1.0) as usize;
+        for i in 0..n {
+            let t = i as f64 / n as f64;
+            out.push((x0 + (x1 - x0) * t, y0 + (y1 - y0) * t));
+        }
+    }
+    out.extend(points.last());
+    out
+}
+
+/// Apply `op` to the regions filled by `a` and `b` (nonzero winding rule),
+/// returning the boundaries of the result as closed contours (first point
+/// repeated at the end), each with the region on its left (clockwise on
+/// screen, where y points down). Contours are closed implicitly.
+///
+/// The result is exact up to rounding: edges are split where they cross or
+/// touch, and each piece is kept if the operation holds on one side of it
+/// and not the other.
+pub fn boolean(a: &[Contour], b: &[Contour], op: BooleanOp) -> Vec<Contour> {
+    let Some(bbox) =
+        BoundingBox::of_points(a.iter().chain(b).flat_map(|c| c.points.iter().copied()))
+    else {
+        return vec![];
+    };
+    let extent = bbox.width().max(bbox.height());
+    if !(extent > 0.0 && extent.is_finite()) {
+        return vec![];
+    }
+    let tolerance = extent * TOLERANCE;
+    let mut edges = Vec::new();
+    for (operand, contours) in [a, b].into_iter().enumerate() {
+        for c in contours {
+            let n = c.points.len();
+            for k in 0..n {
+                let (from, to) = (c.points[k], c.points[(k + 1) % n]);
+                if from != to {
+                    edges.push(Edge { from, to, operand });
+                }
+            }
+        }
+    }
+    let arrangement = Arrangement::new(&edges, tolerance);
+    let bands = [Bands::new(&arrangement, 0), Bands::new(&arrangement, 1)];
+    let mut boundary = Vec::new();
+    for (i, &(u, v, counts)) in arrangement.segments.iter().enumerate() {
+        let left = winding_left(&arrangement, &bands, i);
+        let right = [left[0] - counts[0], left[1] - counts[1]];
+        let inside = |w: [i32; 2]| op.keeps(w[0] != 0, w[1] != 0);
+        match (inside(left), inside(right)) {
+            (true, false) => boundary.push((u, v)),
+            (false, true) => boundary.push((v, u)),
+            _ => {}
+        }
+    }
+    stitch(&arrangement.vertices, &boundary)
+        .into_iter()
+        .map(|c| Contour {
+            points: subdivide(&c.points, extent * MAX_EDGE),
+        })
+        .collect()
+}
+
+pub fn union(a: &[Contour], b: &[Contour]) -> Vec<Contour> {
+    boolean(a, b, BooleanOp::Union)
+}
+
+pub fn intersection(a: &[Contour], b: &[Contour]) -> Vec<Contour> {
+    boolean(a, b, BooleanOp::Intersection)
+}
+
+pub fn difference(a: &[Contour], b: &[Contour]) -> Vec<Contour> {
+    boolean(a, b, BooleanOp::Difference)
+}
+
+/// Replace overlapping outlines by the boundaries of the region they fill,
+/// as seen when the outlines are filled with the nonzero rule: inner
+/// contours of overlapping glyphs or shapes disappear, holes are kept.
+pub fn flatten(contours: &[Contour]) -> Vec<Contour> {
+    union(contours, &[])
+}
@@ -1,5 +1,9 @@
-use crate::contour::{Contour, FourierDecomposition};
-use crate::model::{EmbedOptions, HarmonicSteps, WhenToShow};
+use crate::contour::{
+    AxisSeries, BoundingBox, Contour, EllipseSeries, FourierDecomposition, RealCoeff,
+    jump_threshold,
+};
+use crate::model::{EmbedOptions, HarmonicSteps, Visualization, WhenToShow};
+use serde::Deserialize;
 
 fn format_js_array(v: &[usize]) -> String {
     let items: Vec<String> = v.iter().map(|n| n.to_string()).collect();
@@ -15,15 +19,7 @@ pub fn svg_path_of_contour(contour: &Contour) -> String {
     if contour.points.is_empty() {
         return String::new();
     }
-    // Compute average segment length to detect jumps between sub-paths
-    let mut total_dist = 0.0;
-    for i in 1..contour.points.len() {
-        let (x0, y0) = contour.points[i - 1];
-        let (x1, y1) = contour.points[i];
-        total_dist += ((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt();
-    }
-    let avg_dist = total_dist / (contour.points.len() - 1) as f64;
-    let jump_threshold = avg_dist * 5.0;
+    let jump_threshold = jump_threshold(&contour.points);
 
     let mut parts = Vec::new();
     let (x, y) = contour.points[0];
@@ -42,8 +38,28 @@ pub fn svg_path_of_contour(contour: &Contour) -> String {
 }
 
 pub fn points_of_svg_path(svg_path: &str) -> Vec<(f64, f64)> {
+    parse_svg_path(svg_path).0
+}
+
+/// Points of each subpath (started by `M` or `m`) of an SVG path.
+pub fn subpaths_of_svg_path(svg_path: &str) -> Vec<Contour> {
+    let (points, starts) = parse_svg_path(svg_path);
+    let mut bounds = starts;
+    bounds.push(points.len());
+    bounds
+        .windows(2)
+        .filter(|w| w[1] > w[0])
+        .map(|w| Contour {
+            points: points[w[0]..w[1]].to_vec(),
+        })
+        .collect()
+}
+
+/// Sampled points of an SVG path, and the index where each subpath starts.
+fn parse_svg_path(svg_path: &str) -> (Vec<(f64, f64)>, Vec<usize>) {
     let tokens = tokenize_svg_path(svg_path);
     let mut points = Vec::new();
+    let mut starts = vec![0];
     let mut cx = 0.0_f64;
     let mut cy = 0.0_f64;
     let mut start_x = 0.0_f64;
@@ -59,6 +75,7 @@ pub fn points_of_svg_path(svg_path: &str) -> Vec<(f64, f64)> {
                 cy = y;
                 start_x = x;
                 start_y = y;
+                starts.push(points.len());
                 points.push((cx, cy));
                 i += 3;
                 // Implicit L for subsequent pairs
@@ -80,6 +97,7 @@ pub fn points_of_svg_path(svg_path: &str) -> Vec<(f64, f64)> {
                 cy += dy;
                 start_x = cx;
                 start_y = cy;
+                starts.push(points.len());
                 points.push((cx, cy));
                 i += 3;
                 while i < tokens.len()
@@ -221,6 +239,35 @@ pub fn points_of_svg_path(svg_path: &str) -> Vec<(f64, f64)> {
                     i += 4;
                 }
             }
+            "A" | "a" => {
+                let relative = tokens[i] == "a";
+                i += 1;
+                while i + 6 < tokens.len()
+                    && tokens[i].starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.')
+                {
+                    let v: Vec<f64> = tokens[i..i + 7]
+                        .iter()
+                        .map(|t| t.parse::<f64>().unwrap())
+                        .collect();
+                    let (x, y) = if relative {
+                        (cx + v[5], cy + v[6])
+                    } else {
+                        (v[5], v[6])
+                    };
+                    sample_arc(
+                        &mut points,
+                        (cx, cy),
+                        (v[0], v[1]),
+                        v[2],
+                        v[3] != 0.0,
+                        v[4] != 0.0,
+                        (x, y),
+                    );
+                    cx = x;
+                    cy = y;
+                    i += 7;
+                }
+            }
             "Z" | "z" => {
                 cx = start_x;
                 cy = start_y;
@@ -232,7 +279,7 @@ pub fn points_of_svg_path(svg_path: &str) -> Vec<(f64, f64)> {
             }
         }
     }
-    points
+    (points, starts)
 }
 
 fn tokenize_svg_path(path: &str) -> Vec<String> {
@@ -245,22 +292,36 @@ fn tokenize_svg_path(path: &str) -> Vec<String> {
             tokens.push(ch.to_string());
             chars.next();
         } else {
-            // Number (possibly negative)
+            // Number (possibly negative). A second dot starts the next
+            // number, as in `1.5.5`
             let mut num = String::new();
-            if ch == '-' {
+            if ch == '-' || ch == '+' {
                 num.push(ch);
                 chars.next();
             }
+            let mut seen_dot = false;
             while let Some(&c) = chars.peek() {
-                if c.is_ascii_digit() || c == '.' {
+                if c.is_ascii_digit() || (c == '.' && !seen_dot) {
+                    seen_dot |= c == '.';
+                    num.push(c);
+                    chars.next();
+                } else if (c == 'e' || c == 'E') && num.ends_with(|d: char| d.is_ascii_digit()) {
+                    // Exponent, e.g. `1e-3`
                     num.push(c);
                     chars.next();
+                    if let Some(&sign @ ('-' | '+')) = chars.peek() {
+                        num.push(sign);
+                        chars.next();
+                    }
                 } else {
                     break;
                 }
             }
-            if !num.is_empty() && num != "-" {
+            if !num.is_empty() && num != "-" && num != "+" {
                 tokens.push(num);
+            } else if num.is_empty() {
+                // Skip a character that starts no token
+                chars.next();
             }
         }
     }
@@ -289,6 +350,58 @@ fn sample_cubic(
     }
 }
 
+/// Sample an elliptical arc given in SVG endpoint form, following the SVG
+/// implementation notes for the conversion to center form.
+fn sample_arc(
+    points: &mut Vec<(f64, f64)>,
+    (x1, y1): (f64, f64),
+    (rx, ry): (f64, f64),
+    rotation: f64,
+    large_arc: bool,
+    sweep: bool,
+    (x2, y2): (f64, f64),
+) {
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    if rx == 0.0 || ry == 0.0 || (x1 == x2 && y1 == y2) {
+        points.push((x2, y2));
+        return;
+    }
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let (dx, dy) = ((x1 - x2) / 2.0, (y1 - y2) / 2.0);
+    let (x1p, y1p) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+    // Scale radii up when they cannot span the endpoints
+    let lambda = (x1p / rx).powi(2) + (y1p / ry).powi(2);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let num = rx * rx * ry * ry - rx * rx * y1p * y1p - ry * ry * x1p * x1p;
+    let den = rx * rx * y1p * y1p + ry * ry * x1p * x1p;
+    let sign = if large_arc != sweep { 1.0 } else { -1.0 };
+    let coef = sign * (num / den).max(0.0).sqrt();
+    let (cxp, cyp) = (coef * rx * y1p / ry, -coef * ry * x1p / rx);
+    let (cx, cy) = (
+        cos * cxp - sin * cyp + (x1 + x2) / 2.0,
+        sin * cxp + cos * cyp + (y1 + y2) / 2.0,
+    );
+    let angle = |ux: f64, uy: f64| uy.atan2(ux);
+    let theta1 = angle((x1p - cxp) / rx, (y1p - cyp) / ry);
+    let mut delta = angle((-x1p - cxp) / rx, (-y1p - cyp) / ry) - theta1;
+    let tau = std::f64::consts::TAU;
+    if sweep && delta < 0.0 {
+        delta += tau;
+    } else if !sweep && delta > 0.0 {
+        delta -= tau;
+    }
+    // About one point per 11.25 degrees
+    let steps = (delta.abs() / (tau / 32.0)).ceil().max(2.0) as usize;
+    for i in 1..=steps {
+        let t = theta1 + delta * i as f64 / steps as f64;
+        let (ex, ey) = (rx * t.cos(), ry * t.sin());
+        points.push((cx + cos * ex - sin * ey, cy + sin * ex + cos * ey));
+    }
+}
+
 fn sample_quad(points: &mut Vec<(f64, f64)>, x0: f64, y0: f64, x1: f64, y1: f64, x2: f64, y2: f64) {
     let steps = 8;
     for i in 1..=steps {
@@ -311,8 +424,79 @@ pub fn html_of_svg_path_with_fourier(
     opts: &EmbedOptions,
     command: Option<&str>,
 ) -> String {
-    let p = compute_params(svg_path, points, fourier, &opts.steps);
-    let inner = inner_content_full(&p, opts, command);
+    let chains = single_chain(points, fourier);
+    let p = compute_params(svg_path, points, &chains, opts);
+    full_page(&p, opts, command)
+}
+
+pub fn embed_html_of_svg_path_with_fourier(
+    svg_path: &str,
+    points: &[(f64, f64)],
+    fourier: Option<&FourierDecomposition>,
+    opts: &EmbedOptions,
+) -> String {
+    let chains = single_chain(points, fourier);
+    let p = compute_params(svg_path, points, &chains, opts);
+    embed_page(&p, opts)
+}
+
+/// One epicycle chain: a contour drawn by its own Fourier decomposition,
+/// relative to `offset`.
+pub struct Chain {
+    pub contour: Contour,
+    pub fourier: FourierDecomposition,
+    pub offset: (f64, f64),
+    pub style: ChainStyle,
+}
+
+/// Per-chain overrides of the page options.
+#[derive(Default)]
+pub struct ChainStyle {
+    /// Trace colors cycled per loop, instead of `trace_colors`
+    pub trace_colors: Option<Vec<String>>,
+    /// Colors of the epicycle circles, cycled per term
+    pub circle_colors: Option<Vec<String>>,
+    /// Harmonics drawn at each loop. The page schedule still sets the loop
+    /// speed; this chain's speeds are ignored
+    pub steps: Option<HarmonicSteps>,
+    /// Fraction of a turn added to the chain's time, which moves the point
+    /// where it starts drawing
+    pub phase: f64,
+}
+
+/// How several chains share one animation loop.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum ChainTiming {
+    /// One after the other, each for a share of the loop proportional to
+    /// its contour length
+    Sequential,
+    /// All at the same time
+    Parallel,
+}
+
+/// Full page animating several epicycle chains on one canvas.
+pub fn html_of_chains(
+    chains: &[Chain],
+    timing: ChainTiming,
+    opts: &EmbedOptions,
+    command: Option<&str>,
+) -> String {
+    let (svg_path, points, chains) = chain_params(chains, timing);
+    let p = compute_params(&svg_path, &points, &chains, opts);
+    full_page(&p, opts, command)
+}
+
+/// Minimal embed page animating several epicycle chains on one canvas.
+pub fn embed_html_of_chains(chains: &[Chain], timing: ChainTiming, opts: &EmbedOptions) -> String {
+    let (svg_path, points, chains) = chain_params(chains, timing);
+    let p = compute_params(&svg_path, &points, &chains, opts);
+    embed_page(&p, opts)
+}
+
+fn full_page(p: &Params, opts: &EmbedOptions, command: Option<&str>) -> String {
+    let inner = inner_content_full(p, opts, command);
     format!(
         r#"<html>
 <head><title id="pageTitle">Harmonics: 2</title></head>
@@ -324,14 +508,8 @@ pub fn html_of_svg_path_with_fourier(
     )
 }
 
-pub fn embed_html_of_svg_path_with_fourier(
-    svg_path: &str,
-    points: &[(f64, f64)],
-    fourier: Option<&FourierDecomposition>,
-    opts: &EmbedOptions,
-) -> String {
-    let p = compute_params(svg_path, points, fourier, &opts.steps);
-    let inner = inner_content_embed(&p, opts);
+fn embed_page(p: &Params, opts: &EmbedOptions) -> String {
+    let inner = inner_content_embed(p, opts);
     format!(
         r#"<div style="display:flex;flex-direction:column;align-items:center;background:black;color:white">
 {inner}
@@ -340,10 +518,116 @@ pub fn embed_html_of_svg_path_with_fourier(
     )
 }
 
+/// A chain as passed to the page script: loop time `start..end` maps to the
+/// chain's own time 0..1, and `n` sizes its trace.
+pub(crate) struct ChainJs<'a> {
+    pub fourier: &'a FourierDecomposition,
+    pub style: Option<&'a ChainStyle>,
+    pub offset: (f64, f64),
+    pub start: f64,
+    pub end: f64,
+    pub n: usize,
+}
+
+fn single_chain<'a>(
+    points: &[(f64, f64)],
+    fourier: Option<&'a FourierDecomposition>,
+) -> Vec<ChainJs<'a>> {
+    fourier
+        .filter(|fd| !fd.coeffs.is_empty())
+        .map(|fd| ChainJs {
+            fourier: fd,
+            style: None,
+            offset: (0.0, 0.0),
+            start: 0.0,
+            end: 1.0,
+            n: points.len(),
+        })
+        .into_iter()
+        .collect()
+}
+
+/// Combined contour path and points (in canvas coordinates) and timed
+/// chains.
+pub(crate) fn chain_params(
+    chains: &[Chain],
+    timing: ChainTiming,
+) -> (String, Vec<(f64, f64)>, Vec<ChainJs<'_>>) {
+    let placed: Vec<Contour> = chains
+        .iter()
+        .map(|c| Contour {
+            points: c
+                .contour
+                .points
+                .iter()
+                .map(|&(x, y)| (x + c.offset.0, y + c.offset.1))
+                .collect(),
+        })
+        .collect();
+    let svg_path = placed
+        .iter()
+        .map(svg_path_of_contour)
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let points = placed
+        .iter()
+        .flat_map(|c| c.points.iter().copied())
+        .collect();
+
+    let timed = chains
+        .iter()
+        .zip(chain_windows(chains, timing))
+        .map(|(c, (s, e))| ChainJs {
+            fourier: &c.fourier,
+            style: Some(&c.style),
+            offset: c.offset,
+            start: s,
+            end: e,
+            n: c.contour.points.len(),
+        })
+        .filter(|c| !c.fourier.coeffs.is_empty() && c.end > c.start)
+        .collect();
+    (svg_path, points, timed)
+}
+
+/// Part `start..end` of the loop during which each chain draws.
+pub(crate) fn chain_windows(chains: &[Chain], timing: ChainTiming) -> Vec<(f64, f64)> {
+    let lengths: Vec<f64> = chains
+        .iter()
+        .map(|c| {
+            c.contour
+                .points
+                .windows(2)
+                .map(|w| ((w[1].0 - w[0].0).powi(2) + (w[1].1 - w[0].1).powi(2)).sqrt())
+                .sum()
+        })
+        .collect();
+    let total: f64 = lengths.iter().sum();
+    let mut start = 0.0;
+    lengths
+        .iter()
+        .enumerate()
+        .map(|(i, len)| match timing {
+            ChainTiming::Parallel => (0.0, 1.0),
+            ChainTiming::Sequential => {
+                let share = if total > 0.0 {
+                    len / total
+                } else {
+                    1.0 / chains.len() as f64
+                };
+                let s = start;
+                start += share;
+                // Avoid rounding gaps at the end of the loop
+                (s, if i + 1 == chains.len() { 1.0 } else { start })
+            }
+        })
+        .collect()
+}
+
 struct Params {
     svg_path: String,
-    points_array: String,
-    fourier_json: String,
+    chains_json: String,
     vb_x: f64,
     vb_y: f64,
     vb_size: f64,
@@ -351,70 +635,228 @@ struct Params {
     steps_str: String,
 }
 
-fn compute_params(
-    svg_path: &str,
-    points: &[(f64, f64)],
-    fourier: Option<&FourierDecomposition>,
-    steps: &HarmonicSteps,
-) -> Params {
-    let points_json: Vec<String> = points
-        .iter()
-        .map(|(x, y)| format!("[{},{}]", x, y))
-        .collect();
-    let points_array = format!("[{}]", points_json.join(","));
+/// Harmonics drawn at each loop for `steps`, as computed by the page script.
+pub(crate) fn harmonics_schedule(steps: &HarmonicSteps, max_nh: usize) -> Vec<usize> {
+    loop_schedule(steps, max_nh)
+        .into_iter()
+        .map(|(nh, _)| nh)
+        .collect()
+}
 
-    let (min_x, min_y, max_x, max_y) = if points.is_empty() {
-        (0.0, 0.0, 100.0, 100.0)
-    } else {
-        let mut min_x = f64::MAX;
-        let mut min_y = f64::MAX;
-        let mut max_x = f64::MIN;
-        let mut max_y = f64::MIN;
-        for &(x, y) in points {
-            if x < min_x {
-                min_x = x;
-            }
-            if y < min_y {
-                min_y = y;
-            }
-            if x > max_x {
-                max_x = x;
-            }
-            if y > max_y {
-                max_y = y;
-            }
-        }
-        (min_x, min_y, max_x, max_y)
+/// Harmonics and speed of each loop for `steps`, as computed by the page
+/// script.
+pub(crate) fn loop_schedule(steps: &HarmonicSteps, max_nh: usize) -> Vec<(usize, f64)> {
+    let ranges = &steps.ranges;
+    let Some(first) = ranges.first() else {
+        return vec![(1, 1.0)];
     };
-    let w = max_x - min_x;
-    let h = max_y - min_y;
+    let mut schedule = Vec::new();
+    let mut i = first.from;
+    while schedule.len() < 10000 {
+        let speed = ranges
+            .iter()
+            .find(|r| i >= r.from && i < r.to)
+            .map_or(1.0, |r| r.speed);
+        schedule.push((i.min(max_nh), speed));
+        if i >= max_nh {
+            break;
+        }
+        if let Some(r) = ranges.iter().find(|r| i >= r.from && i < r.to) {
+            i += r.step.max(1);
+        } else if let Some(next) = ranges.windows(2).find(|w| i >= w[0].to && i < w[1].from) {
+            i = next[1].from;
+        } else {
+            break;
+        }
+    }
+    schedule
+}
+
+/// Place two-axis chains: x chains are centered on the line `y = top` above
+/// the contours, y chains on the line `x = left` to their left. Returns
+/// `top`, `left` and the bounding box of everything drawn.
+fn axis_layout(
+    bbox: BoundingBox,
+    chains: &[ChainJs],
+    axes: &[AxisSeries],
+) -> (f64, f64, BoundingBox) {
+    let reach = |terms: &[RealCoeff]| terms.iter().map(|c| c.amp).sum::<f64>();
+    let reach_x = axes.iter().map(|a| reach(&a.x)).fold(0.0, f64::max);
+    let reach_y = axes.iter().map(|a| reach(&a.y)).fold(0.0, f64::max);
+    let gap = bbox.width().max(bbox.height()) * 0.1;
+    let top = bbox.min.1 - gap - reach_x;
+    let left = bbox.min.0 - gap - reach_y;
+    let mut corners = vec![bbox.min, bbox.max];
+    for (c, a) in chains.iter().zip(axes) {
+        let x = c.offset.0 + a.x0;
+        let y = c.offset.1 + a.y0;
+        corners.extend([
+            (x - reach_x, top - reach_x),
+            (x + reach_x, top + reach_x),
+            (left - reach_y, y - reach_y),
+            (left + reach_y, y + reach_y),
+        ]);
+    }
+    let extended = BoundingBox::of_points(corners).expect("non-empty");
+    (top, left, extended)
+}
+
+/// Square view `(x, y, size)` centered on `bbox`, with a 10% margin.
+pub(crate) fn view_box(bbox: &BoundingBox) -> (f64, f64, f64) {
+    let w = bbox.width();
+    let h = bbox.height();
     let size = if w > h { w } else { h };
     let padding = size * 0.1;
-    let vb_x = min_x - padding - (size - w) / 2.0;
-    let vb_y = min_y - padding - (size - h) / 2.0;
-    let vb_size = size + padding * 2.0;
-
-    let fourier_json = match fourier {
-        Some(fd) if !fd.coeffs.is_empty() => {
-            let terms: Vec<String> = fd
-                .coeffs
-                .iter()
-                .map(|c| {
-                    format!(
-                        "{{freq:{},re:{},im:{},r:{}}}",
-                        c.freq,
-                        c.re,
-                        c.im,
-                        c.radius()
-                    )
-                })
-                .collect();
-            format!("[{}]", terms.join(","))
-        }
-        _ => "null".to_string(),
+    let vb_x = bbox.min.0 - padding - (size - w) / 2.0;
+    let vb_y = bbox.min.1 - padding - (size - h) / 2.0;
+    (vb_x, vb_y, size + padding * 2.0)
+}
+
+/// Colors of the epicycle circles, as `fourierCircleColors` in the pages.
+pub(crate) const CIRCLE_COLORS: [&str; 6] =
+    ["blue", "green", "orange", "purple", "cyan", "magenta"];
+
+/// Square view of a page and the series drawn by its visualization, one
+/// per chain.
+pub(crate) struct Layout {
+    pub view: (f64, f64, f64),
+    pub axes: Vec<AxisSeries>,
+    /// `top` and `left` lines of the two-axis chains
+    pub axis_lines: Option<(f64, f64)>,
+    pub ellipses: Vec<EllipseSeries>,
+}
+
+pub(crate) fn layout(points: &[(f64, f64)], chains: &[ChainJs], opts: &EmbedOptions) -> Layout {
+    let mut bbox = BoundingBox::of_points(points.iter().copied()).unwrap_or(BoundingBox {
+        min: (0.0, 0.0),
+        max: (100.0, 100.0),
+    });
+    let axes: Vec<AxisSeries> = match opts.visualization {
+        Visualization::TwoAxis => chains.iter().map(|c| c.fourier.axis_series()).collect(),
+        Visualization::Epicycles | Visualization::Ellipses => vec![],
+    };
+    let ellipses: Vec<EllipseSeries> = match opts.visualization {
+        Visualization::Ellipses => chains.iter().map(|c| c.fourier.ellipses()).collect(),
+        Visualization::Epicycles | Visualization::TwoAxis => vec![],
+    };
+    let axis_lines = if axes.is_empty() {
+        None
+    } else {
+        let (top, left, extended) = axis_layout(bbox, chains, &axes);
+        bbox = extended;
+        Some((top, left))
     };
+    Layout {
+        view: view_box(&bbox),
+        axes,
+        axis_lines,
+        ellipses,
+    }
+}
 
-    let steps_str = steps
+fn compute_params(
+    svg_path: &str,
+    points: &[(f64, f64)],
+    chains: &[ChainJs],
+    opts: &EmbedOptions,
+) -> Params {
+    let Layout {
+        view: (vb_x, vb_y, vb_size),
+        axes,
+        axis_lines,
+        ellipses,
+    } = layout(points, chains, opts);
+
+    let chains_json = format!(
+        "[{}]",
+        chains
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let terms: Vec<String> = c
+                    .fourier
+                    .coeffs
+                    .iter()
+                    .map(|c| {
+                        format!(
+                            "{{freq:{},re:{},im:{},r:{}}}",
+                            c.freq,
+                            c.re,
+                            c.im,
+                            c.radius()
+                        )
+                    })
+                    .collect();
+                let mut extra = String::new();
+                if let (Some(axis), Some((top, left))) = (axes.get(i), axis_lines) {
+                    let terms = |terms: &[RealCoeff]| {
+                        terms
+                            .iter()
+                            .map(|c| format!("[{},{},{}]", c.freq, c.amp, c.phase))
+                            .collect::<Vec<_>>()
+                            .join(",")
+                    };
+                    extra += &format!(
+                        ",axis:{{x0:{},y0:{},top:{},left:{},x:[{}],y:[{}]}}",
+                        axis.x0,
+                        axis.y0,
+                        top,
+                        left,
+                        terms(&axis.x),
+                        terms(&axis.y)
+                    );
+                }
+                if let Some(series) = ellipses.get(i) {
+                    let terms: Vec<String> = series
+                        .ellipses
+                        .iter()
+                        .map(|e| {
+                            format!(
+                                "[{},{},{},{},{}]",
+                                e.freq, e.semi_major, e.semi_minor, e.tilt, e.phase
+                            )
+                        })
+                        .collect();
+                    extra += &format!(
+                        ",ellipse:{{x0:{},y0:{},e:[{}]}}",
+                        series.center.0,
+                        series.center.1,
+                        terms.join(",")
+                    );
+                }
+                if let Some(style) = c.style {
+                    if let Some(colors) = &style.trace_colors {
+                        extra += &format!(",colors:{}", serde_json_string_array(colors));
+                    }
+                    if let Some(colors) = &style.circle_colors {
+                        extra += &format!(",circles:{}", serde_json_string_array(colors));
+                    }
+                    if let Some(steps) = &style.steps {
+                        let max_nh = opts.max_harmonics.min(c.fourier.coeffs.len());
+                        extra += &format!(
+                            ",nh:{}",
+                            format_js_array(&harmonics_schedule(steps, max_nh))
+                        );
+                    }
+                }
+                format!(
+                    "{{fourier:[{}],dx:{},dy:{},start:{},end:{},n:{},ph:{}{}}}",
+                    terms.join(","),
+                    c.offset.0,
+                    c.offset.1,
+                    c.start,
+                    c.end,
+                    c.n,
+                    c.style.map_or(0.0, |s| s.phase),
+                    extra
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+
+    let steps_str = opts
+        .steps
         .ranges
         .iter()
         .map(|r| format!("{} {} {} {}", r.from, r.step, r.to, r.speed))
@@ -423,8 +865,7 @@ fn compute_params(
 
     Params {
         svg_path: svg_path.to_string(),
-        points_array,
-        fourier_json,
+        chains_json,
         vb_x,
         vb_y,
         vb_size,
@@ -491,7 +932,7 @@ fn when_to_show_select_html(id: &str, label: &str, w: &WhenToShow) -> String {
     )
 }
 
-fn html_escape(s: &str) -> String {
+pub(crate) fn html_escape(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
         .replace('>', "&gt;")
@@ -549,8 +990,7 @@ const VB_X = {vb_x};
 const VB_Y = {vb_y};
 const VB_SIZE = {vb_size};
 const contourPath2D = new Path2D("{svg_path}");
-const points = {points_array};
-const fourier = {fourier_json};
+const chains = {chains_json};
 const slider = document.getElementById("slider");
 const tval = document.getElementById("tval");
 const fourierCircleColors = ["blue","green","orange","purple","cyan","magenta"];
@@ -570,7 +1010,7 @@ let showNh = {show_nh};
 let traceOpacity = {opacity};
 let traceWidth = {trace_width};
 let contourWidth = {contour_width};
-let traceHistory = [];
+let traceHistories = chains.map(() => []);
 let firstDotX = 0, firstDotY = 0;
 
 const dpr = window.devicePixelRatio || 1;
@@ -650,13 +1090,37 @@ document.getElementById("chkNh").addEventListener("change", function() {{
   showNh = this.checked;
 }});
 
-function evalFourier(t) {{
-  if (!fourier) return null;
-  const numH = getNumHarmonics();
-  let cx = 0, cy = 0;
+function clearTraces() {{
+  traceHistories = chains.map(() => []);
+}}
+
+// Local time of a chain, or null when it is not drawing at loop time t.
+// Chains that ended keep their trace but lose their circles.
+function chainTime(ch, t) {{
+  if (t < ch.start || (t >= ch.end && ch.end < 1)) return null;
+  return Math.min(1, (t - ch.start) / (ch.end - ch.start));
+}}
+
+function evalFourier(ch, t) {{
+  const numH = getNumHarmonics(ch);
+  if (ch.axis) {{
+    const tt = t + ch.ph;
+    return [ch.dx + axisSum(ch.axis.x, numH, tt, ch.axis.x0), ch.dy + axisSum(ch.axis.y, numH, tt, ch.axis.y0)];
+  }}
+  if (ch.ellipse) {{
+    let cx = ch.dx + ch.ellipse.x0, cy = ch.dy + ch.ellipse.y0;
+    const n = Math.min(numH, ch.ellipse.e.length);
+    for (let k = 0; k < n; k++) {{
+      const [dx, dy] = ellipsePoint(ch.ellipse.e[k], t + ch.ph);
+      cx += dx;
+      cy += dy;
+    }}
+    return [cx, cy];
+  }}
+  let cx = ch.dx, cy = ch.dy;
   for (let k = 0; k < numH; k++) {{
-    const c = fourier[k];
-    const theta = 2 * Math.PI * c.freq * t;
+    const c = ch.fourier[k];
+    const theta = 2 * Math.PI * c.freq * (t + ch.ph);
     cx += c.re * Math.cos(theta) - c.im * Math.sin(theta);
     cy += c.im * Math.cos(theta) + c.re * Math.sin(theta);
   }}
@@ -673,64 +1137,159 @@ function drawContour() {{
 }}
 
 function drawFourier(t) {{
-  if (!fourierVisible || !fourier) return;
-  const numH = getNumHarmonics();
-  let cx = 0, cy = 0;
+  if (!fourierVisible) return;
+  let first = true;
+  for (const ch of chains) {{
+    const lt = chainTime(ch, t);
+    if (lt === null) continue;
+    drawChain(ch, lt, first);
+    first = false;
+  }}
+}}
+
+// Value of a real series [[freq, amp, phase], ...] over its first numH terms.
+// For the axis and ellipse views, numH counts frequencies |k|, each standing
+// for the complex terms k and -k, not single complex terms
+function axisSum(terms, numH, t, v) {{
+  const n = Math.min(numH, terms.length);
+  for (let k = 0; k < n; k++) {{
+    const [f, a, p] = terms[k];
+    v += a * Math.cos(2 * Math.PI * f * t + p);
+  }}
+  return v;
+}}
+
+// Offset of an ellipse [freq, a, b, tilt, phase] from its center at time t
+function ellipsePoint(e, t) {{
+  const [f, a, b, tilt, p] = e;
+  const theta = 2 * Math.PI * f * t + p;
+  const u = a * Math.cos(theta), v = b * Math.sin(theta);
+  return [u * Math.cos(tilt) - v * Math.sin(tilt), u * Math.sin(tilt) + v * Math.cos(tilt)];
+}}
+
+// One circle of radius r around (cx, cy), or an ellipse with semi-axes r
+// and ry turned by tilt, and its radius to (nx, ny)
+function drawTerm(cx, cy, r, nx, ny, color, ry, tilt) {{
+  ctx.beginPath();
+  if (ry === undefined) ctx.arc(cx, cy, r, 0, 2 * Math.PI);
+  else ctx.ellipse(cx, cy, r, ry, tilt, 0, 2 * Math.PI);
+  ctx.strokeStyle = color;
+  ctx.lineWidth = 0.3 * scale;
+  ctx.setLineDash([scale, scale]);
+  ctx.stroke();
+  ctx.setLineDash([]);
+  ctx.beginPath();
+  ctx.moveTo(cx, cy);
+  ctx.lineTo(nx, ny);
+  ctx.strokeStyle = color;
+  ctx.lineWidth = 0.3 * scale;
+  ctx.stroke();
+  ctx.beginPath();
+  ctx.arc(nx, ny, 0.8 * scale, 0, 2 * Math.PI);
+  ctx.fillStyle = color;
+  ctx.fill();
+}}
+
+function drawChain(ch, t, first) {{
+  if (ch.axis) return drawAxisChains(ch, t, first);
+  const numH = getNumHarmonics(ch);
+  const circleColors = ch.circles || fourierCircleColors;
+  if (ch.ellipse) {{
+    let cx = ch.dx + ch.ellipse.x0, cy = ch.dy + ch.ellipse.y0;
+    const n = Math.min(numH, ch.ellipse.e.length);
+    for (let k = 0; k < n; k++) {{
+      const e = ch.ellipse.e[k];
+      const [dx, dy] = ellipsePoint(e, t + ch.ph);
+      drawTerm(cx, cy, e[1], cx + dx, cy + dy, circleColors[k % circleColors.length], Math.abs(e[2]), e[3]);
+      if (first && k === 0) {{ firstDotX = cx + dx; firstDotY = cy + dy; }}
+      cx += dx;
+      cy += dy;
+    }}
+    return;
+  }}
+  let cx = ch.dx, cy = ch.dy;
   for (let k = 0; k < numH; k++) {{
-    const c = fourier[k];
-    const theta = 2 * Math.PI * c.freq * t;
+    const c = ch.fourier[k];
+    const theta = 2 * Math.PI * c.freq * (t + ch.ph);
     const dx = c.re * Math.cos(theta) - c.im * Math.sin(theta);
     const dy = c.im * Math.cos(theta) + c.re * Math.sin(theta);
     const nx = cx + dx;
     const ny = cy + dy;
-    const color = fourierCircleColors[k % fourierCircleColors.length];
-    ctx.beginPath();
-    ctx.arc(cx, cy, c.r, 0, 2 * Math.PI);
-    ctx.strokeStyle = color;
-    ctx.lineWidth = 0.3 * scale;
-    ctx.setLineDash([scale, scale]);
-    ctx.stroke();
-    ctx.setLineDash([]);
-    ctx.beginPath();
-    ctx.moveTo(cx, cy);
-    ctx.lineTo(nx, ny);
-    ctx.strokeStyle = color;
-    ctx.lineWidth = 0.3 * scale;
-    ctx.stroke();
-    ctx.beginPath();
-    ctx.arc(nx, ny, 0.8 * scale, 0, 2 * Math.PI);
-    ctx.fillStyle = color;
-    ctx.fill();
-    if (k === 0) {{ firstDotX = nx; firstDotY = ny; }}
+    drawTerm(cx, cy, c.r, nx, ny, circleColors[k % circleColors.length]);
+    if (first && k === 0) {{ firstDotX = nx; firstDotY = ny; }}
     cx = nx;
     cy = ny;
   }}
 }}
 
-function updateTraceData(t) {{
-  if (!traceVisible || !fourier) return;
-  const pt = evalFourier(t);
-  if (!pt) return;
-  traceHistory.push(pt);
-  const maxLen = Math.round(parseFloat(traceLenSlider.value) * points.length);
-  if (traceHistory.length > maxLen) {{
-    traceHistory = traceHistory.slice(traceHistory.length - maxLen);
+// Two-axis mode: the x chain along the top turns so that its tip's x is
+// x(t), the y chain on the left so that its tip's y is y(t); guide lines
+// from both tips meet at the pen. Each chain draws numH real terms, i.e.
+// numH frequencies, so the label's harmonic count is per axis
+function drawAxisChains(ch, t, first) {{
+  const numH = getNumHarmonics(ch);
+  const circleColors = ch.circles || fourierCircleColors;
+  const tt = t + ch.ph;
+  const tips = [];
+  for (const [terms, vertical] of [[ch.axis.x, false], [ch.axis.y, true]]) {{
+    let cx = vertical ? ch.axis.left : ch.dx + ch.axis.x0;
+    let cy = vertical ? ch.dy + ch.axis.y0 : ch.axis.top;
+    const n = Math.min(numH, terms.length);
+    for (let k = 0; k < n; k++) {{
+      const [f, a, p] = terms[k];
+      const theta = 2 * Math.PI * f * tt + p;
+      const along = a * Math.cos(theta), across = a * Math.sin(theta);
+      const nx = cx + (vertical ? across : along);
+      const ny = cy + (vertical ? along : across);
+      drawTerm(cx, cy, a, nx, ny, circleColors[k % circleColors.length]);
+      if (first && k === 0 && !vertical) {{ firstDotX = nx; firstDotY = ny; }}
+      cx = nx;
+      cy = ny;
+    }}
+    tips.push([cx, cy]);
   }}
+  const pen = evalFourier(ch, t);
+  ctx.save();
+  ctx.strokeStyle = "gray";
+  ctx.lineWidth = 0.3 * scale;
+  for (const [x, y] of tips) {{
+    ctx.beginPath();
+    ctx.moveTo(x, y);
+    ctx.lineTo(pen[0], pen[1]);
+    ctx.stroke();
+  }}
+  ctx.restore();
+}}
+
+function updateTraceData(t) {{
+  if (!traceVisible) return;
+  chains.forEach((ch, i) => {{
+    const lt = chainTime(ch, t);
+    if (lt === null) return;
+    const history = traceHistories[i];
+    history.push(evalFourier(ch, lt));
+    const maxLen = Math.round(parseFloat(traceLenSlider.value) * ch.n);
+    if (history.length > maxLen) history.splice(0, history.length - maxLen);
+  }});
 }}
 
 function drawTrace() {{
-  if (!traceVisible || traceHistory.length < 2) return;
+  if (!traceVisible) return;
   ctx.save();
   ctx.globalAlpha = traceOpacity;
-  ctx.strokeStyle = traceColors[traceColorIdx];
   ctx.lineWidth = traceWidth * scale;
   ctx.lineJoin = "round";
-  ctx.beginPath();
-  ctx.moveTo(traceHistory[0][0], traceHistory[0][1]);
-  for (let i = 1; i < traceHistory.length; i++) {{
-    ctx.lineTo(traceHistory[i][0], traceHistory[i][1]);
-  }}
-  ctx.stroke();
+  chains.forEach((ch, j) => {{
+    const history = traceHistories[j];
+    if (history.length < 2) return;
+    ctx.strokeStyle = ch.colors ? ch.colors[loopIndex % ch.colors.length] : traceColors[traceColorIdx];
+    ctx.beginPath();
+    ctx.moveTo(history[0][0], history[0][1]);
+    for (let i = 1; i < history.length; i++) {{
+      ctx.lineTo(history[i][0], history[i][1]);
+    }}
+    ctx.stroke();
+  }});
   ctx.restore();
 }}
 
@@ -782,19 +1341,20 @@ function drawSpark(px, py) {{
 }}
 
 function drawNhLabel() {{
-  if (!showNh || !fourier) return;
+  if (!showNh || chains.length === 0) return;
   ctx.save();
   ctx.fillStyle = "white";
   ctx.font = (VB_SIZE * 4 / 100) + "px sans-serif";
   ctx.textBaseline = "middle";
-  ctx.fillText(getNumHarmonics(), firstDotX + 2 * scale, firstDotY);
+  ctx.fillText(Math.max(1, Math.min(numHarmonics, maxNh)), firstDotX + 2 * scale, firstDotY);
   ctx.restore();
 }}
 
 let numHarmonics = 2;
-function getNumHarmonics() {{
-  if (!fourier) return 0;
-  return Math.max(1, Math.min(numHarmonics, fourier.length));
+// Chains with their own schedule (nh) ignore the page's harmonics loop
+function getNumHarmonics(ch) {{
+  const h = ch.nh ? ch.nh[loopIndex % ch.nh.length] : numHarmonics;
+  return Math.max(1, Math.min(h, ch.fourier.length));
 }}
 
 function updateDisplay(t) {{
@@ -807,13 +1367,17 @@ function updateDisplay(t) {{
   drawFourier(t);
   updateTraceData(t);
   drawTrace();
-  const pt = evalFourier(t);
-  if (pt) drawSpark(pt[0], pt[1]);
+  for (const ch of chains) {{
+    const lt = chainTime(ch, t);
+    if (lt === null) continue;
+    const pt = evalFourier(ch, lt);
+    drawSpark(pt[0], pt[1]);
+  }}
   drawNhLabel();
 }}
 
 slider.addEventListener("input", function() {{
-  traceHistory = [];
+  clearTraces();
   updateDisplay(parseFloat(this.value));
 }});
 
@@ -823,7 +1387,9 @@ let currentT = 0;
 let loopIndex = 0;
 
 const maxHarmonics = {max_harmonics};
-const maxNh = fourier ? Math.min(maxHarmonics, fourier.length) : 1;
+const maxNh = chains.length > 0
+  ? Math.min(maxHarmonics, Math.max(...chains.map(ch => ch.fourier.length)))
+  : 1;
 let nhSteps = [];
 let nhSpeeds = [];
 let currentSpeed = 1;
@@ -895,7 +1461,7 @@ function applyLoopParams() {{
   document.getElementById("loopVal").textContent = "loop " + loopIndex + "/" + totalLoops + " \u2014 harmonics: " + h;
   traceColorIdx = loopIndex % traceColors.length;
   traceVisible = shouldShow(getShowMode("selTrace"), loopIndex);
-  if (!traceVisible) traceHistory = [];
+  if (!traceVisible) clearTraces();
   contourVisible = shouldShow(getShowMode("selContour"), loopIndex);
   fourierVisible = shouldShow(getShowMode("selCircles"), loopIndex);
 }}
@@ -909,7 +1475,7 @@ function animate(timestamp) {{
   currentT += dt * currentSpeed * 0.1;
   if (currentT > 1) {{
     currentT -= 1;
-    traceHistory = [];
+    clearTraces();
     loopIndex = (loopIndex + 1) % totalLoops;
     applyLoopParams();
   }}
@@ -931,15 +1497,16 @@ document.getElementById("stopBtn").addEventListener("click", function() {{
   }}
 }});
 
-// Harmonics table
-if (fourier) {{
-  const tbody = document.getElementById("harmonicsTbody");
-  fourier.forEach((c, i) => {{
+// Harmonics table, numbered chain:term when there are several chains
+const tbody = document.getElementById("harmonicsTbody");
+chains.forEach((ch, j) => {{
+  ch.fourier.forEach((c, i) => {{
     const tr = document.createElement("tr");
-    tr.innerHTML = "<td style=\"padding:2px 8px\">"+i+"</td><td style=\"padding:2px 8px\">"+c.freq+"</td><td style=\"padding:2px 8px\">"+c.re.toFixed(4)+"</td><td style=\"padding:2px 8px\">"+c.im.toFixed(4)+"</td><td style=\"padding:2px 8px\">"+c.r.toFixed(4)+"</td>";
+    const idx = chains.length > 1 ? j + ":" + i : i;
+    tr.innerHTML = "<td style=\"padding:2px 8px\">"+idx+"</td><td style=\"padding:2px 8px\">"+c.freq+"</td><td style=\"padding:2px 8px\">"+c.re.toFixed(4)+"</td><td style=\"padding:2px 8px\">"+c.im.toFixed(4)+"</td><td style=\"padding:2px 8px\">"+c.r.toFixed(4)+"</td>";
     tbody.appendChild(tr);
   }});
-}}
+}});
 document.getElementById("harmonicsBtn").addEventListener("click", function() {{
   const div = document.getElementById("harmonicsDiv");
   div.style.display = div.style.display === "none" ? "" : "none";
@@ -954,8 +1521,7 @@ animId = requestAnimationFrame(animate);
         vb_y = p.vb_y,
         vb_size = p.vb_size,
         svg_path = p.svg_path,
-        points_array = p.points_array,
-        fourier_json = p.fourier_json,
+        chains_json = p.chains_json,
         dot_r = p.dot_r,
         show_point = opts.show_point,
         show_nh = opts.show_nh,
@@ -1037,7 +1603,7 @@ fn inner_content_embed(p: &Params, opts: &EmbedOptions) -> String {
     let update_trace_js = match &opts.show_trace {
         WhenToShow::Always | WhenToShow::Never => String::new(),
         WhenToShow::Congruence(e) => format!(
-            "  traceVisible = {}.includes(loopIndex % {});\n  if (!traceVisible) traceHistory = [];",
+            "  traceVisible = {}.includes(loopIndex % {});\n  if (!traceVisible) clearTraces();",
             format_js_array(&e.congruents),
             e.modulo
         ),
@@ -1059,8 +1625,7 @@ const VB_X = {vb_x};
 const VB_Y = {vb_y};
 const VB_SIZE = {vb_size};
 const contourPath2D = new Path2D("{svg_path}");
-const points = {points_array};
-const fourier = {fourier_json};
+const chains = {chains_json};
 const fourierCircleColors = ["blue","green","orange","purple","cyan","magenta"];
 const traceColors = {trace_colors_json};
 let traceColorIdx = 0;
@@ -1077,8 +1642,7 @@ const showNh = {show_nh};
 const traceOpacity = {opacity};
 const traceWidth = {trace_width};
 const contourWidth = {contour_width};
-let traceHistory = [];
-const traceMaxLen = Math.round({trace_length} * points.length);
+let traceHistories = chains.map(() => []);
 let firstDotX = 0, firstDotY = 0;
 
 const dpr = window.devicePixelRatio || 1;
@@ -1093,13 +1657,37 @@ function setupTransform() {{
   ctx.scale(s, s);
 }}
 
-function evalFourier(t) {{
-  if (!fourier) return null;
-  const numH = getNumHarmonics();
-  let cx = 0, cy = 0;
+function clearTraces() {{
+  traceHistories = chains.map(() => []);
+}}
+
+// Local time of a chain, or null when it is not drawing at loop time t.
+// Chains that ended keep their trace but lose their circles.
+function chainTime(ch, t) {{
+  if (t < ch.start || (t >= ch.end && ch.end < 1)) return null;
+  return Math.min(1, (t - ch.start) / (ch.end - ch.start));
+}}
+
+function evalFourier(ch, t) {{
+  const numH = getNumHarmonics(ch);
+  if (ch.axis) {{
+    const tt = t + ch.ph;
+    return [ch.dx + axisSum(ch.axis.x, numH, tt, ch.axis.x0), ch.dy + axisSum(ch.axis.y, numH, tt, ch.axis.y0)];
+  }}
+  if (ch.ellipse) {{
+    let cx = ch.dx + ch.ellipse.x0, cy = ch.dy + ch.ellipse.y0;
+    const n = Math.min(numH, ch.ellipse.e.length);
+    for (let k = 0; k < n; k++) {{
+      const [dx, dy] = ellipsePoint(ch.ellipse.e[k], t + ch.ph);
+      cx += dx;
+      cy += dy;
+    }}
+    return [cx, cy];
+  }}
+  let cx = ch.dx, cy = ch.dy;
   for (let k = 0; k < numH; k++) {{
-    const c = fourier[k];
-    const theta = 2 * Math.PI * c.freq * t;
+    const c = ch.fourier[k];
+    const theta = 2 * Math.PI * c.freq * (t + ch.ph);
     cx += c.re * Math.cos(theta) - c.im * Math.sin(theta);
     cy += c.im * Math.cos(theta) + c.re * Math.sin(theta);
   }}
@@ -1116,63 +1704,159 @@ function drawContour() {{
 }}
 
 function drawFourier(t) {{
-  if (!fourierVisible || !fourier) return;
-  const numH = getNumHarmonics();
-  let cx = 0, cy = 0;
+  if (!fourierVisible) return;
+  let first = true;
+  for (const ch of chains) {{
+    const lt = chainTime(ch, t);
+    if (lt === null) continue;
+    drawChain(ch, lt, first);
+    first = false;
+  }}
+}}
+
+// Value of a real series [[freq, amp, phase], ...] over its first numH terms.
+// For the axis and ellipse views, numH counts frequencies |k|, each standing
+// for the complex terms k and -k, not single complex terms
+function axisSum(terms, numH, t, v) {{
+  const n = Math.min(numH, terms.length);
+  for (let k = 0; k < n; k++) {{
+    const [f, a, p] = terms[k];
+    v += a * Math.cos(2 * Math.PI * f * t + p);
+  }}
+  return v;
+}}
+
+// Offset of an ellipse [freq, a, b, tilt, phase] from its center at time t
+function ellipsePoint(e, t) {{
+  const [f, a, b, tilt, p] = e;
+  const theta = 2 * Math.PI * f * t + p;
+  const u = a * Math.cos(theta), v = b * Math.sin(theta);
+  return [u * Math.cos(tilt) - v * Math.sin(tilt), u * Math.sin(tilt) + v * Math.cos(tilt)];
+}}
+
+// One circle of radius r around (cx, cy), or an ellipse with semi-axes r
+// and ry turned by tilt, and its radius to (nx, ny)
+function drawTerm(cx, cy, r, nx, ny, color, ry, tilt) {{
+  ctx.beginPath();
+  if (ry === undefined) ctx.arc(cx, cy, r, 0, 2 * Math.PI);
+  else ctx.ellipse(cx, cy, r, ry, tilt, 0, 2 * Math.PI);
+  ctx.strokeStyle = color;
+  ctx.lineWidth = 0.3 * scale;
+  ctx.setLineDash([scale, scale]);
+  ctx.stroke();
+  ctx.setLineDash([]);
+  ctx.beginPath();
+  ctx.moveTo(cx, cy);
+  ctx.lineTo(nx, ny);
+  ctx.strokeStyle = color;
+  ctx.lineWidth = 0.3 * scale;
+  ctx.stroke();
+  ctx.beginPath();
+  ctx.arc(nx, ny, 0.8 * scale, 0, 2 * Math.PI);
+  ctx.fillStyle = color;
+  ctx.fill();
+}}
+
+function drawChain(ch, t, first) {{
+  if (ch.axis) return drawAxisChains(ch, t, first);
+  const numH = getNumHarmonics(ch);
+  const circleColors = ch.circles || fourierCircleColors;
+  if (ch.ellipse) {{
+    let cx = ch.dx + ch.ellipse.x0, cy = ch.dy + ch.ellipse.y0;
+    const n = Math.min(numH, ch.ellipse.e.length);
+    for (let k = 0; k < n; k++) {{
+      const e = ch.ellipse.e[k];
+      const [dx, dy] = ellipsePoint(e, t + ch.ph);
+      drawTerm(cx, cy, e[1], cx + dx, cy + dy, circleColors[k % circleColors.length], Math.abs(e[2]), e[3]);
+      if (first && k === 0) {{ firstDotX = cx + dx; firstDotY = cy + dy; }}
+      cx += dx;
+      cy += dy;
+    }}
+    return;
+  }}
+  let cx = ch.dx, cy = ch.dy;
   for (let k = 0; k < numH; k++) {{
-    const c = fourier[k];
-    const theta = 2 * Math.PI * c.freq * t;
+    const c = ch.fourier[k];
+    const theta = 2 * Math.PI * c.freq * (t + ch.ph);
     const dx = c.re * Math.cos(theta) - c.im * Math.sin(theta);
     const dy = c.im * Math.cos(theta) + c.re * Math.sin(theta);
     const nx = cx + dx;
     const ny = cy + dy;
-    const color = fourierCircleColors[k % fourierCircleColors.length];
-    ctx.beginPath();
-    ctx.arc(cx, cy, c.r, 0, 2 * Math.PI);
-    ctx.strokeStyle = color;
-    ctx.lineWidth = 0.3 * scale;
-    ctx.setLineDash([scale, scale]);
-    ctx.stroke();
-    ctx.setLineDash([]);
-    ctx.beginPath();
-    ctx.moveTo(cx, cy);
-    ctx.lineTo(nx, ny);
-    ctx.strokeStyle = color;
-    ctx.lineWidth = 0.3 * scale;
-    ctx.stroke();
-    ctx.beginPath();
-    ctx.arc(nx, ny, 0.8 * scale, 0, 2 * Math.PI);
-    ctx.fillStyle = color;
-    ctx.fill();
-    if (k === 0) {{ firstDotX = nx; firstDotY = ny; }}
+    drawTerm(cx, cy, c.r, nx, ny, circleColors[k % circleColors.length]);
+    if (first && k === 0) {{ firstDotX = nx; firstDotY = ny; }}
     cx = nx;
     cy = ny;
   }}
 }}
 
-function updateTraceData(t) {{
-  if (!traceVisible || !fourier) return;
-  const pt = evalFourier(t);
-  if (!pt) return;
-  traceHistory.push(pt);
-  if (traceHistory.length > traceMaxLen) {{
-    traceHistory = traceHistory.slice(traceHistory.length - traceMaxLen);
+// Two-axis mode: the x chain along the top turns so that its tip's x is
+// x(t), the y chain on the left so that its tip's y is y(t); guide lines
+// from both tips meet at the pen. Each chain draws numH real terms, i.e.
+// numH frequencies, so the label's harmonic count is per axis
+function drawAxisChains(ch, t, first) {{
+  const numH = getNumHarmonics(ch);
+  const circleColors = ch.circles || fourierCircleColors;
+  const tt = t + ch.ph;
+  const tips = [];
+  for (const [terms, vertical] of [[ch.axis.x, false], [ch.axis.y, true]]) {{
+    let cx = vertical ? ch.axis.left : ch.dx + ch.axis.x0;
+    let cy = vertical ? ch.dy + ch.axis.y0 : ch.axis.top;
+    const n = Math.min(numH, terms.length);
+    for (let k = 0; k < n; k++) {{
+      const [f, a, p] = terms[k];
+      const theta = 2 * Math.PI * f * tt + p;
+      const along = a * Math.cos(theta), across = a * Math.sin(theta);
+      const nx = cx + (vertical ? across : along);
+      const ny = cy + (vertical ? along : across);
+      drawTerm(cx, cy, a, nx, ny, circleColors[k % circleColors.length]);
+      if (first && k === 0 && !vertical) {{ firstDotX = nx; firstDotY = ny; }}
+      cx = nx;
+      cy = ny;
+    }}
+    tips.push([cx, cy]);
   }}
+  const pen = evalFourier(ch, t);
+  ctx.save();
+  ctx.strokeStyle = "gray";
+  ctx.lineWidth = 0.3 * scale;
+  for (const [x, y] of tips) {{
+    ctx.beginPath();
+    ctx.moveTo(x, y);
+    ctx.lineTo(pen[0], pen[1]);
+    ctx.stroke();
+  }}
+  ctx.restore();
+}}
+
+function updateTraceData(t) {{
+  if (!traceVisible) return;
+  chains.forEach((ch, i) => {{
+    const lt = chainTime(ch, t);
+    if (lt === null) return;
+    const history = traceHistories[i];
+    history.push(evalFourier(ch, lt));
+    const maxLen = Math.round({trace_length} * ch.n);
+    if (history.length > maxLen) history.splice(0, history.length - maxLen);
+  }});
 }}
 
 function drawTrace() {{
-  if (!traceVisible || traceHistory.length < 2) return;
+  if (!traceVisible) return;
   ctx.save();
   ctx.globalAlpha = traceOpacity;
-  ctx.strokeStyle = traceColors[traceColorIdx];
   ctx.lineWidth = traceWidth * scale;
   ctx.lineJoin = "round";
-  ctx.beginPath();
-  ctx.moveTo(traceHistory[0][0], traceHistory[0][1]);
-  for (let i = 1; i < traceHistory.length; i++) {{
-    ctx.lineTo(traceHistory[i][0], traceHistory[i][1]);
-  }}
-  ctx.stroke();
+  chains.forEach((ch, j) => {{
+    const history = traceHistories[j];
+    if (history.length < 2) return;
+    ctx.strokeStyle = ch.colors ? ch.colors[loopIndex % ch.colors.length] : traceColors[traceColorIdx];
+    ctx.beginPath();
+    ctx.moveTo(history[0][0], history[0][1]);
+    for (let i = 1; i < history.length; i++) {{
+      ctx.lineTo(history[i][0], history[i][1]);
+    }}
+    ctx.stroke();
+  }});
   ctx.restore();
 }}
 
@@ -1224,19 +1908,20 @@ function drawSpark(px, py) {{
 }}
 
 function drawNhLabel() {{
-  if (!showNh || !fourier) return;
+  if (!showNh || chains.length === 0) return;
   ctx.save();
   ctx.fillStyle = "white";
   ctx.font = (VB_SIZE * 4 / 100) + "px sans-serif";
   ctx.textBaseline = "middle";
-  ctx.fillText(getNumHarmonics(), firstDotX + 2 * scale, firstDotY);
+  ctx.fillText(Math.max(1, Math.min(numHarmonics, maxNh)), firstDotX + 2 * scale, firstDotY);
   ctx.restore();
 }}
 
 let numHarmonics = 2;
-function getNumHarmonics() {{
-  if (!fourier) return 0;
-  return Math.max(1, Math.min(numHarmonics, fourier.length));
+// Chains with their own schedule (nh) ignore the page's harmonics loop
+function getNumHarmonics(ch) {{
+  const h = ch.nh ? ch.nh[loopIndex % ch.nh.length] : numHarmonics;
+  return Math.max(1, Math.min(h, ch.fourier.length));
 }}
 
 function updateDisplay(t) {{
@@ -1247,8 +1932,12 @@ function updateDisplay(t) {{
   drawFourier(t);
   updateTraceData(t);
   drawTrace();
-  const pt = evalFourier(t);
-  if (pt) drawSpark(pt[0], pt[1]);
+  for (const ch of chains) {{
+    const lt = chainTime(ch, t);
+    if (lt === null) continue;
+    const pt = evalFourier(ch, lt);
+    drawSpark(pt[0], pt[1]);
+  }}
   drawNhLabel();
 }}
 
@@ -1258,7 +1947,9 @@ let currentT = 0;
 let loopIndex = 0;
 
 const maxHarmonics = {max_harmonics};
-const maxNh = fourier ? Math.min(maxHarmonics, fourier.length) : 1;
+const maxNh = chains.length > 0
+  ? Math.min(maxHarmonics, Math.max(...chains.map(ch => ch.fourier.length)))
+  : 1;
 const nhSteps = [];
 const nhSpeeds = [];
 {{
@@ -1307,7 +1998,7 @@ function animate(timestamp) {{
   currentT += dt * currentSpeed * 0.1;
   if (currentT > 1) {{
     currentT -= 1;
-    traceHistory = [];
+    clearTraces();
     loopIndex = (loopIndex + 1) % totalLoops;
     applyLoopParams();
   }}
@@ -1323,8 +2014,7 @@ animId = requestAnimationFrame(animate);
         vb_y = p.vb_y,
         vb_size = p.vb_size,
         svg_path = p.svg_path,
-        points_array = p.points_array,
-        fourier_json = p.fourier_json,
+        chains_json = p.chains_json,
         trace_colors_json = serde_json_string_array(&opts.trace_colors),
         dot_r = p.dot_r,
         contour_visible_init = contour_visible_init,
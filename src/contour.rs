@@ -2,6 +2,12 @@ pub trait ContourFunction {
     fn x(&self, t: f64) -> f64;
     fn y(&self, t: f64) -> f64;
 
+    /// The point `(x(t), y(t))`; functions that compute both coordinates
+    /// together override it.
+    fn point(&self, t: f64) -> (f64, f64) {
+        (self.x(t), self.y(t))
+    }
+
     fn with_offset(self, x_offset: f64, y_offset: f64) -> OffsetContourFunction<Self>
     where
         Self: Sized,
@@ -12,6 +18,15 @@ pub trait ContourFunction {
             y_offset,
         }
     }
+
+    /// Compose with a map of the complex plane, `z = x + iy` being sent to
+    /// `map(z)`.
+    fn map_complex(self, map: ComplexMap) -> MappedContourFunction<Self>
+    where
+        Self: Sized,
+    {
+        MappedContourFunction { inner: self, map }
+    }
 }
 
 pub struct OffsetContourFunction<T: ContourFunction> {
@@ -28,15 +43,323 @@ impl<T: ContourFunction> ContourFunction for OffsetContourFunction<T> {
     fn y(&self, t: f64) -> f64 {
         self.inner.y(t) + self.y_offset
     }
+
+    fn point(&self, t: f64) -> (f64, f64) {
+        let (x, y) = self.inner.point(t);
+        (x + self.x_offset, y + self.y_offset)
+    }
 }
 
-use serde::Deserialize;
+pub struct MappedContourFunction<T: ContourFunction> {
+    inner: T,
+    map: ComplexMap,
+}
 
-#[derive(Deserialize)]
+impl<T: ContourFunction> ContourFunction for MappedContourFunction<T> {
+    fn x(&self, t: f64) -> f64 {
+        self.point(t).0
+    }
+
+    fn y(&self, t: f64) -> f64 {
+        self.point(t).1
+    }
+
+    fn point(&self, t: f64) -> (f64, f64) {
+        self.map.map(self.inner.point(t))
+    }
+}
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::equation::ComplexMap;
+
+#[derive(Clone, Deserialize)]
 pub struct Contour {
     pub points: Vec<(f64, f64)>,
 }
 
+/// Direction in which a closed contour turns, in coordinates with y
+/// pointing down as on screen.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Orientation {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// Point a closed contour is made to start from, in coordinates with y
+/// pointing down as on screen.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StartAnchor {
+    /// Point with the smallest y, the first one if several
+    Top,
+    /// Point with the smallest x, the first one if several
+    Left,
+    /// Point whose direction from the centroid is closest to this angle, in
+    /// degrees counter-clockwise on screen from the +x axis
+    Angle(f64),
+    /// Point at this index, modulo the number of points
+    Index(usize),
+}
+
+/// Axis-aligned bounding box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingBox {
+    pub min: (f64, f64),
+    pub max: (f64, f64),
+}
+
+impl BoundingBox {
+    /// Box around `points`, or `None` if there are none.
+    pub fn of_points(points: impl IntoIterator<Item = (f64, f64)>) -> Option<BoundingBox> {
+        points.into_iter().fold(None, |bbox, (x, y)| {
+            Some(match bbox {
+                None => BoundingBox {
+                    min: (x, y),
+                    max: (x, y),
+                },
+                Some(BoundingBox { min, max }) => BoundingBox {
+                    min: (min.0.min(x), min.1.min(y)),
+                    max: (max.0.max(x), max.1.max(y)),
+                },
+            })
+        })
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max.0 - self.min.0
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.1 - self.min.1
+    }
+
+    pub fn center(&self) -> (f64, f64) {
+        (
+            (self.min.0 + self.max.0) / 2.0,
+            (self.min.1 + self.max.1) / 2.0,
+        )
+    }
+}
+
+/// Point where two non-adjacent segments of a closed contour cross.
+/// Segment `i` runs from point `i` to point `i + 1`, the last one back to
+/// the first point.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Intersection {
+    pub segments: (usize, usize),
+    pub point: (f64, f64),
+}
+
+/// Signed area of a polygon, closed implicitly (positive when
+/// counter-clockwise with y up, i.e. clockwise on screen).
+pub(crate) fn polygon_area(points: &[(f64, f64)]) -> f64 {
+    let n = points.len();
+    let mut area = 0.0;
+    for i in 0..n {
+        let (x0, y0) = points[i];
+        let (x1, y1) = points[(i + 1) % n];
+        area += x0 * y1 - x1 * y0;
+    }
+    area / 2.0
+}
+
+impl Contour {
+    /// Signed area of the closed loop through the points, positive when
+    /// clockwise (see `Orientation`).
+    pub fn signed_area(&self) -> f64 {
+        polygon_area(&self.points)
+    }
+
+    /// Whether the loop encloses no area, up to rounding: retraced strokes
+    /// and figures of eight with balanced lobes do not.
+    fn encloses_no_area(&self, area: f64) -> bool {
+        area.abs() <= 1e-9 * self.perimeter().powi(2)
+    }
+
+    /// Turning direction of the loop, or `None` if it encloses no area.
+    pub fn orientation(&self) -> Option<Orientation> {
+        let area = self.signed_area();
+        if self.encloses_no_area(area) {
+            None
+        } else if area > 0.0 {
+            Some(Orientation::Clockwise)
+        } else {
+            Some(Orientation::CounterClockwise)
+        }
+    }
+
+    /// Centroid of the enclosed area, or the mean of the points if the loop
+    /// encloses no area. `None` for an empty contour.
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        let pts = &self.points;
+        let n = pts.len();
+        if n == 0 {
+            return None;
+        }
+        let area = self.signed_area();
+        if self.encloses_no_area(area) {
+            let (sx, sy) = pts
+                .iter()
+                .fold((0.0, 0.0), |(sx, sy), &(x, y)| (sx + x, sy + y));
+            return Some((sx / n as f64, sy / n as f64));
+        }
+        let (mut cx, mut cy) = (0.0, 0.0);
+        for i in 0..n {
+            let (x0, y0) = pts[i];
+            let (x1, y1) = pts[(i + 1) % n];
+            let cross = x0 * y1 - x1 * y0;
+            cx += (x0 + x1) * cross;
+            cy += (y0 + y1) * cross;
+        }
+        Some((cx / (6.0 * area), cy / (6.0 * area)))
+    }
+
+    /// Length of the closed loop, including the segment from the last point
+    /// back to the first.
+    pub fn perimeter(&self) -> f64 {
+        let pts = &self.points;
+        (0..pts.len())
+            .map(|i| {
+                let ((x0, y0), (x1, y1)) = (pts[i], pts[(i + 1) % pts.len()]);
+                (x1 - x0).hypot(y1 - y0)
+            })
+            .sum()
+    }
+
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        BoundingBox::of_points(self.points.iter().copied())
+    }
+
+    /// Points of an explicitly closed loop (last point equal to the first)
+    /// without the repeated point, and whether it was there.
+    fn open_points(&self) -> (&[(f64, f64)], bool) {
+        match self.points.as_slice() {
+            [first, .., last] if first == last => (&self.points[..self.points.len() - 1], true),
+            pts => (pts, false),
+        }
+    }
+
+    /// The same loop traversed in `orientation`, reversed if needed. The
+    /// starting point is kept. Loops enclosing no area are left as is.
+    pub fn oriented(&self, orientation: Orientation) -> Contour {
+        match self.orientation() {
+            Some(o) if o != orientation => {
+                let (pts, closed) = self.open_points();
+                let mut points: Vec<(f64, f64)> = pts[..1]
+                    .iter()
+                    .chain(pts[1..].iter().rev())
+                    .copied()
+                    .collect();
+                if closed {
+                    points.push(points[0]);
+                }
+                Contour { points }
+            }
+            _ => self.clone(),
+        }
+    }
+
+    /// Index of the point designated by `anchor`, or `None` for an empty
+    /// contour.
+    pub fn anchor_index(&self, anchor: StartAnchor) -> Option<usize> {
+        let (pts, _) = self.open_points();
+        let first_min = |key: &dyn Fn((f64, f64)) -> f64| {
+            (0..pts.len()).min_by(|&i, &j| key(pts[i]).total_cmp(&key(pts[j])))
+        };
+        match anchor {
+            StartAnchor::Top => first_min(&|(_, y)| y),
+            StartAnchor::Left => first_min(&|(x, _)| x),
+            StartAnchor::Angle(degrees) => {
+                let (cx, cy) = self.centroid()?;
+                let target = degrees.to_radians();
+                first_min(&|(x, y)| {
+                    let diff = ((cy - y).atan2(x - cx) - target).rem_euclid(std::f64::consts::TAU);
+                    diff.min(std::f64::consts::TAU - diff)
+                })
+            }
+            StartAnchor::Index(i) => (!pts.is_empty()).then(|| i % pts.len()),
+        }
+    }
+
+    /// The same loop starting from the point designated by `anchor`. An
+    /// explicitly closed loop stays closed on its new first point.
+    pub fn with_start(&self, anchor: StartAnchor) -> Contour {
+        let Some(start) = self.anchor_index(anchor) else {
+            return self.clone();
+        };
+        let (pts, closed) = self.open_points();
+        let mut points: Vec<(f64, f64)> =
+            pts[start..].iter().chain(&pts[..start]).copied().collect();
+        if closed {
+            points.push(points[0]);
+        }
+        Contour { points }
+    }
+
+    /// Crossings between non-adjacent segments of the closed loop. A
+    /// crossing at a shared point of several segments is reported once per
+    /// pair of segments that start there or cross there; touching collinear
+    /// segments are not reported.
+    pub fn self_intersections(&self) -> Vec<Intersection> {
+        let pts = &self.points;
+        let n = pts.len();
+        if n < 4 {
+            return vec![];
+        }
+        let segment = |i: usize| (pts[i], pts[(i + 1) % n]);
+        // Sweep along x: only segments whose x ranges overlap are compared
+        let mut order: Vec<usize> = (0..n).collect();
+        let min_x = |i: usize| segment(i).0.0.min(segment(i).1.0);
+        order.sort_by(|&a, &b| min_x(a).total_cmp(&min_x(b)));
+        let mut found = Vec::new();
+        for (k, &i) in order.iter().enumerate() {
+            let (a0, a1) = segment(i);
+            let max_x = a0.0.max(a1.0);
+            for &j in &order[k + 1..] {
+                if min_x(j) > max_x {
+                    break;
+                }
+                let (lo, hi) = (i.min(j), i.max(j));
+                if hi - lo == 1 || (lo == 0 && hi == n - 1) {
+                    continue;
+                }
+                let (b0, b1) = segment(j);
+                if let Some(point) = segment_crossing(a0, a1, b0, b1) {
+                    found.push(Intersection {
+                        segments: (lo, hi),
+                        point,
+                    });
+                }
+            }
+        }
+        found.sort_by_key(|c| c.segments);
+        found
+    }
+}
+
+/// Crossing of segments `a0`–`a1` and `b0`–`b1`, each including its start
+/// and excluding its end, so that a crossing at a vertex is found once.
+fn segment_crossing(
+    a0: (f64, f64),
+    a1: (f64, f64),
+    b0: (f64, f64),
+    b1: (f64, f64),
+) -> Option<(f64, f64)> {
+    let (dax, day) = (a1.0 - a0.0, a1.1 - a0.1);
+    let (dbx, dby) = (b1.0 - b0.0, b1.1 - b0.1);
+    let denom = dax * dby - day * dbx;
+    // Parallel segments, up to rounding, never cross
+    if denom.abs() <= 1e-9 * dax.hypot(day) * dbx.hypot(dby) {
+        return None;
+    }
+    let (ex, ey) = (b0.0 - a0.0, b0.1 - a0.1);
+    let t = (ex * dby - ey * dbx) / denom;
+    let u = (ex * day - ey * dax) / denom;
+    ((0.0..1.0).contains(&t) && (0.0..1.0).contains(&u)).then_some((a0.0 + t * dax, a0.1 + t * day))
+}
+
 struct ContourFunctionImpl {
     points: Vec<(f64, f64)>,
 }
@@ -85,17 +408,409 @@ pub fn f_of_contour(contour: &Contour) -> impl ContourFunction {
     }
 }
 
-pub fn interpolate(contour: &Contour, n: usize) -> Contour {
-    let f = f_of_contour(contour);
+/// Sample `n` evenly spaced values of `t` in `[0, 1]` (both ends included).
+pub fn contour_of_function(f: &impl ContourFunction, n: usize) -> Contour {
     let points = (0..n)
         .map(|i| {
             let t = i as f64 / (n - 1) as f64;
-            (f.x(t), f.y(t))
+            f.point(t)
+        })
+        .collect();
+    Contour { points }
+}
+
+pub fn interpolate(contour: &Contour, n: usize) -> Contour {
+    contour_of_function(&f_of_contour(contour), n)
+}
+
+/// How open paths (strokes) are turned into the closed loop that the Fourier
+/// series traces.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum OpenCurve {
+    /// Draw each path forward then back along itself, so it is drawn as a
+    /// single stroke
+    Retrace,
+    /// Join the end of each path straight back to its start
+    Close,
+}
+
+/// Join open paths into one contour using `strategy`. With `Retrace`, each
+/// path returns to its own start before the next one begins.
+pub fn close_open_paths(paths: &[Contour], strategy: OpenCurve) -> Contour {
+    let mut points = Vec::new();
+    for path in paths {
+        points.extend_from_slice(&path.points);
+        match strategy {
+            OpenCurve::Retrace => {
+                if path.points.len() > 1 {
+                    points.extend(path.points.iter().rev().skip(1));
+                }
+            }
+            OpenCurve::Close => {
+                if let Some(&first) = path.points.first()
+                    && path.points.last() != Some(&first)
+                {
+                    points.push(first);
+                }
+            }
+        }
+    }
+    Contour { points }
+}
+
+/// Distance from `p` to the segment `a`–`b`.
+fn segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len2 = dx * dx + dy * dy;
+    let t = if len2 > 0.0 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len2).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (p.0 - a.0 - t * dx).hypot(p.1 - a.1 - t * dy)
+}
+
+/// Length above which an edge of `points` is a jump between subpaths: 5
+/// times the mean edge length.
+pub(crate) fn jump_threshold(points: &[(f64, f64)]) -> f64 {
+    let total: f64 = points
+        .windows(2)
+        .map(|w| (w[1].0 - w[0].0).hypot(w[1].1 - w[0].1))
+        .sum();
+    total / (points.len().max(2) - 1) as f64 * 5.0
+}
+
+/// Indices, in order, of the points of the closed loop through `points`
+/// that split it into separately simplified or smoothed paths: both ends of
+/// each jump between subpaths, and each tip where a retraced stroke turns
+/// back along itself.
+fn path_breaks(points: &[(f64, f64)]) -> Vec<usize> {
+    let n = points.len();
+    let threshold = jump_threshold(points);
+    let jump = |i: usize| {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        (b.0 - a.0).hypot(b.1 - a.1) > threshold
+    };
+    // Nearest point different from point i, stepping by `step` (1 or n - 1)
+    let neighbour = |i: usize, step: usize| {
+        (1..n)
+            .map(|k| points[(i + k * step) % n])
+            .find(|&p| p != points[i])
+    };
+    (0..n)
+        .filter(|&i| {
+            jump(i)
+                || jump((i + n - 1) % n)
+                || neighbour(i, 1).is_some_and(|p| neighbour(i, n - 1) == Some(p))
         })
+        .collect()
+}
+
+/// Douglas–Peucker simplification of the closed loop through `contour`:
+/// every dropped point lies within `tolerance` of the simplified outline.
+/// Subpaths and retraced strokes are simplified on their own, keeping their
+/// ends.
+pub fn simplify_douglas_peucker(contour: &Contour, tolerance: f64) -> Contour {
+    let pts = &contour.points;
+    let n = pts.len();
+    if n < 4 {
+        return contour.clone();
+    }
+    let mut splits = path_breaks(pts);
+    if splits.is_empty() {
+        // Split the loop at the point farthest from the first one, so that
+        // neither half has a degenerate baseline
+        let dist2 = |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2);
+        let far = (1..n)
+            .max_by(|&i, &j| dist2(pts[0], pts[i]).total_cmp(&dist2(pts[0], pts[j])))
+            .unwrap();
+        splits = vec![0, far];
+    }
+    let mut keep = vec![false; n];
+    for &i in &splits {
+        keep[i] = true;
+    }
+    // Indices past n wrap around to the start of the loop
+    let ends = splits.iter().skip(1).copied().chain([splits[0] + n]);
+    let mut stack: Vec<(usize, usize)> = splits.iter().copied().zip(ends).collect();
+    while let Some((first, last)) = stack.pop() {
+        let (a, b) = (pts[first % n], pts[last % n]);
+        let farthest = (first + 1..last)
+            .map(|i| (i, segment_distance(pts[i % n], a, b)))
+            .max_by(|x, y| x.1.total_cmp(&y.1));
+        if let Some((i, d)) = farthest
+            && d > tolerance
+        {
+            keep[i % n] = true;
+            stack.push((first, i));
+            stack.push((i, last));
+        }
+    }
+    Contour {
+        points: pts
+            .iter()
+            .zip(&keep)
+            .filter(|(_, k)| **k)
+            .map(|(&p, _)| p)
+            .collect(),
+    }
+}
+
+/// Candidate for removal in Visvalingam–Whyatt simplification, ordered so
+/// that the smallest area comes first out of a `BinaryHeap`.
+struct Candidate {
+    area: f64,
+    index: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other
+            .area
+            .total_cmp(&self.area)
+            .then(other.index.cmp(&self.index))
+    }
+}
+
+/// Visvalingam–Whyatt simplification of the closed loop through `contour`:
+/// repeatedly drop the point whose triangle with its two neighbours has the
+/// smallest area, while that area is below `tolerance`. At least three
+/// points are kept, and so are the ends of subpaths and the tips of retraced
+/// strokes, whose triangles are flat.
+pub fn simplify_visvalingam(contour: &Contour, tolerance: f64) -> Contour {
+    let pts = &contour.points;
+    let n = pts.len();
+    if n < 4 {
+        return contour.clone();
+    }
+    let mut pinned = vec![false; n];
+    for i in path_breaks(pts) {
+        pinned[i] = true;
+    }
+    let mut prev: Vec<usize> = (0..n).map(|i| (i + n - 1) % n).collect();
+    let mut next: Vec<usize> = (0..n).map(|i| (i + 1) % n).collect();
+    let area = |p: usize, i: usize, q: usize| {
+        let ((x0, y0), (x1, y1), (x2, y2)) = (pts[p], pts[i], pts[q]);
+        ((x1 - x0) * (y2 - y0) - (x2 - x0) * (y1 - y0)).abs() / 2.0
+    };
+    let mut areas: Vec<f64> = (0..n).map(|i| area(prev[i], i, next[i])).collect();
+    let mut removed = vec![false; n];
+    let mut heap: std::collections::BinaryHeap<Candidate> = areas
+        .iter()
+        .enumerate()
+        .filter(|&(index, _)| !pinned[index])
+        .map(|(index, &area)| Candidate { area, index })
         .collect();
+    let mut remaining = n;
+    while remaining > 3
+        && let Some(Candidate { area: a, index: i }) = heap.pop()
+    {
+        // Skip entries made stale by an earlier removal
+        if removed[i] || a != areas[i] {
+            continue;
+        }
+        if a >= tolerance {
+            break;
+        }
+        removed[i] = true;
+        remaining -= 1;
+        let (p, q) = (prev[i], next[i]);
+        next[p] = q;
+        prev[q] = p;
+        for j in [p, q].into_iter().filter(|&j| !pinned[j]) {
+            areas[j] = area(prev[j], j, next[j]);
+            heap.push(Candidate {
+                area: areas[j],
+                index: j,
+            });
+        }
+    }
+    Contour {
+        points: pts
+            .iter()
+            .zip(&removed)
+            .filter(|(_, r)| !**r)
+            .map(|(&p, _)| p)
+            .collect(),
+    }
+}
+
+/// Convolve the closed loop through `contour` with `kernel`, centered on
+/// each point. Kernel weights must sum to 1. Subpaths and retraced strokes
+/// are smoothed on their own, keeping their ends.
+fn convolve_loop(contour: &Contour, kernel: &[f64]) -> Contour {
+    let pts = &contour.points;
+    let n = pts.len();
+    if n == 0 {
+        return contour.clone();
+    }
+    let breaks = path_breaks(pts);
+    if breaks.is_empty() {
+        return Contour {
+            points: convolve_path(pts, true, kernel),
+        };
+    }
+    let mut points = pts.clone();
+    let ends = breaks.iter().skip(1).copied().chain([breaks[0] + n]);
+    for (first, last) in breaks.iter().copied().zip(ends) {
+        let path: Vec<(f64, f64)> = (first..=last).map(|i| pts[i % n]).collect();
+        let smoothed = convolve_path(&path, false, kernel);
+        for (i, p) in (first..=last).zip(smoothed) {
+            points[i % n] = p;
+        }
+    }
     Contour { points }
 }
 
+/// Convolve the path through `points` with `kernel`, taps being spaced by
+/// the mean distance between points along the path, so that unevenly spaced
+/// points are weighted by arc length. A closed path wraps around; an open
+/// one keeps its ends.
+fn convolve_path(points: &[(f64, f64)], closed: bool, kernel: &[f64]) -> Vec<(f64, f64)> {
+    let n = points.len();
+    let edges = if closed { n } else { n - 1 };
+    // Arc length at each point, and at the end of the path
+    let mut arc = vec![0.0];
+    for i in 0..edges {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        arc.push(arc[i] + (b.0 - a.0).hypot(b.1 - a.1));
+    }
+    let length = arc[edges];
+    if length == 0.0 || !length.is_finite() {
+        return points.to_vec();
+    }
+    let step = length / edges as f64;
+    let at = |s: f64| {
+        let s = if closed {
+            s.rem_euclid(length)
+        } else {
+            s.clamp(0.0, length)
+        };
+        let i = arc.partition_point(|&a| a <= s).clamp(1, edges) - 1;
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        let t = if arc[i + 1] > arc[i] {
+            (s - arc[i]) / (arc[i + 1] - arc[i])
+        } else {
+            0.0
+        };
+        (a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1))
+    };
+    let half = (kernel.len() / 2) as f64;
+    (0..n)
+        .map(|i| {
+            if !closed && (i == 0 || i == n - 1) {
+                return points[i];
+            }
+            kernel
+                .iter()
+                .enumerate()
+                .fold((0.0, 0.0), |(x, y), (k, w)| {
+                    let (px, py) = at(arc[i] + (k as f64 - half) * step);
+                    (x + w * px, y + w * py)
+                })
+        })
+        .collect()
+}
+
+/// Replace each point by the mean of the `window` points centered on it
+/// (`window` is odd), wrapping around the closed loop.
+pub fn smooth_moving_average(contour: &Contour, window: usize) -> Contour {
+    convolve_loop(contour, &vec![1.0 / window as f64; window])
+}
+
+/// Gaussian smoothing along the closed loop, with a standard deviation of
+/// `sigma` points.
+pub fn smooth_gaussian(contour: &Contour, sigma: f64) -> Contour {
+    let half = (3.0 * sigma).ceil() as i64;
+    let weights: Vec<f64> = (-half..=half)
+        .map(|i| (-(i * i) as f64 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f64 = weights.iter().sum();
+    convolve_loop(
+        contour,
+        &weights.iter().map(|w| w / total).collect::<Vec<_>>(),
+    )
+}
+
+/// Savitzky–Golay smoothing along the closed loop: each point is replaced by
+/// the value at its position of the polynomial of degree `order` fitted by
+/// least squares to the `window` points centered on it (`window` is odd and
+/// greater than `order`). Unlike a moving average, corners and peaks keep
+/// their height.
+pub fn smooth_savitzky_golay(contour: &Contour, window: usize, order: usize) -> Contour {
+    let half = (window / 2) as i32;
+    let size = order + 1;
+    // Normal equations (AᵀA) a = e₀ with A[i][j] = zᵢʲ; the fitted value at
+    // z = 0 is Σ yᵢ Σⱼ aⱼ zᵢʲ
+    let mut m: Vec<Vec<f64>> = (0..size)
+        .map(|r| {
+            let mut row: Vec<f64> = (0..size)
+                .map(|c| {
+                    (-half..=half)
+                        .map(|z| (z as f64).powi((r + c) as i32))
+                        .sum()
+                })
+                .collect();
+            row.push(if r == 0 { 1.0 } else { 0.0 });
+            row
+        })
+        .collect();
+    for col in 0..size {
+        let pivot = (col..size)
+            .max_by(|&a, &b| m[a][col].abs().total_cmp(&m[b][col].abs()))
+            .unwrap();
+        m.swap(col, pivot);
+        let pivot_row = m[col].clone();
+        for (r, row) in m.iter_mut().enumerate() {
+            if r != col {
+                let f = row[col] / pivot_row[col];
+                for (v, p) in row.iter_mut().zip(&pivot_row).skip(col) {
+                    *v -= f * p;
+                }
+            }
+        }
+    }
+    let a: Vec<f64> = (0..size).map(|r| m[r][size] / m[r][r]).collect();
+    let kernel: Vec<f64> = (-half..=half)
+        .map(|z| {
+            a.iter()
+                .enumerate()
+                .map(|(j, aj)| aj * (z as f64).powi(j as i32))
+                .sum()
+        })
+        .collect();
+    convolve_loop(contour, &kernel)
+}
+
+/// Spectral low-pass filter: keep the Fourier terms of the closed loop with
+/// frequency at most `cutoff` and resample the result at as many points as
+/// the input.
+pub fn smooth_low_pass(contour: &Contour, cutoff: usize) -> Contour {
+    let n = contour.points.len();
+    if n == 0 {
+        return contour.clone();
+    }
+    let fd = fourier_decomposition(contour, cutoff.min(n / 2));
+    Contour {
+        points: (0..n).map(|j| fd.eval(j as f64 / n as f64)).collect(),
+    }
+}
+
 /// Complex Fourier coefficient: c_k = re + i*im, frequency k
 /// At time t, contributes: (re*cos(2πkt) - im*sin(2πkt), im*cos(2πkt) + re*sin(2πkt))
 /// This traces a circle of radius |c_k|.
@@ -117,6 +832,13 @@ pub struct FourierDecomposition {
 }
 
 impl FourierDecomposition {
+    /// The first `n` terms, the largest ones, as drawn with `n` harmonics.
+    pub fn truncated(&self, n: usize) -> FourierDecomposition {
+        FourierDecomposition {
+            coeffs: self.coeffs.iter().take(n).cloned().collect(),
+        }
+    }
+
     pub fn eval(&self, t: f64) -> (f64, f64) {
         let two_pi = 2.0 * std::f64::consts::PI;
         let mut x = 0.0;
@@ -130,6 +852,157 @@ impl FourierDecomposition {
     }
 }
 
+/// Term `amp·cos(2π·freq·t + phase)` of a real Fourier series.
+#[derive(Clone, Debug)]
+pub struct RealCoeff {
+    pub freq: u32,
+    pub amp: f64,
+    pub phase: f64,
+}
+
+/// Separate real Fourier series of x(t) and y(t), as drawn by two-axis
+/// drawing machines: `x(t) = x0 + Σ x terms`, `y(t) = y0 + Σ y terms`.
+pub struct AxisSeries {
+    pub x0: f64,
+    pub y0: f64,
+    /// Sorted by descending amplitude
+    pub x: Vec<RealCoeff>,
+    /// Sorted by descending amplitude
+    pub y: Vec<RealCoeff>,
+}
+
+impl AxisSeries {
+    pub fn eval(&self, t: f64) -> (f64, f64) {
+        let sum = |terms: &[RealCoeff]| -> f64 {
+            terms
+                .iter()
+                .map(|c| c.amp * (std::f64::consts::TAU * c.freq as f64 * t + c.phase).cos())
+                .sum()
+        };
+        (self.x0 + sum(&self.x), self.y0 + sum(&self.y))
+    }
+}
+
+/// Frequency `k` and the terms of frequency `k` and `-k`, if any.
+type TermPair<'a> = (u32, [Option<&'a ComplexCoeff>; 2]);
+
+impl FourierDecomposition {
+    /// Real series of each coordinate. The terms of frequency `k` and `-k`
+    /// combine into `X = c_k + conj(c_-k)` for x and `Y = c_k - conj(c_-k)`
+    /// for y, so that `x = Re(X e^{iθ})` and `y = Im(Y e^{iθ})` with
+    /// `θ = 2πkt`. Each real term thus stands for up to two complex terms,
+    /// and the page draws `n` real terms per axis for `n` harmonics.
+    pub fn axis_series(&self) -> AxisSeries {
+        let (dc, pairs) = self.term_pairs();
+        let parts = |c: Option<&ComplexCoeff>| c.map_or((0.0, 0.0), |c| (c.re, c.im));
+        let (x0, y0) = parts(dc);
+        let (mut x, mut y) = (Vec::new(), Vec::new());
+        for (k, [positive, negative]) in pairs {
+            let (pr, pi) = parts(positive);
+            let (nr, ni) = parts(negative);
+            let (xr, xi) = (pr + nr, pi - ni);
+            let (yr, yi) = (pr - nr, pi + ni);
+            // Im(Y e^{iθ}) = |Y| cos(θ + arg Y - π/2)
+            for (terms, re, im, shift) in [
+                (&mut x, xr, xi, 0.0),
+                (&mut y, yr, yi, std::f64::consts::FRAC_PI_2),
+            ] {
+                let amp = re.hypot(im);
+                if amp > 0.0 {
+                    terms.push(RealCoeff {
+                        freq: k,
+                        amp,
+                        phase: im.atan2(re) - shift,
+                    });
+                }
+            }
+        }
+        x.sort_by(|a, b| b.amp.total_cmp(&a.amp));
+        y.sort_by(|a, b| b.amp.total_cmp(&a.amp));
+        AxisSeries { x0, y0, x, y }
+    }
+
+    /// The term of frequency 0, and the terms of frequency `k` and `-k` for
+    /// each `k` from 1 to the highest frequency, looked up by frequency.
+    fn term_pairs(&self) -> (Option<&ComplexCoeff>, Vec<TermPair<'_>>) {
+        let terms: HashMap<i32, &ComplexCoeff> = self.coeffs.iter().map(|c| (c.freq, c)).collect();
+        let max_k = self.coeffs.iter().map(|c| c.freq.unsigned_abs()).max();
+        let pairs = (1..=max_k.unwrap_or(0))
+            .map(|k| {
+                let term = |freq: i32| terms.get(&freq).copied();
+                (k, [term(k as i32), term(-(k as i32))])
+            })
+            .collect();
+        (terms.get(&0).copied(), pairs)
+    }
+}
+
+/// Ellipse traced by the terms of frequency `k` and `-k` together: at time
+/// `t`, with `θ = 2πkt + phase`, the point `(a·cos θ, b·sin θ)` rotated by
+/// `tilt`. `b` is negative when the ellipse is traversed clockwise.
+#[derive(Clone, Debug)]
+pub struct EllipseCoeff {
+    pub freq: u32,
+    pub semi_major: f64,
+    pub semi_minor: f64,
+    pub tilt: f64,
+    pub phase: f64,
+}
+
+impl EllipseCoeff {
+    pub fn eval(&self, t: f64) -> (f64, f64) {
+        let theta = std::f64::consts::TAU * self.freq as f64 * t + self.phase;
+        let (u, v) = (self.semi_major * theta.cos(), self.semi_minor * theta.sin());
+        let (sin, cos) = self.tilt.sin_cos();
+        (u * cos - v * sin, u * sin + v * cos)
+    }
+}
+
+/// Fourier series as a chain of ellipses, one per frequency `|k|`.
+pub struct EllipseSeries {
+    pub center: (f64, f64),
+    /// Sorted by descending semi-major axis
+    pub ellipses: Vec<EllipseCoeff>,
+}
+
+impl EllipseSeries {
+    pub fn eval(&self, t: f64) -> (f64, f64) {
+        self.ellipses.iter().fold(self.center, |(x, y), e| {
+            let (dx, dy) = e.eval(t);
+            (x + dx, y + dy)
+        })
+    }
+}
+
+impl FourierDecomposition {
+    /// Ellipse of each pair of terms. With `c_k = p·e^{iα}` and
+    /// `c_-k = q·e^{iβ}`, the pair is `e^{i(α+β)/2}·((p+q)·cos ψ + i(p-q)·sin ψ)`
+    /// where `ψ = 2πkt + (α-β)/2`. Each ellipse thus stands for up to two
+    /// complex terms, and the page draws `n` ellipses for `n` harmonics.
+    pub fn ellipses(&self) -> EllipseSeries {
+        let (dc, pairs) = self.term_pairs();
+        let center = dc.map_or((0.0, 0.0), |c| (c.re, c.im));
+        let polar =
+            |c: Option<&ComplexCoeff>| c.map_or((0.0, 0.0), |c| (c.radius(), c.im.atan2(c.re)));
+        let mut ellipses = Vec::new();
+        for (k, [positive, negative]) in pairs {
+            let (p, alpha) = polar(positive);
+            let (q, beta) = polar(negative);
+            if p + q > 0.0 {
+                ellipses.push(EllipseCoeff {
+                    freq: k,
+                    semi_major: p + q,
+                    semi_minor: p - q,
+                    tilt: (alpha + beta) / 2.0,
+                    phase: (alpha - beta) / 2.0,
+                });
+            }
+        }
+        ellipses.sort_by(|a, b| b.semi_major.total_cmp(&a.semi_major));
+        EllipseSeries { center, ellipses }
+    }
+}
+
 pub fn fourier_decomposition(contour: &Contour, num_terms: usize) -> FourierDecomposition {
     let n = contour.points.len();
     let two_pi = 2.0 * std::f64::consts::PI;
@@ -0,0 +1,762 @@
+use std::collections::HashMap;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::contour::{Contour, ContourFunction, contour_of_function};
+
+/// Expression tree of the small math language used by equation inputs.
+///
+/// Supports `+ - * / ^`, unary minus, parentheses, implicit multiplication
+/// (`3t`, `k θ`, `2(t+1)`), named parameters, the constants `pi`,
+/// `tau` and `e`, and the usual elementary functions (`sin`, `cos`, `exp`,
+/// `sqrt`, `atan2`, `min`, ...). Evaluated over complex numbers (see
+/// [`Expr::eval_complex`]), `i` is the imaginary unit and `conj`, `re`, `im`
+/// and `arg` are available.
+#[derive(Clone, Debug)]
+pub enum Expr {
+    Num(f64),
+    Var(String),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+    Pow(Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+const FUNCTIONS: &[(&str, usize)] = &[
+    ("sin", 1),
+    ("cos", 1),
+    ("tan", 1),
+    ("asin", 1),
+    ("acos", 1),
+    ("atan", 1),
+    ("sinh", 1),
+    ("cosh", 1),
+    ("tanh", 1),
+    ("exp", 1),
+    ("ln", 1),
+    ("log", 1),
+    ("sqrt", 1),
+    ("abs", 1),
+    ("floor", 1),
+    ("ceil", 1),
+    ("sign", 1),
+    ("conj", 1),
+    ("re", 1),
+    ("im", 1),
+    ("arg", 1),
+    ("atan2", 2),
+    ("min", 2),
+    ("max", 2),
+    ("pow", 2),
+    ("mod", 2),
+];
+
+fn constant(name: &str) -> Option<f64> {
+    match name {
+        "pi" | "π" => Some(std::f64::consts::PI),
+        "tau" | "τ" => Some(std::f64::consts::TAU),
+        "e" => Some(std::f64::consts::E),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Op(char),
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(&ch) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+        } else if ch.is_ascii_digit() || ch == '.' {
+            let mut num = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_ascii_digit() || c == '.' {
+                    num.push(c);
+                    chars.next();
+                } else if (c == 'e' || c == 'E') && !num.contains(['e', 'E']) {
+                    // Exponent only if followed by a digit or a signed digit
+                    let mut ahead = chars.clone();
+                    ahead.next();
+                    let next = ahead.next();
+                    let is_exp = match next {
+                        Some(d) if d.is_ascii_digit() => true,
+                        Some('+') | Some('-') => ahead.next().is_some_and(|d| d.is_ascii_digit()),
+                        _ => false,
+                    };
+                    if !is_exp {
+                        break;
+                    }
+                    num.push(c);
+                    chars.next();
+                    if let Some(&sign) = chars.peek()
+                        && (sign == '+' || sign == '-')
+                    {
+                        num.push(sign);
+                        chars.next();
+                    }
+                } else {
+                    break;
+                }
+            }
+            let value = num
+                .parse::<f64>()
+                .map_err(|_| format!("invalid number: {num}"))?;
+            tokens.push(Token::Num(value));
+        } else if ch.is_alphabetic() || ch == '_' {
+            let mut ident = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_alphanumeric() || c == '_' {
+                    ident.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Ident(ident));
+        } else if "+-*/^(),".contains(ch) {
+            tokens.push(Token::Op(ch));
+            chars.next();
+        } else {
+            return Err(format!("unexpected character '{ch}'"));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, op: char) -> bool {
+        if self.peek() == Some(&Token::Op(op)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, op: char) -> Result<(), String> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            Err(format!("expected '{op}'"))
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.term()?;
+        loop {
+            if self.eat('+') {
+                lhs = Expr::Add(Box::new(lhs), Box::new(self.term()?));
+            } else if self.eat('-') {
+                lhs = Expr::Sub(Box::new(lhs), Box::new(self.term()?));
+            } else {
+                return Ok(lhs);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        loop {
+            if self.eat('*') {
+                lhs = Expr::Mul(Box::new(lhs), Box::new(self.unary()?));
+            } else if self.eat('/') {
+                lhs = Expr::Div(Box::new(lhs), Box::new(self.unary()?));
+            } else {
+                return Ok(lhs);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat('-') {
+            Ok(Expr::Neg(Box::new(self.unary()?)))
+        } else if self.eat('+') {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.implicit()?;
+        if self.eat('^') {
+            // Right-associative, binds tighter than unary minus on the left
+            Ok(Expr::Pow(Box::new(base), Box::new(self.unary()?)))
+        } else {
+            Ok(base)
+        }
+    }
+
+    /// Juxtaposition is an implicit product: `3t`, `k θ`, `2(t+1)`,
+    /// `0.5sin(t)`.
+    fn implicit(&mut self) -> Result<Expr, String> {
+        let atom = self.atom()?;
+        if matches!(self.peek(), Some(Token::Ident(_)) | Some(Token::Op('('))) {
+            return Ok(Expr::Mul(Box::new(atom), Box::new(self.power()?)));
+        }
+        Ok(atom)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        match self.peek().cloned() {
+            Some(Token::Num(v)) => {
+                self.pos += 1;
+                Ok(Expr::Num(v))
+            }
+            Some(Token::Ident(name)) => {
+                self.pos += 1;
+                if self.eat('(') {
+                    let mut args = vec![self.expr()?];
+                    while self.eat(',') {
+                        args.push(self.expr()?);
+                    }
+                    self.expect(')')?;
+                    match FUNCTIONS.iter().find(|(f, _)| *f == name) {
+                        Some(&(_, arity)) if arity == args.len() => Ok(Expr::Call(name, args)),
+                        Some(&(_, arity)) => Err(format!(
+                            "{name} expects {arity} argument(s), got {}",
+                            args.len()
+                        )),
+                        None => Err(format!("unknown function: {name}")),
+                    }
+                } else {
+                    Ok(Expr::Var(name))
+                }
+            }
+            Some(Token::Op('(')) => {
+                self.pos += 1;
+                let e = self.expr()?;
+                self.expect(')')?;
+                Ok(e)
+            }
+            Some(Token::Op(op)) => Err(format!("unexpected '{op}'")),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+impl Expr {
+    pub fn parse(s: &str) -> Result<Expr, String> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+        };
+        let e = parser.expr()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(format!("unexpected trailing input in '{s}'"));
+        }
+        Ok(e)
+    }
+
+    /// Names of free variables (excluding built-in constants).
+    pub fn variables(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        self.collect_variables(&mut out);
+        out.sort();
+        out.dedup();
+        out
+    }
+
+    fn collect_variables(&self, out: &mut Vec<String>) {
+        match self {
+            Expr::Num(_) => {}
+            Expr::Var(name) => {
+                if constant(name).is_none() {
+                    out.push(name.clone());
+                }
+            }
+            Expr::Neg(a) => a.collect_variables(out),
+            Expr::Add(a, b)
+            | Expr::Sub(a, b)
+            | Expr::Mul(a, b)
+            | Expr::Div(a, b)
+            | Expr::Pow(a, b) => {
+                a.collect_variables(out);
+                b.collect_variables(out);
+            }
+            Expr::Call(_, args) => {
+                for a in args {
+                    a.collect_variables(out);
+                }
+            }
+        }
+    }
+
+    /// Check that every free variable is bound in `vars`.
+    pub fn check_bound(&self, vars: &HashMap<String, f64>) -> Result<(), String> {
+        for name in self.variables() {
+            if !vars.contains_key(&name) {
+                return Err(format!("unknown variable: {name}"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluate with the given variable bindings. Unbound variables evaluate
+    /// to NaN; use [`Expr::check_bound`] beforehand to report them.
+    pub fn eval(&self, vars: &HashMap<String, f64>) -> f64 {
+        self.eval_with(&|name| vars.get(name).copied())
+    }
+
+    /// Evaluate with variables bound by `vars`, as [`Expr::eval`].
+    pub fn eval_with(&self, vars: &dyn Fn(&str) -> Option<f64>) -> f64 {
+        match self {
+            Expr::Num(v) => *v,
+            Expr::Var(name) => vars(name).or_else(|| constant(name)).unwrap_or(f64::NAN),
+            Expr::Neg(a) => -a.eval_with(vars),
+            Expr::Add(a, b) => a.eval_with(vars) + b.eval_with(vars),
+            Expr::Sub(a, b) => a.eval_with(vars) - b.eval_with(vars),
+            Expr::Mul(a, b) => a.eval_with(vars) * b.eval_with(vars),
+            Expr::Div(a, b) => a.eval_with(vars) / b.eval_with(vars),
+            Expr::Pow(a, b) => a.eval_with(vars).powf(b.eval_with(vars)),
+            Expr::Call(name, args) => {
+                let a = args[0].eval_with(vars);
+                let b = || args[1].eval_with(vars);
+                match name.as_str() {
+                    "sin" => a.sin(),
+                    "cos" => a.cos(),
+                    "tan" => a.tan(),
+                    "asin" => a.asin(),
+                    "acos" => a.acos(),
+                    "atan" => a.atan(),
+                    "sinh" => a.sinh(),
+                    "cosh" => a.cosh(),
+                    "tanh" => a.tanh(),
+                    "exp" => a.exp(),
+                    "ln" | "log" => a.ln(),
+                    "sqrt" => a.sqrt(),
+                    "abs" => a.abs(),
+                    "floor" => a.floor(),
+                    "ceil" => a.ceil(),
+                    "sign" => {
+                        if a == 0.0 {
+                            0.0
+                        } else {
+                            a.signum()
+                        }
+                    }
+                    "conj" | "re" => a,
+                    "im" => 0.0,
+                    "arg" => {
+                        if a < 0.0 {
+                            std::f64::consts::PI
+                        } else {
+                            0.0
+                        }
+                    }
+                    "atan2" => a.atan2(b()),
+                    "min" => a.min(b()),
+                    "max" => a.max(b()),
+                    "pow" => a.powf(b()),
+                    "mod" => a.rem_euclid(b()),
+                    _ => f64::NAN,
+                }
+            }
+        }
+    }
+}
+
+impl Expr {
+    /// Evaluate over complex numbers with the given variable bindings, `i`
+    /// being the imaginary unit. Functions without a complex extension
+    /// (`floor`, `ceil`, `atan2`, `min`, `max`, `mod`) apply to the real
+    /// parts of their arguments. Unbound variables evaluate to NaN.
+    pub fn eval_complex(&self, vars: &HashMap<String, Complex>) -> Complex {
+        self.eval_complex_with(&|name| vars.get(name).copied())
+    }
+
+    /// Evaluate with variables bound by `vars`, as [`Expr::eval_complex`].
+    pub fn eval_complex_with(&self, vars: &dyn Fn(&str) -> Option<Complex>) -> Complex {
+        match self {
+            Expr::Num(v) => Complex::real(*v),
+            Expr::Var(name) => vars(name).unwrap_or_else(|| {
+                if name == "i" {
+                    Complex::I
+                } else {
+                    Complex::real(constant(name).unwrap_or(f64::NAN))
+                }
+            }),
+            Expr::Neg(a) => -a.eval_complex_with(vars),
+            Expr::Add(a, b) => a.eval_complex_with(vars) + b.eval_complex_with(vars),
+            Expr::Sub(a, b) => a.eval_complex_with(vars) - b.eval_complex_with(vars),
+            Expr::Mul(a, b) => a.eval_complex_with(vars) * b.eval_complex_with(vars),
+            Expr::Div(a, b) => a.eval_complex_with(vars) / b.eval_complex_with(vars),
+            Expr::Pow(a, b) => a.eval_complex_with(vars).pow(b.eval_complex_with(vars)),
+            Expr::Call(name, args) => {
+                let a = args[0].eval_complex_with(vars);
+                let b = || args[1].eval_complex_with(vars);
+                let real = |f: fn(f64, f64) -> f64| Complex::real(f(a.re, b().re));
+                match name.as_str() {
+                    "sin" => a.sin(),
+                    "cos" => a.cos(),
+                    "tan" => a.sin() / a.cos(),
+                    // asin z = -i ln(iz + √(1 - z²))
+                    "asin" => -Complex::I * (Complex::I * a + (Complex::ONE - a * a).sqrt()).ln(),
+                    "acos" => {
+                        Complex::real(std::f64::consts::FRAC_PI_2)
+                            + Complex::I * (Complex::I * a + (Complex::ONE - a * a).sqrt()).ln()
+                    }
+                    // atan z = i/2 (ln(1 - iz) - ln(1 + iz))
+                    "atan" => {
+                        Complex::new(0.0, 0.5)
+                            * ((Complex::ONE - Complex::I * a).ln()
+                                - (Complex::ONE + Complex::I * a).ln())
+                    }
+                    "sinh" => -Complex::I * (Complex::I * a).sin(),
+                    "cosh" => (Complex::I * a).cos(),
+                    "tanh" => -Complex::I * (Complex::I * a).sin() / (Complex::I * a).cos(),
+                    "exp" => a.exp(),
+                    "ln" | "log" => a.ln(),
+                    "sqrt" => a.sqrt(),
+                    "abs" => Complex::real(a.abs()),
+                    "sign" => {
+                        if a.abs() == 0.0 {
+                            Complex::ZERO
+                        } else {
+                            a / Complex::real(a.abs())
+                        }
+                    }
+                    "conj" => Complex::new(a.re, -a.im),
+                    "re" => Complex::real(a.re),
+                    "im" => Complex::real(a.im),
+                    "arg" => Complex::real(a.arg()),
+                    "floor" => Complex::new(a.re.floor(), a.im.floor()),
+                    "ceil" => Complex::new(a.re.ceil(), a.im.ceil()),
+                    "pow" => a.pow(b()),
+                    "atan2" => real(f64::atan2),
+                    "min" => real(f64::min),
+                    "max" => real(f64::max),
+                    "mod" => real(f64::rem_euclid),
+                    _ => Complex::real(f64::NAN),
+                }
+            }
+        }
+    }
+}
+
+/// Complex number `re + i·im`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub const ZERO: Complex = Complex { re: 0.0, im: 0.0 };
+    pub const ONE: Complex = Complex { re: 1.0, im: 0.0 };
+    pub const I: Complex = Complex { re: 0.0, im: 1.0 };
+
+    pub fn new(re: f64, im: f64) -> Complex {
+        Complex { re, im }
+    }
+
+    pub fn real(re: f64) -> Complex {
+        Complex { re, im: 0.0 }
+    }
+
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    pub fn arg(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    pub fn exp(self) -> Complex {
+        let m = self.re.exp();
+        Complex::new(m * self.im.cos(), m * self.im.sin())
+    }
+
+    /// Principal logarithm.
+    pub fn ln(self) -> Complex {
+        Complex::new(self.abs().ln(), self.arg())
+    }
+
+    /// Principal square root.
+    pub fn sqrt(self) -> Complex {
+        let m = self.abs().sqrt();
+        let a = self.arg() / 2.0;
+        Complex::new(m * a.cos(), m * a.sin())
+    }
+
+    pub fn sin(self) -> Complex {
+        Complex::new(
+            self.re.sin() * self.im.cosh(),
+            self.re.cos() * self.im.sinh(),
+        )
+    }
+
+    pub fn cos(self) -> Complex {
+        Complex::new(
+            self.re.cos() * self.im.cosh(),
+            -self.re.sin() * self.im.sinh(),
+        )
+    }
+
+    /// Principal power. Small integer exponents are computed by repeated
+    /// multiplication, so that `0^2 = 0` and results stay exact.
+    pub fn pow(self, exponent: Complex) -> Complex {
+        let n = exponent.re;
+        if exponent.im == 0.0 && n.fract() == 0.0 && n.abs() <= 64.0 {
+            let mut result = Complex::ONE;
+            for _ in 0..n.abs() as u32 {
+                result = result * self;
+            }
+            return if n < 0.0 {
+                Complex::ONE / result
+            } else {
+                result
+            };
+        }
+        if self == Complex::ZERO {
+            return if exponent.re > 0.0 {
+                Complex::ZERO
+            } else {
+                Complex::real(f64::NAN)
+            };
+        }
+        (exponent * self.ln()).exp()
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+    fn add(self, o: Complex) -> Complex {
+        Complex::new(self.re + o.re, self.im + o.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+    fn sub(self, o: Complex) -> Complex {
+        Complex::new(self.re - o.re, self.im - o.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+    fn mul(self, o: Complex) -> Complex {
+        Complex::new(
+            self.re * o.re - self.im * o.im,
+            self.re * o.im + self.im * o.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Complex;
+    fn div(self, o: Complex) -> Complex {
+        let d = o.re * o.re + o.im * o.im;
+        Complex::new(
+            (self.re * o.re + self.im * o.im) / d,
+            (self.im * o.re - self.re * o.im) / d,
+        )
+    }
+}
+
+impl Neg for Complex {
+    type Output = Complex;
+    fn neg(self) -> Complex {
+        // Subtract rather than flip signs: -(4 + 0i) must keep a positive
+        // zero imaginary part for `sqrt` and `ln` to take principal values
+        Complex::ZERO - self
+    }
+}
+
+/// Map of the plane given by a complex expression of `z = x + iy`, such as
+/// `z^2`, `exp(z/50)`, the Möbius map `(z - a)/(1 - a z)` or the inversion
+/// `r^2/conj(z)`. Parameters are real.
+pub struct ComplexMap {
+    expr: Expr,
+    params: HashMap<String, f64>,
+}
+
+/// Contours with fewer points are subdivided before being mapped, since the
+/// image of a straight segment is usually curved.
+const MIN_MAPPED_POINTS: usize = 1000;
+
+impl ComplexMap {
+    pub fn new(expr: &str, params: HashMap<String, f64>) -> Result<Self, String> {
+        let expr = Expr::parse(expr)?;
+        let mut bound = params.clone();
+        for name in ["z", "i"] {
+            if params.contains_key(name) {
+                return Err(format!("parameter name {name} is reserved"));
+            }
+            bound.insert(name.to_string(), 0.0);
+        }
+        expr.check_bound(&bound)?;
+        Ok(Self { expr, params })
+    }
+
+    /// Image of the point `(x, y)`, i.e. of `z = x + iy`.
+    pub fn map(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let w = self.expr.eval_complex_with(&|name| {
+            if name == "z" {
+                Some(Complex::new(x, y))
+            } else {
+                self.params.get(name).map(|&v| Complex::real(v))
+            }
+        });
+        (w.re, w.im)
+    }
+
+    /// Image of the closed loop through `contour`. Sparse contours are
+    /// subdivided first; points mapped to infinity or NaN (poles) are
+    /// dropped.
+    pub fn apply(&self, contour: &Contour) -> Contour {
+        let pts = &contour.points;
+        let n = pts.len();
+        let pieces = if n == 0 {
+            1
+        } else {
+            MIN_MAPPED_POINTS.div_ceil(n)
+        };
+        let mut points = Vec::with_capacity(n * pieces);
+        for (i, &(x0, y0)) in pts.iter().enumerate() {
+            let (x1, y1) = pts[(i + 1) % n];
+            let steps = if (x0, y0) == (x1, y1) { 1 } else { pieces };
+            for k in 0..steps {
+                let t = k as f64 / steps as f64;
+                points.push(self.map((x0 + (x1 - x0) * t, y0 + (y1 - y0) * t)));
+            }
+        }
+        points.retain(|(x, y)| x.is_finite() && y.is_finite());
+        Contour { points }
+    }
+}
+
+/// Parse a `NAME=VALUE` parameter binding, where VALUE is itself a constant
+/// expression (e.g. `k=5`, `r=pi/4`).
+pub fn parse_param(s: &str) -> Result<(String, f64), String> {
+    let (name, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("parameter must be NAME=VALUE: {s}"))?;
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(format!("invalid parameter name: {name}"));
+    }
+    let expr = Expr::parse(value)?;
+    expr.check_bound(&HashMap::new())?;
+    Ok((name.to_string(), expr.eval(&HashMap::new())))
+}
+
+pub enum CurveForm {
+    Parametric { x: Expr, y: Expr },
+    Polar { r: Expr },
+}
+
+/// A curve defined by equations, evaluated over `t_min..=t_max`.
+///
+/// As a [`ContourFunction`], `t ∈ [0, 1]` is mapped linearly onto the
+/// parameter range. In polar form the parameter is the angle θ and may be
+/// written `t`, `theta` or `θ`.
+pub struct EquationCurve {
+    form: CurveForm,
+    t_min: f64,
+    t_max: f64,
+    params: HashMap<String, f64>,
+}
+
+const PARAMETER_NAMES: &[&str] = &["t", "theta", "θ"];
+
+impl EquationCurve {
+    pub fn new(
+        form: CurveForm,
+        t_min: f64,
+        t_max: f64,
+        params: HashMap<String, f64>,
+    ) -> Result<Self, String> {
+        if !t_min.is_finite() || !t_max.is_finite() || t_max <= t_min {
+            return Err(format!(
+                "t range [{t_min}, {t_max}] must be finite and increasing"
+            ));
+        }
+        let mut bound = params.clone();
+        for name in PARAMETER_NAMES {
+            bound.insert(name.to_string(), 0.0);
+        }
+        match &form {
+            CurveForm::Parametric { x, y } => {
+                x.check_bound(&bound).map_err(|e| format!("x(t): {e}"))?;
+                y.check_bound(&bound).map_err(|e| format!("y(t): {e}"))?;
+            }
+            CurveForm::Polar { r } => {
+                r.check_bound(&bound).map_err(|e| format!("r(θ): {e}"))?;
+            }
+        }
+        Ok(Self {
+            form,
+            t_min,
+            t_max,
+            params,
+        })
+    }
+
+    /// Value of `expr` at `t ∈ [0, 1]`, without copying the parameters.
+    fn eval(&self, expr: &Expr, t: f64) -> f64 {
+        let t = self.parameter(t);
+        expr.eval_with(&|name| {
+            if PARAMETER_NAMES.contains(&name) {
+                Some(t)
+            } else {
+                self.params.get(name).copied()
+            }
+        })
+    }
+
+    /// Parameter value at `t ∈ [0, 1]`.
+    fn parameter(&self, t: f64) -> f64 {
+        self.t_min + t * (self.t_max - self.t_min)
+    }
+
+    /// The curve sampled at `n` evenly spaced values of the parameter, as
+    /// `contour_of_function` does, without the points where it is undefined
+    /// or infinite (e.g. `ln(t)` at `t = 0`).
+    pub fn sample(&self, n: usize) -> Result<Contour, String> {
+        let mut contour = contour_of_function(self, n);
+        contour
+            .points
+            .retain(|(x, y)| x.is_finite() && y.is_finite());
+        if contour.points.len() < 2 {
+            return Err(format!(
+                "the curve is undefined or infinite over [{}, {}]",
+                self.t_min, self.t_max
+            ));
+        }
+        Ok(contour)
+    }
+}
+
+impl ContourFunction for EquationCurve {
+    fn x(&self, t: f64) -> f64 {
+        match &self.form {
+            CurveForm::Parametric { x, .. } => self.eval(x, t),
+            CurveForm::Polar { r } => self.eval(r, t) * self.parameter(t).cos(),
+        }
+    }
+
+    fn y(&self, t: f64) -> f64 {
+        match &self.form {
+            CurveForm::Parametric { y, .. } => self.eval(y, t),
+            CurveForm::Polar { r } => self.eval(r, t) * self.parameter(t).sin(),
+        }
+    }
+}
@@ -0,0 +1,451 @@
+use std::fmt::Write;
+
+use crate::canvas::{
+    CIRCLE_COLORS, Chain, ChainTiming, chain_windows, html_escape, svg_path_of_contour, view_box,
+};
+use crate::contour::{BoundingBox, Contour, FourierDecomposition};
+use crate::model::{EmbedOptions, WhenToShow};
+
+/// How `static_svg` arranges the reconstructions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum SvgLayout {
+    /// All reconstructions over the contour, one group (an Inkscape layer)
+    /// each
+    Layers,
+    /// One tile per reconstruction, each over the contour
+    Grid,
+}
+
+/// Options of `static_svg`.
+pub struct StaticSvgOptions {
+    /// Harmonic counts of the reconstructions, e.g. `[5, 20, 100]`
+    pub harmonics: Vec<usize>,
+    pub layout: SvgLayout,
+    /// Tiles per row for `Grid` (defaults to a square-ish grid)
+    pub columns: Option<usize>,
+    /// Draw the epicycle circles of each reconstruction at this time (0 to 1)
+    pub circles_at: Option<f64>,
+    /// Width and height of the picture, or of each tile for `Grid`, in px
+    pub size: f64,
+    /// Fill color behind the drawing, or `none`
+    pub background: String,
+}
+
+/// Color of the original contour and of the labels, readable on dark and
+/// light backgrounds.
+pub(crate) const CONTOUR_COLOR: &str = "gray";
+
+/// Static SVG of the chains' contours and of their Fourier reconstructions
+/// with `svg.harmonics` terms, for places where scripts cannot run.
+pub fn static_svg(chains: &[Chain], opts: &EmbedOptions, svg: &StaticSvgOptions) -> String {
+    let bbox =
+        BoundingBox::of_points(chains.iter().flat_map(placed_points)).unwrap_or(BoundingBox {
+            min: (0.0, 0.0),
+            max: (100.0, 100.0),
+        });
+    let (vb_x, vb_y, vb_size) = view_box(&bbox);
+    let view = format!("{vb_x} {vb_y} {vb_size} {vb_size}");
+    let scale = vb_size / 100.0;
+    let contour = contour_markup(chains, opts, scale, CONTOUR_COLOR);
+
+    let mut out = String::new();
+    match svg.layout {
+        SvgLayout::Layers => {
+            writeln!(
+                out,
+                r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" width="{size}" height="{size}" viewBox="{view}">"#,
+                size = svg.size
+            )
+            .unwrap();
+            out += &background_markup(&svg.background, vb_x, vb_y, vb_size, vb_size);
+            writeln!(
+                out,
+                r#"<g id="contour" inkscape:groupmode="layer" inkscape:label="contour">{contour}</g>"#
+            )
+            .unwrap();
+            for (i, &n) in svg.harmonics.iter().enumerate() {
+                writeln!(
+                    out,
+                    r#"<g id="harmonics-{n}" inkscape:groupmode="layer" inkscape:label="N = {n}">{}</g>"#,
+                    reconstruction_markup(chains, opts, n, i, svg.circles_at, scale)
+                )
+                .unwrap();
+            }
+        }
+        SvgLayout::Grid => {
+            let (columns, rows) = grid(svg.harmonics.len(), svg.columns);
+            writeln!(
+                out,
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}">"#,
+                columns as f64 * svg.size,
+                rows as f64 * svg.size
+            )
+            .unwrap();
+            for (i, &n) in svg.harmonics.iter().enumerate() {
+                writeln!(
+                    out,
+                    r#"<svg id="harmonics-{n}" x="{}" y="{}" width="{size}" height="{size}" viewBox="{view}">"#,
+                    (i % columns) as f64 * svg.size,
+                    (i / columns) as f64 * svg.size,
+                    size = svg.size
+                )
+                .unwrap();
+                out += &background_markup(&svg.background, vb_x, vb_y, vb_size, vb_size);
+                out += &contour;
+                out += &reconstruction_markup(chains, opts, n, i, svg.circles_at, scale);
+                writeln!(
+                    out,
+                    r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" fill="{CONTOUR_COLOR}">N = {n}</text>"#,
+                    vb_x + 2.0 * scale,
+                    vb_y + 6.0 * scale,
+                    4.0 * scale
+                )
+                .unwrap();
+                out += "</svg>\n";
+            }
+        }
+    }
+    out += "</svg>\n";
+    out
+}
+
+/// Columns and rows of a grid of `count` tiles with `columns` tiles per row
+/// (defaults to a square-ish grid).
+pub(crate) fn grid(count: usize, columns: Option<usize>) -> (usize, usize) {
+    let count = count.max(1);
+    let columns = columns
+        .unwrap_or_else(|| (count as f64).sqrt().ceil() as usize)
+        .clamp(1, count);
+    (columns, count.div_ceil(columns))
+}
+
+/// Points of a chain's contour in canvas coordinates.
+pub(crate) fn placed_points(chain: &Chain) -> impl Iterator<Item = (f64, f64)> + '_ {
+    let (dx, dy) = chain.offset;
+    chain
+        .contour
+        .points
+        .iter()
+        .map(move |&(x, y)| (x + dx, y + dy))
+}
+
+pub(crate) fn background_markup(color: &str, x: f64, y: f64, w: f64, h: f64) -> String {
+    if color == "none" {
+        return String::new();
+    }
+    let color = html_escape(color);
+    format!(r#"<rect x="{x}" y="{y}" width="{w}" height="{h}" fill="{color}"/>"#) + "\n"
+}
+
+pub(crate) fn contour_markup(
+    chains: &[Chain],
+    opts: &EmbedOptions,
+    scale: f64,
+    color: &str,
+) -> String {
+    let d = chains
+        .iter()
+        .map(|c| {
+            svg_path_of_contour(&Contour {
+                points: placed_points(c).collect(),
+            })
+        })
+        .filter(|d| !d.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        r#"<path d="{d}" fill="none" stroke="{color}" stroke-width="{}" stroke-linejoin="round"/>"#,
+        opts.contour_width * scale
+    ) + "\n"
+}
+
+/// Reconstruction of every chain with `n` harmonics, in the trace color of
+/// loop `i`, and its circles at time `circles_at` if requested.
+pub(crate) fn reconstruction_markup(
+    chains: &[Chain],
+    opts: &EmbedOptions,
+    n: usize,
+    i: usize,
+    circles_at: Option<f64>,
+    scale: f64,
+) -> String {
+    let mut out = String::new();
+    for chain in chains {
+        let fourier = chain.fourier.truncated(n.max(1));
+        let color = html_escape(trace_color(chain, opts, i));
+        writeln!(
+            out,
+            r#"<path d="{}" fill="none" stroke="{color}" stroke-opacity="{}" stroke-width="{}" stroke-linejoin="round"/>"#,
+            closed_path(&samples(&fourier, chain.offset, 0.0), scale),
+            opts.opacity,
+            opts.trace_width * scale
+        )
+        .unwrap();
+        if let Some(t) = circles_at {
+            out += &circles_markup(chain, &fourier, t, opts, scale);
+        }
+    }
+    out
+}
+
+/// Trace color of `chain` in loop `i`, as in the pages.
+pub(crate) fn trace_color<'a>(chain: &'a Chain, opts: &'a EmbedOptions, i: usize) -> &'a str {
+    let colors = chain
+        .style
+        .trace_colors
+        .as_ref()
+        .unwrap_or(&opts.trace_colors);
+    colors.get(i % colors.len().max(1)).map_or("red", |c| c)
+}
+
+/// Points of one period of `fourier` from time `phase`, moved by `offset`,
+/// sampled finely enough for its highest frequency.
+pub(crate) fn samples(
+    fourier: &FourierDecomposition,
+    (dx, dy): (f64, f64),
+    phase: f64,
+) -> Vec<(f64, f64)> {
+    let max_freq = fourier
+        .coeffs
+        .iter()
+        .map(|c| c.freq.unsigned_abs() as usize)
+        .max()
+        .unwrap_or(0);
+    let n = (max_freq * 8).clamp(1000, 20000);
+    (0..n)
+        .map(|j| {
+            let (x, y) = fourier.eval(phase + j as f64 / n as f64);
+            (x + dx, y + dy)
+        })
+        .collect()
+}
+
+/// Path data of the closed loop through `points`, with coordinates rounded
+/// to a ten-thousandth of a view whose page scale is `scale`.
+pub(crate) fn closed_path(points: &[(f64, f64)], scale: f64) -> String {
+    let decimals = (2.0 - scale.log10()).ceil().clamp(0.0, 15.0) as usize;
+    let mut d = String::new();
+    for (j, (x, y)) in points.iter().enumerate() {
+        let command = if j == 0 { "M" } else { "L" };
+        write!(d, "{command}{x:.decimals$} {y:.decimals$} ").unwrap();
+    }
+    d + "Z"
+}
+
+/// Epicycle circles of `fourier` at time `t`, drawn as in the pages.
+fn circles_markup(
+    chain: &Chain,
+    fourier: &FourierDecomposition,
+    t: f64,
+    opts: &EmbedOptions,
+    scale: f64,
+) -> String {
+    let colors = circle_colors(chain);
+    let tt = t + chain.style.phase;
+    let (mut cx, mut cy) = chain.offset;
+    let mut out = String::from("<g>");
+    for (k, c) in fourier.coeffs.iter().enumerate() {
+        let theta = std::f64::consts::TAU * c.freq as f64 * tt;
+        let nx = cx + c.re * theta.cos() - c.im * theta.sin();
+        let ny = cy + c.im * theta.cos() + c.re * theta.sin();
+        let color = &colors[k % colors.len()];
+        write!(
+            out,
+            r#"<circle cx="{cx}" cy="{cy}" r="{r}" fill="none" stroke="{color}" stroke-width="{w}" stroke-dasharray="{scale} {scale}"/><line x1="{cx}" y1="{cy}" x2="{nx}" y2="{ny}" stroke="{color}" stroke-width="{w}"/><circle cx="{nx}" cy="{ny}" r="{dot}" fill="{color}"/>"#,
+            r = c.radius(),
+            w = 0.3 * scale,
+            dot = 0.8 * scale
+        )
+        .unwrap();
+        (cx, cy) = (nx, ny);
+    }
+    if opts.show_point {
+        write!(
+            out,
+            r#"<circle cx="{cx}" cy="{cy}" r="{}" fill="white" stroke="orange" stroke-width="{}"/>"#,
+            0.7 * scale,
+            0.3 * scale
+        )
+        .unwrap();
+    }
+    out + "</g>\n"
+}
+
+/// Circle colors of `chain`, escaped for attributes.
+fn circle_colors(chain: &Chain) -> Vec<String> {
+    match &chain.style.circle_colors {
+        Some(colors) if !colors.is_empty() => colors.iter().map(|c| html_escape(c)).collect(),
+        _ => CIRCLE_COLORS.iter().map(|c| c.to_string()).collect(),
+    }
+}
+
+/// Options of `animated_svg`.
+pub struct AnimatedSvgOptions {
+    /// Number of terms drawn, the same in every loop
+    pub harmonics: usize,
+    /// Length of one loop in seconds
+    pub duration: f64,
+    /// Width and height of the picture in px
+    pub size: f64,
+    /// Fill color behind the drawing, or `none`
+    pub background: String,
+}
+
+/// Key frames of the trace's `stroke-dashoffset`, with the trace drawn as a
+/// path of length 1.
+const TRACE_KEY_FRAMES: usize = 200;
+
+/// SVG animating the chains without scripts: each epicycle turns in a
+/// nested group with `animateTransform`, and the trace is revealed by a
+/// `stroke-dashoffset` animation.
+pub fn animated_svg(
+    chains: &[Chain],
+    timing: ChainTiming,
+    opts: &EmbedOptions,
+    svg: &AnimatedSvgOptions,
+) -> String {
+    let bbox =
+        BoundingBox::of_points(chains.iter().flat_map(placed_points)).unwrap_or(BoundingBox {
+            min: (0.0, 0.0),
+            max: (100.0, 100.0),
+        });
+    let (vb_x, vb_y, vb_size) = view_box(&bbox);
+    let scale = vb_size / 100.0;
+    let dur = svg.duration;
+    // Every loop of the animation is the same, so a congruence, which picks
+    // loops by index, cannot apply: it is ignored and the part always shown
+    let shown = |w: &WhenToShow| match w {
+        WhenToShow::Always | WhenToShow::Congruence(_) => true,
+        WhenToShow::Never => false,
+    };
+
+    let mut out = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="{vb_x} {vb_y} {vb_size} {vb_size}">"#,
+        size = svg.size
+    ) + "\n";
+    out += &background_markup(&svg.background, vb_x, vb_y, vb_size, vb_size);
+    if shown(&opts.show_contour) {
+        out += &contour_markup(chains, opts, scale, CONTOUR_COLOR);
+    }
+    for (chain, (start, end)) in chains.iter().zip(chain_windows(chains, timing)) {
+        let fourier = chain.fourier.truncated(svg.harmonics.max(1));
+        if fourier.coeffs.is_empty() || end <= start {
+            continue;
+        }
+        // Loop time of chain time `tau`
+        let at = |tau: f64| start + (end - start) * tau;
+
+        if shown(&opts.show_trace) {
+            let points = samples(&fourier, chain.offset, chain.style.phase);
+            // Drawn share of the trace follows its length, not time
+            let mut lengths = vec![0.0];
+            for (j, &(x, y)) in points.iter().enumerate() {
+                let (nx, ny) = points[(j + 1) % points.len()];
+                lengths.push(lengths[j] + (nx - x).hypot(ny - y));
+            }
+            let total = lengths[points.len()].max(f64::MIN_POSITIVE);
+            let mut frames = vec![];
+            if start > 0.0 {
+                frames.push((0.0, 1.0));
+            }
+            for f in 0..=TRACE_KEY_FRAMES {
+                let j = f * points.len() / TRACE_KEY_FRAMES;
+                frames.push((
+                    at(f as f64 / TRACE_KEY_FRAMES as f64),
+                    1.0 - lengths[j] / total,
+                ));
+            }
+            if end < 1.0 {
+                frames.push((1.0, 0.0));
+            }
+            let colors = chain
+                .style
+                .trace_colors
+                .as_ref()
+                .unwrap_or(&opts.trace_colors);
+            writeln!(
+                out,
+                r#"<path d="{}" pathLength="1" stroke-dasharray="1 1" stroke-dashoffset="1" fill="none" stroke="{}" stroke-opacity="{}" stroke-width="{}" stroke-linejoin="round"><animate attributeName="stroke-dashoffset" {} dur="{dur}s" repeatCount="indefinite"/></path>"#,
+                closed_path(&points, scale),
+                html_escape(colors.first().map_or("red", |c| c)),
+                opts.opacity,
+                opts.trace_width * scale,
+                key_frames(&frames)
+            )
+            .unwrap();
+        }
+
+        let (ox, oy) = chain.offset;
+        write!(out, r#"<g transform="translate({ox} {oy})">"#).unwrap();
+        if start > 0.0 || end < 1.0 {
+            // Circles only while the chain draws
+            write!(
+                out,
+                r#"<animate attributeName="visibility" values="hidden;visible;hidden" keyTimes="0;{start};{end}" calcMode="discrete" dur="{dur}s" repeatCount="indefinite"/>"#
+            )
+            .unwrap();
+        }
+        let colors = circle_colors(chain);
+        let show_circles = shown(&opts.show_fourier_circles);
+        // Each term turns relative to the previous one, in degrees
+        let (mut angle, mut freq) = (0.0, 0.0);
+        for (k, c) in fourier.coeffs.iter().enumerate() {
+            let phi = c.im.atan2(c.re).to_degrees();
+            let turn =
+                |tau: f64| phi - angle + 360.0 * (c.freq as f64 - freq) * (tau + chain.style.phase);
+            let mut frames = vec![(0.0, turn(0.0))];
+            if start > 0.0 {
+                frames.push((start, turn(0.0)));
+            }
+            frames.push((end, turn(1.0)));
+            if end < 1.0 {
+                frames.push((1.0, turn(1.0)));
+            }
+            write!(
+                out,
+                r#"<g><animateTransform attributeName="transform" type="rotate" {} dur="{dur}s" repeatCount="indefinite"/>"#,
+                key_frames(&frames)
+            )
+            .unwrap();
+            let r = c.radius();
+            if show_circles {
+                let color = &colors[k % colors.len()];
+                write!(
+                    out,
+                    r#"<circle r="{r}" fill="none" stroke="{color}" stroke-width="{w}" stroke-dasharray="{scale} {scale}"/><line x2="{r}" stroke="{color}" stroke-width="{w}"/><circle cx="{r}" r="{dot}" fill="{color}"/>"#,
+                    w = 0.3 * scale,
+                    dot = 0.8 * scale
+                )
+                .unwrap();
+            }
+            write!(out, r#"<g transform="translate({r} 0)">"#).unwrap();
+            (angle, freq) = (phi, c.freq as f64);
+        }
+        if opts.show_point {
+            write!(
+                out,
+                r#"<circle r="{}" fill="white" stroke="orange" stroke-width="{}"/>"#,
+                0.7 * scale,
+                0.3 * scale
+            )
+            .unwrap();
+        }
+        out += &"</g></g>".repeat(fourier.coeffs.len());
+        out += "</g>\n";
+    }
+    out += "</svg>\n";
+    out
+}
+
+/// `values` and `keyTimes` attributes of `(time, value)` frames.
+fn key_frames(frames: &[(f64, f64)]) -> String {
+    let join = |f: fn(&(f64, f64)) -> f64| {
+        frames
+            .iter()
+            .map(|p| f(p).to_string())
+            .collect::<Vec<_>>()
+            .join(";")
+    };
+    format!(r#"values="{}" keyTimes="{}""#, join(|p| p.1), join(|p| p.0))
+}
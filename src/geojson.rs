@@ -0,0 +1,222 @@
+use serde_json::Value;
+
+use crate::contour::Contour;
+
+/// Map projection from lon/lat degrees to plane coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Projection {
+    /// Plate carrée, with longitudes scaled by cos(origin latitude)
+    Equirectangular,
+    /// Web/spherical Mercator
+    Mercator,
+}
+
+impl Projection {
+    /// Project `(lon, lat)` relative to `origin`, in degrees at the origin.
+    /// The y axis points down (north is up on screen).
+    pub fn project(&self, (lon, lat): (f64, f64), (lon0, lat0): (f64, f64)) -> (f64, f64) {
+        let x = lon - lon0;
+        match self {
+            Projection::Equirectangular => (x * lat0.to_radians().cos(), -(lat - lat0)),
+            Projection::Mercator => {
+                // Clamp to avoid infinities at the poles
+                let merc = |lat: f64| {
+                    let phi = lat.clamp(-85.0, 85.0).to_radians();
+                    (std::f64::consts::FRAC_PI_4 + phi / 2.0)
+                        .tan()
+                        .ln()
+                        .to_degrees()
+                };
+                (x, -(merc(lat) - merc(lat0)))
+            }
+        }
+    }
+}
+
+pub struct GeoOptions {
+    /// Keep only features whose property matches one of these `(key, value)`
+    /// pairs; all features if empty
+    pub select: Vec<(String, String)>,
+    pub projection: Projection,
+    /// Projection origin `(lon, lat)`; defaults to the center of the selected
+    /// features' bounding box
+    pub origin: Option<(f64, f64)>,
+    /// Skip polygon holes (interior rings)
+    pub outer_only: bool,
+}
+
+impl Default for GeoOptions {
+    fn default() -> Self {
+        Self {
+            select: vec![],
+            projection: Projection::Equirectangular,
+            origin: None,
+            outer_only: false,
+        }
+    }
+}
+
+/// One projected ring or line of the input.
+pub struct GeoPart {
+    pub contour: Contour,
+    /// Polygon rings are closed; line strings are open
+    pub closed: bool,
+}
+
+/// Parse a `SELECTOR` of the form `KEY=VALUE`.
+pub fn parse_selector(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .ok_or_else(|| format!("selector must be KEY=VALUE: {s}"))
+}
+
+fn property_matches(feature: &Value, select: &[(String, String)]) -> bool {
+    if select.is_empty() {
+        return true;
+    }
+    let props = feature.get("properties");
+    select
+        .iter()
+        .any(|(k, v)| match props.and_then(|p| p.get(k)) {
+            Some(Value::String(s)) => s == v,
+            Some(Value::Number(n)) => n.to_string() == v.as_str(),
+            Some(Value::Bool(b)) => b.to_string() == v.as_str(),
+            _ => false,
+        })
+}
+
+fn position(v: &Value) -> Result<(f64, f64), String> {
+    let pair = v.as_array().filter(|a| a.len() >= 2);
+    match pair.map(|a| (a[0].as_f64(), a[1].as_f64())) {
+        Some((Some(lon), Some(lat))) => Ok((lon, lat)),
+        _ => Err(format!("invalid position: {v}")),
+    }
+}
+
+fn line(v: &Value) -> Result<Vec<(f64, f64)>, String> {
+    v.as_array()
+        .ok_or_else(|| format!("expected an array of positions: {v}"))?
+        .iter()
+        .map(position)
+        .collect()
+}
+
+/// Collect raw (unprojected) rings and lines of a geometry.
+fn collect_geometry(
+    geometry: &Value,
+    outer_only: bool,
+    out: &mut Vec<(Vec<(f64, f64)>, bool)>,
+) -> Result<(), String> {
+    let kind = geometry.get("type").and_then(Value::as_str).unwrap_or("");
+    let coords = geometry.get("coordinates").unwrap_or(&Value::Null);
+    let polygon = |rings: &Value, out: &mut Vec<(Vec<(f64, f64)>, bool)>| {
+        let rings = rings
+            .as_array()
+            .ok_or("Polygon: expected an array of rings")?;
+        for (i, ring) in rings.iter().enumerate() {
+            if i > 0 && outer_only {
+                break;
+            }
+            out.push((line(ring)?, true));
+        }
+        Ok::<(), String>(())
+    };
+    match kind {
+        "Polygon" => polygon(coords, out)?,
+        "MultiPolygon" => {
+            for p in coords.as_array().ok_or("MultiPolygon: expected an array")? {
+                polygon(p, out)?;
+            }
+        }
+        "LineString" => out.push((line(coords)?, false)),
+        "MultiLineString" => {
+            for l in coords
+                .as_array()
+                .ok_or("MultiLineString: expected an array")?
+            {
+                out.push((line(l)?, false));
+            }
+        }
+        "GeometryCollection" => {
+            for g in geometry
+                .get("geometries")
+                .and_then(Value::as_array)
+                .ok_or("GeometryCollection: expected geometries")?
+            {
+                collect_geometry(g, outer_only, out)?;
+            }
+        }
+        // Points carry no outline
+        "Point" | "MultiPoint" => {}
+        other => return Err(format!("unsupported geometry type: {other:?}")),
+    }
+    Ok(())
+}
+
+/// Read Polygon, MultiPolygon, LineString and MultiLineString geometries
+/// from a FeatureCollection, a Feature or a bare geometry, and project them.
+pub fn parse_geojson(text: &str, opts: &GeoOptions) -> Result<Vec<GeoPart>, String> {
+    let root: Value = serde_json::from_str(text).map_err(|e| format!("GeoJSON: {e}"))?;
+    let features: Vec<&Value> = match root.get("type").and_then(Value::as_str) {
+        Some("FeatureCollection") => root
+            .get("features")
+            .and_then(Value::as_array)
+            .ok_or("GeoJSON: FeatureCollection without features")?
+            .iter()
+            .collect(),
+        Some("Feature") => vec![&root],
+        Some(_) => {
+            if !opts.select.is_empty() {
+                return Err("GeoJSON: selectors need features with properties".to_string());
+            }
+            let mut raw = Vec::new();
+            collect_geometry(&root, opts.outer_only, &mut raw)
+                .map_err(|e| format!("GeoJSON: {e}"))?;
+            return Ok(project_parts(raw, opts));
+        }
+        None => return Err("GeoJSON: missing \"type\"".to_string()),
+    };
+
+    let mut raw = Vec::new();
+    for feature in features {
+        if !property_matches(feature, &opts.select) {
+            continue;
+        }
+        if let Some(geometry) = feature.get("geometry").filter(|g| !g.is_null()) {
+            collect_geometry(geometry, opts.outer_only, &mut raw)
+                .map_err(|e| format!("GeoJSON: {e}"))?;
+        }
+    }
+    Ok(project_parts(raw, opts))
+}
+
+fn project_parts(raw: Vec<(Vec<(f64, f64)>, bool)>, opts: &GeoOptions) -> Vec<GeoPart> {
+    let origin = opts.origin.unwrap_or_else(|| {
+        let (mut min_lon, mut min_lat) = (f64::MAX, f64::MAX);
+        let (mut max_lon, mut max_lat) = (f64::MIN, f64::MIN);
+        for &(lon, lat) in raw.iter().flat_map(|(pts, _)| pts) {
+            min_lon = min_lon.min(lon);
+            max_lon = max_lon.max(lon);
+            min_lat = min_lat.min(lat);
+            max_lat = max_lat.max(lat);
+        }
+        if min_lon > max_lon {
+            (0.0, 0.0)
+        } else {
+            ((min_lon + max_lon) / 2.0, (min_lat + max_lat) / 2.0)
+        }
+    });
+    raw.into_iter()
+        .filter(|(pts, _)| pts.len() > 1)
+        .map(|(pts, closed)| GeoPart {
+            contour: Contour {
+                points: pts
+                    .into_iter()
+                    .map(|p| opts.projection.project(p, origin))
+                    .collect(),
+            },
+            closed,
+        })
+        .collect()
+}
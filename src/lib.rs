@@ -1,6 +1,18 @@
+pub mod boolean;
 pub mod canvas;
 pub mod contour;
+pub mod equation;
+pub mod export;
+pub mod geojson;
 pub mod model;
+pub mod points;
+pub mod poster;
+pub mod raster;
+pub mod render;
+pub mod scene;
+pub mod shape;
+pub mod stroke_font;
+pub mod svg;
 pub mod text;
 
 #[cfg(test)]
@@ -1,6 +1,14 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+use crate::contour::{
+    Contour, Orientation, StartAnchor, simplify_douglas_peucker, simplify_visvalingam,
+    smooth_gaussian, smooth_low_pass, smooth_moving_average, smooth_savitzky_golay,
+};
+use crate::equation::ComplexMap;
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct HarmonicRange {
     pub from: usize,
     pub step: usize,
@@ -8,12 +16,36 @@ pub struct HarmonicRange {
     pub speed: f64,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct HarmonicSteps {
     pub ranges: Vec<HarmonicRange>,
 }
 
 impl HarmonicSteps {
+    /// Parse ranges written as in the pages' steps field, `from step to
+    /// speed` separated by `;` (e.g. `1 1 10 3 ; 10 5 100 3`).
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut ranges = vec![];
+        for group in s.split(';').map(str::trim).filter(|g| !g.is_empty()) {
+            let parts: Vec<&str> = group.split_whitespace().collect();
+            let [from, step, to, speed] = parts[..] else {
+                return Err(format!("range must be `from step to speed`: {group}"));
+            };
+            let number = |s: &str| s.parse().map_err(|_| format!("invalid number: {s}"));
+            ranges.push(HarmonicRange {
+                from: number(from)?,
+                step: number(step)?,
+                to: number(to)?,
+                speed: speed
+                    .parse()
+                    .map_err(|_| format!("invalid number: {speed}"))?,
+            });
+        }
+        let steps = HarmonicSteps { ranges };
+        steps.validate()?;
+        Ok(steps)
+    }
+
     pub fn validate(&self) -> Result<(), String> {
         for (i, r) in self.ranges.iter().enumerate() {
             if r.to <= r.from {
@@ -86,6 +118,191 @@ pub enum WhenToShow {
     Congruence(Congruence),
 }
 
+/// Simplification or smoothing step applied to the input contour, in config
+/// order, before it is interpolated and decomposed.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Preprocess {
+    /// Drop points within `tolerance` (input units) of the simplified outline
+    DouglasPeucker { tolerance: f64 },
+    /// Drop points whose triangle with their neighbours is smaller than
+    /// `tolerance` (square input units)
+    Visvalingam { tolerance: f64 },
+    /// Average each point with its neighbours over `window` points (odd)
+    MovingAverage { window: usize },
+    /// Gaussian smoothing with a standard deviation of `sigma` points
+    Gaussian { sigma: f64 },
+    /// Local polynomial fit of degree `order` over `window` points (odd)
+    SavitzkyGolay { window: usize, order: usize },
+    /// Drop Fourier terms with frequency above `cutoff`
+    LowPass { cutoff: usize },
+    /// Traverse the contour in this direction as drawn on the page (y down)
+    Orient { direction: Orientation },
+    /// Start the contour at the point designated by `at`
+    Start { at: StartAnchor },
+    /// Send each point `z = x + iy` to the complex expression `expr` of `z`
+    /// (see `ComplexMap`), with real `params`, after moving the centroid of
+    /// the contours to the origin if `center` is set
+    Map {
+        expr: String,
+        #[serde(default)]
+        params: HashMap<String, f64>,
+        #[serde(default)]
+        center: bool,
+    },
+}
+
+impl Preprocess {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preprocess::DouglasPeucker { .. } => "douglas-peucker",
+            Preprocess::Visvalingam { .. } => "visvalingam",
+            Preprocess::MovingAverage { .. } => "moving-average",
+            Preprocess::Gaussian { .. } => "gaussian",
+            Preprocess::SavitzkyGolay { .. } => "savitzky-golay",
+            Preprocess::LowPass { .. } => "low-pass",
+            Preprocess::Orient { .. } => "orient",
+            Preprocess::Start { .. } => "start",
+            Preprocess::Map { .. } => "map",
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Preprocess::DouglasPeucker { tolerance } | Preprocess::Visvalingam { tolerance } => {
+                if !(*tolerance >= 0.0 && tolerance.is_finite()) {
+                    return Err(format!("{}: tolerance must be >= 0", self.name()));
+                }
+            }
+            Preprocess::MovingAverage { window } | Preprocess::SavitzkyGolay { window, .. } => {
+                if window % 2 == 0 {
+                    return Err(format!("{}: window must be odd", self.name()));
+                }
+                if let Preprocess::SavitzkyGolay { order, .. } = self
+                    && order >= window
+                {
+                    return Err(format!("{}: order must be < window", self.name()));
+                }
+            }
+            Preprocess::Gaussian { sigma } => {
+                if !(*sigma > 0.0 && sigma.is_finite()) {
+                    return Err(format!("{}: sigma must be > 0", self.name()));
+                }
+            }
+            Preprocess::LowPass { cutoff } => {
+                if *cutoff == 0 {
+                    return Err(format!("{}: cutoff must be > 0", self.name()));
+                }
+            }
+            Preprocess::Start {
+                at: StartAnchor::Angle(degrees),
+            } => {
+                if !degrees.is_finite() {
+                    return Err(format!("{}: angle must be a number", self.name()));
+                }
+            }
+            Preprocess::Map { .. } => {
+                self.complex_map()?;
+            }
+            Preprocess::Orient { .. } | Preprocess::Start { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// The parsed expression of a `Map` step.
+    fn complex_map(&self) -> Result<Option<ComplexMap>, String> {
+        match self {
+            Preprocess::Map { expr, params, .. } => ComplexMap::new(expr, params.clone())
+                .map(Some)
+                .map_err(|e| format!("{}: {expr}: {e}", self.name())),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn apply(&self, contour: &Contour) -> Result<Contour, String> {
+        let mut contour = contour.clone();
+        self.apply_each(&mut [&mut contour])?;
+        Ok(contour)
+    }
+
+    /// Apply the step to each of `contours`, parsing a map only once. A
+    /// centered map moves all of them by the same offset.
+    pub fn apply_each(&self, contours: &mut [&mut Contour]) -> Result<(), String> {
+        let map = self.complex_map()?;
+        let center = match self {
+            Preprocess::Map { center: true, .. } => common_centroid(contours),
+            _ => (0.0, 0.0),
+        };
+        for contour in contours {
+            **contour = match &map {
+                Some(map) => map.apply(&Contour {
+                    points: contour
+                        .points
+                        .iter()
+                        .map(|&(x, y)| (x - center.0, y - center.1))
+                        .collect(),
+                }),
+                None => self.apply_unmapped(contour),
+            };
+        }
+        Ok(())
+    }
+
+    fn apply_unmapped(&self, contour: &Contour) -> Contour {
+        match self {
+            Preprocess::DouglasPeucker { tolerance } => {
+                simplify_douglas_peucker(contour, *tolerance)
+            }
+            Preprocess::Visvalingam { tolerance } => simplify_visvalingam(contour, *tolerance),
+            Preprocess::MovingAverage { window } => smooth_moving_average(contour, *window),
+            Preprocess::Gaussian { sigma } => smooth_gaussian(contour, *sigma),
+            Preprocess::SavitzkyGolay { window, order } => {
+                smooth_savitzky_golay(contour, *window, *order)
+            }
+            Preprocess::LowPass { cutoff } => smooth_low_pass(contour, *cutoff),
+            // The page is drawn with y down, which swaps the y-up directions
+            Preprocess::Orient { direction } => contour.oriented(*direction),
+            Preprocess::Start { at } => contour.with_start(*at),
+            Preprocess::Map { .. } => contour.clone(),
+        }
+    }
+}
+
+/// Centroid of `contours` together, each weighted by its enclosed area, or
+/// equally if none encloses any.
+fn common_centroid(contours: &[&mut Contour]) -> (f64, f64) {
+    let centroids: Vec<((f64, f64), f64)> = contours
+        .iter()
+        .filter_map(|c| Some((c.centroid()?, c.signed_area().abs())))
+        .collect();
+    let total: f64 = centroids.iter().map(|(_, a)| a).sum();
+    let weight = |a: f64| {
+        if total > 0.0 {
+            a / total
+        } else {
+            1.0 / centroids.len() as f64
+        }
+    };
+    centroids.iter().fold((0.0, 0.0), |(x, y), &((cx, cy), a)| {
+        (x + weight(a) * cx, y + weight(a) * cy)
+    })
+}
+
+/// How the page draws the Fourier series.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Visualization {
+    /// One chain of rotating circles whose tip is the pen
+    #[default]
+    Epicycles,
+    /// Real series of x(t) and y(t): one chain along the top drives x, one
+    /// along the left side drives y, and guide lines meet at the pen. A
+    /// harmonic count is the number of real terms of each chain, i.e. of
+    /// frequencies |k|
+    TwoAxis,
+    /// One rotating ellipse per frequency |k|, combining the terms k and -k.
+    /// A harmonic count is the number of ellipses
+    Ellipses,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct EmbedOptions {
     pub max_harmonics: usize,
@@ -103,6 +320,10 @@ pub struct EmbedOptions {
     pub trace_colors: Vec<String>,
     #[serde(default)]
     pub flip_y: bool,
+    #[serde(default)]
+    pub preprocess: Vec<Preprocess>,
+    #[serde(default)]
+    pub visualization: Visualization,
 }
 
 fn default_trace_colors() -> Vec<String> {
@@ -129,8 +350,17 @@ impl EmbedOptions {
         if let WhenToShow::Congruence(e) = &self.show_fourier_circles {
             e.validate("show_fourier_circles")?;
         }
+        for step in &self.preprocess {
+            step.validate()?;
+        }
         Ok(())
     }
+
+    /// `point` in page coordinates, where y points down: with its y negated
+    /// if `flip_y` is set.
+    pub fn page_point(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        if self.flip_y { (x, -y) } else { (x, y) }
+    }
 }
 
 impl Default for EmbedOptions {
@@ -149,6 +379,8 @@ impl Default for EmbedOptions {
             show_fourier_circles: WhenToShow::Always,
             trace_colors: default_trace_colors(),
             flip_y: false,
+            preprocess: vec![],
+            visualization: Visualization::Epicycles,
         }
     }
 }
@@ -0,0 +1,202 @@
+use std::io::Read;
+use std::path::Path;
+
+use crate::contour::Contour;
+
+/// File formats accepted for point lists.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum PointsFormat {
+    /// `points:` list of `[x, y]` pairs (the `Contour` format)
+    Yaml,
+    /// Delimited text with one point per row
+    Csv,
+    /// `[[x, y], ...]`, `[{"x": .., "y": ..}, ...]` or `{"points": [...]}`
+    Json,
+}
+
+impl PointsFormat {
+    /// Guess the format from a file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "yml" | "yaml" => Some(PointsFormat::Yaml),
+            "csv" | "tsv" | "txt" => Some(PointsFormat::Csv),
+            "json" => Some(PointsFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from the content, for input without an extension.
+    pub fn sniff(text: &str) -> Self {
+        let trimmed = text.trim_start();
+        if trimmed.starts_with('[') || trimmed.starts_with('{') {
+            PointsFormat::Json
+        } else if trimmed.starts_with("points:") || trimmed.starts_with("---") {
+            PointsFormat::Yaml
+        } else {
+            PointsFormat::Csv
+        }
+    }
+}
+
+/// Column selection for CSV input. Columns are given by header name or by
+/// 0-based index; by default the `x` and `y` columns if the header has them,
+/// else the first two.
+#[derive(Default)]
+pub struct CsvOptions {
+    pub x_column: Option<String>,
+    pub y_column: Option<String>,
+}
+
+pub fn parse_points(text: &str, format: PointsFormat, csv: &CsvOptions) -> Result<Contour, String> {
+    let contour = match format {
+        PointsFormat::Yaml => serde_yaml::from_str(text).map_err(|e| format!("YAML: {e}"))?,
+        PointsFormat::Json => parse_json(text)?,
+        PointsFormat::Csv => parse_csv(text, csv)?,
+    };
+    if contour.points.is_empty() {
+        return Err("no points found".to_string());
+    }
+    Ok(contour)
+}
+
+/// Read points from `path`, or from standard input if `path` is `-`. Without
+/// an explicit `format`, it is guessed from the extension, then the content.
+pub fn read_points(
+    path: &str,
+    format: Option<PointsFormat>,
+    csv: &CsvOptions,
+) -> Result<Contour, String> {
+    let text = if path == "-" {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|e| format!("stdin: {e}"))?;
+        text
+    } else {
+        std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?
+    };
+    let format = format
+        .or_else(|| PointsFormat::from_path(Path::new(path)))
+        .unwrap_or_else(|| PointsFormat::sniff(&text));
+    parse_points(&text, format, csv)
+}
+
+fn json_number(v: &serde_json::Value, what: &str) -> Result<f64, String> {
+    v.as_f64()
+        .ok_or_else(|| format!("JSON: {what} is not a number: {v}"))
+}
+
+fn parse_json(text: &str) -> Result<Contour, String> {
+    let value: serde_json::Value = serde_json::from_str(text).map_err(|e| format!("JSON: {e}"))?;
+    let items = match &value {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Object(obj) => obj
+            .get("points")
+            .and_then(|p| p.as_array())
+            .ok_or("JSON: object must have a \"points\" array")?,
+        _ => return Err("JSON: expected an array of points".to_string()),
+    };
+    let points = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| match item {
+            serde_json::Value::Array(pair) if pair.len() >= 2 => Ok((
+                json_number(&pair[0], &format!("point {i} x"))?,
+                json_number(&pair[1], &format!("point {i} y"))?,
+            )),
+            serde_json::Value::Object(obj) => {
+                let get = |k: &str| {
+                    obj.get(k)
+                        .ok_or_else(|| format!("JSON: point {i} has no \"{k}\""))
+                        .and_then(|v| json_number(v, &format!("point {i} {k}")))
+                };
+                Ok((get("x")?, get("y")?))
+            }
+            _ => Err(format!(
+                "JSON: point {i} must be [x, y] or {{\"x\", \"y\"}}"
+            )),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Contour { points })
+}
+
+/// Pick the most frequent of `,`, `;` and tab in the first line, falling back
+/// to whitespace-separated columns.
+fn csv_delimiter(line: &str) -> Option<char> {
+    [',', ';', '\t']
+        .into_iter()
+        .map(|d| (d, line.matches(d).count()))
+        .filter(|&(_, n)| n > 0)
+        .max_by_key(|&(_, n)| n)
+        .map(|(d, _)| d)
+}
+
+fn split_row(line: &str, delimiter: Option<char>) -> Vec<String> {
+    let cells: Vec<&str> = match delimiter {
+        Some(d) => line.split(d).collect(),
+        None => line.split_whitespace().collect(),
+    };
+    cells
+        .iter()
+        .map(|c| c.trim().trim_matches('"').to_string())
+        .collect()
+}
+
+fn column_index(spec: &str, header: Option<&[String]>) -> Result<usize, String> {
+    if let Some(header) = header
+        && let Some(i) = header.iter().position(|h| h.eq_ignore_ascii_case(spec))
+    {
+        return Ok(i);
+    }
+    spec.parse::<usize>().map_err(|_| match header {
+        Some(h) => format!("CSV: no column '{spec}' (columns: {})", h.join(", ")),
+        None => format!("CSV: no header row, column '{spec}' must be an index"),
+    })
+}
+
+fn parse_csv(text: &str, opts: &CsvOptions) -> Result<Contour, String> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .peekable();
+    let Some(&(_, first)) = lines.peek() else {
+        return Ok(Contour { points: vec![] });
+    };
+    let delimiter = csv_delimiter(first);
+    let first_row = split_row(first, delimiter);
+    // A first row that is not entirely numeric is a header
+    let header = if first_row.iter().any(|c| c.parse::<f64>().is_err()) {
+        lines.next();
+        Some(first_row)
+    } else {
+        None
+    };
+
+    let pick = |spec: &Option<String>, name: &str, fallback: usize| match spec {
+        Some(s) => column_index(s, header.as_deref()),
+        None => Ok(header
+            .as_ref()
+            .and_then(|h| h.iter().position(|c| c.eq_ignore_ascii_case(name)))
+            .unwrap_or(fallback)),
+    };
+    let xi = pick(&opts.x_column, "x", 0)?;
+    let yi = pick(&opts.y_column, "y", 1)?;
+
+    let points = lines
+        .map(|(n, line)| {
+            let row = split_row(line, delimiter);
+            let cell = |i: usize| {
+                let c = row
+                    .get(i)
+                    .ok_or_else(|| format!("CSV line {}: missing column {i}", n + 1))?;
+                c.parse::<f64>()
+                    .map_err(|_| format!("CSV line {}: not a number: '{c}'", n + 1))
+            };
+            Ok((cell(xi)?, cell(yi)?))
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(Contour { points })
+}
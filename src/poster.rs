@@ -0,0 +1,285 @@
+use std::fmt::Write;
+
+use tiny_skia::{Color, LineCap, LineJoin, Pixmap, Stroke, Transform};
+
+use crate::canvas::{Chain, harmonics_schedule, view_box};
+use crate::contour::BoundingBox;
+use crate::export::{
+    CONTOUR_COLOR, background_markup, contour_markup, grid, placed_points, reconstruction_markup,
+    samples, trace_color,
+};
+use crate::model::{EmbedOptions, HarmonicSteps};
+use crate::render::{paint, parse_color, path_of_polylines};
+use crate::stroke_font::{StrokeFont, stroke_text};
+use crate::text::TextLayout;
+
+/// How a poster measures the distance between a contour and a
+/// reconstruction, each point of the contour being compared with the
+/// reconstruction at the same time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ErrorMetric {
+    /// Root mean square distance
+    Rms,
+    /// Largest distance
+    Max,
+}
+
+impl ErrorMetric {
+    fn label(self) -> &'static str {
+        match self {
+            ErrorMetric::Rms => "RMS",
+            ErrorMetric::Max => "max",
+        }
+    }
+}
+
+/// One tile of a poster.
+pub struct Tile {
+    pub harmonics: usize,
+    /// First loop of the schedule drawn with `harmonics`, whose trace color
+    /// the tile uses
+    pub loop_index: usize,
+    /// Error of the reconstruction, as a fraction of the diagonal of the
+    /// contours' bounding box
+    pub error: f64,
+}
+
+/// Options of `poster_svg` and `poster_png`.
+pub struct PosterOptions {
+    pub metric: ErrorMetric,
+    /// Tiles per row (defaults to a square-ish grid)
+    pub columns: Option<usize>,
+    /// Width and height of each tile in px
+    pub size: u32,
+    /// Fill color behind each tile, or `none`
+    pub background: String,
+}
+
+/// One tile per distinct harmonic count of the loops of `steps`, with the
+/// error of its reconstruction.
+pub fn poster_tiles(
+    chains: &[Chain],
+    opts: &EmbedOptions,
+    steps: &HarmonicSteps,
+    metric: ErrorMetric,
+) -> Vec<Tile> {
+    let max_nh = chains
+        .iter()
+        .map(|c| c.fourier.coeffs.len())
+        .max()
+        .map_or(1, |n| n.min(opts.max_harmonics));
+    let mut counts: Vec<(usize, usize)> = harmonics_schedule(steps, max_nh)
+        .into_iter()
+        .enumerate()
+        .collect();
+    counts.dedup_by_key(|(_, n)| *n);
+    let diagonal = BoundingBox::of_points(chains.iter().flat_map(placed_points))
+        .map_or(1.0, |b| b.width().hypot(b.height()))
+        .max(f64::MIN_POSITIVE);
+    counts
+        .into_iter()
+        .map(|(loop_index, n)| Tile {
+            harmonics: n,
+            loop_index,
+            error: reconstruction_error(chains, n, metric) / diagonal,
+        })
+        .collect()
+}
+
+/// Distance between the chains' contour points and their reconstructions
+/// with `n` harmonics.
+fn reconstruction_error(chains: &[Chain], n: usize, metric: ErrorMetric) -> f64 {
+    let (mut sum, mut max, mut count) = (0.0, 0.0, 0);
+    for chain in chains {
+        let fourier = chain.fourier.truncated(n.max(1));
+        let len = chain.contour.points.len();
+        for (j, &(x, y)) in chain.contour.points.iter().enumerate() {
+            // Point j of the contour is the series at time j / len
+            let (fx, fy) = fourier.eval(j as f64 / len as f64);
+            let d2 = (fx - x).powi(2) + (fy - y).powi(2);
+            sum += d2;
+            max = f64::max(max, d2);
+            count += 1;
+        }
+    }
+    match metric {
+        ErrorMetric::Rms => (sum / count.max(1) as f64).sqrt(),
+        ErrorMetric::Max => max.sqrt(),
+    }
+}
+
+fn error_label(tile: &Tile, metric: ErrorMetric) -> String {
+    let percent = tile.error * 100.0;
+    if percent < 0.01 {
+        format!("{} < 0.01%", metric.label())
+    } else {
+        format!("{} {percent:.2}%", metric.label())
+    }
+}
+
+/// Columns of the poster's grid, and its width and height in px.
+fn poster_grid(tiles: &[Tile], poster: &PosterOptions) -> Result<(usize, u32, u32), String> {
+    let (columns, rows) = grid(tiles.len(), poster.columns);
+    let side = |count: usize| {
+        u32::try_from(count)
+            .ok()
+            .and_then(|count| count.checked_mul(poster.size))
+            .ok_or_else(|| "the poster is too large".to_string())
+    };
+    Ok((columns, side(columns)?, side(rows)?))
+}
+
+/// Square view shared by all tiles, and the page scale in it.
+fn tile_view(chains: &[Chain]) -> ((f64, f64, f64), f64) {
+    let bbox =
+        BoundingBox::of_points(chains.iter().flat_map(placed_points)).unwrap_or(BoundingBox {
+            min: (0.0, 0.0),
+            max: (100.0, 100.0),
+        });
+    let view = view_box(&bbox);
+    (view, view.2 / 100.0)
+}
+
+/// SVG poster: a grid of `tiles`, each showing the contours and their
+/// reconstruction, labeled with the harmonic count and the error.
+pub fn poster_svg(
+    chains: &[Chain],
+    opts: &EmbedOptions,
+    tiles: &[Tile],
+    poster: &PosterOptions,
+) -> Result<String, String> {
+    let ((vb_x, vb_y, vb_size), scale) = tile_view(chains);
+    let contour = contour_markup(chains, opts, scale, CONTOUR_COLOR);
+    let size = poster.size;
+    let (columns, width, height) = poster_grid(tiles, poster)?;
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}">"#
+    )
+    .unwrap();
+    for (i, tile) in tiles.iter().enumerate() {
+        let n = tile.harmonics;
+        writeln!(
+            out,
+            r#"<svg id="harmonics-{n}" x="{}" y="{}" width="{size}" height="{size}" viewBox="{vb_x} {vb_y} {vb_size} {vb_size}">"#,
+            (i % columns) as u32 * size,
+            (i / columns) as u32 * size,
+        )
+        .unwrap();
+        out += &background_markup(&poster.background, vb_x, vb_y, vb_size, vb_size);
+        out += &contour;
+        out += &reconstruction_markup(chains, opts, n, tile.loop_index, None, scale);
+        writeln!(
+            out,
+            r#"<text x="{x}" y="{}" font-family="sans-serif" font-size="{}" fill="{CONTOUR_COLOR}">N = {n}</text><text x="{x}" y="{}" font-family="sans-serif" font-size="{}" fill="{CONTOUR_COLOR}">{}</text>"#,
+            vb_y + 6.0 * scale,
+            4.0 * scale,
+            vb_y + 10.5 * scale,
+            3.0 * scale,
+            error_label(tile, poster.metric),
+            x = vb_x + 2.0 * scale,
+        )
+        .unwrap();
+        out += "</svg>\n";
+    }
+    out += "</svg>\n";
+    Ok(out)
+}
+
+/// PNG of the same poster as `poster_svg`, with labels in the built-in
+/// stroke font.
+pub fn poster_png(
+    chains: &[Chain],
+    opts: &EmbedOptions,
+    tiles: &[Tile],
+    poster: &PosterOptions,
+) -> Result<Vec<u8>, String> {
+    let color = |name: &str| parse_color(name).ok_or_else(|| format!("unknown color: {name}"));
+    let background = match poster.background.as_str() {
+        "none" => Color::TRANSPARENT,
+        name => color(name)?,
+    };
+    let label_color = color(CONTOUR_COLOR)?;
+    let ((vb_x, vb_y, vb_size), scale) = tile_view(chains);
+    let (columns, width, height) = poster_grid(tiles, poster)?;
+    let size = poster.size;
+    let mut pixmap =
+        Pixmap::new(width, height).ok_or("the poster must be between 1 px and 2^31 px wide")?;
+    pixmap.fill(background);
+    let contours: Vec<Vec<(f64, f64)>> =
+        chains.iter().map(|c| placed_points(c).collect()).collect();
+    let contour = path_of_polylines(contours.iter().map(|c| c.as_slice()), true);
+    let font = StrokeFont::builtin();
+    let scale = scale as f32;
+    let k = (size as f64 / vb_size) as f32;
+    for (i, tile) in tiles.iter().enumerate() {
+        let left = ((i % columns) as u32 * size) as f32;
+        let top = ((i / columns) as u32 * size) as f32;
+        let view = Transform::from_row(
+            k,
+            0.0,
+            0.0,
+            k,
+            left - vb_x as f32 * k,
+            top - vb_y as f32 * k,
+        );
+        if let Some(contour) = &contour {
+            let stroke = Stroke {
+                width: opts.contour_width as f32 * scale,
+                line_join: LineJoin::Round,
+                ..Stroke::default()
+            };
+            pixmap.stroke_path(contour, &paint(label_color), &stroke, view, None);
+        }
+        let stroke = Stroke {
+            width: opts.trace_width as f32 * scale,
+            line_join: LineJoin::Round,
+            ..Stroke::default()
+        };
+        for chain in chains {
+            let mut trace = color(trace_color(chain, opts, tile.loop_index))?;
+            trace.apply_opacity(opts.opacity as f32);
+            let fourier = chain.fourier.truncated(tile.harmonics.max(1));
+            let points = samples(&fourier, chain.offset, 0.0);
+            if let Some(path) = path_of_polylines([points.as_slice()], true) {
+                pixmap.stroke_path(&path, &paint(trace), &stroke, view, None);
+            }
+        }
+        let x = vb_x as f32 + 2.0 * scale;
+        let labels = [
+            (format!("N = {}", tile.harmonics), 6.0, 4.0),
+            (error_label(tile, poster.metric), 10.5, 3.0),
+        ];
+        for (text, y, height) in labels {
+            let at = view.pre_translate(x, vb_y as f32 + y * scale);
+            draw_label(&mut pixmap, &font, &text, height * scale, at, label_color);
+        }
+    }
+    pixmap.encode_png().map_err(|e| e.to_string())
+}
+
+/// `text` in a stroke font with an em of `height`, its baseline starting
+/// at the origin of `at`.
+fn draw_label(
+    pixmap: &mut Pixmap,
+    font: &StrokeFont,
+    text: &str,
+    height: f32,
+    at: Transform,
+    color: Color,
+) {
+    let f = height / font.em as f32;
+    let laid_out = stroke_text(text, font, &TextLayout::default());
+    let strokes = laid_out.strokes.iter().map(|s| s.points.as_slice());
+    if let Some(path) = path_of_polylines(strokes, false) {
+        let stroke = Stroke {
+            width: font.em as f32 * 0.08,
+            line_cap: LineCap::Round,
+            line_join: LineJoin::Round,
+            ..Stroke::default()
+        };
+        pixmap.stroke_path(&path, &paint(color), &stroke, at.pre_scale(f, f), None);
+    }
+}
@@ -0,0 +1,598 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::contour::{Contour, polygon_area};
+
+/// 8-bit grayscale image, row-major, 0 = black.
+pub struct GrayImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl GrayImage {
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        self.pixels[y * self.width + x]
+    }
+}
+
+/// Binary image: `true` marks foreground pixels.
+pub struct Mask {
+    pub width: usize,
+    pub height: usize,
+    pub bits: Vec<bool>,
+}
+
+impl Mask {
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.bits[y * self.width + x]
+    }
+
+    /// Same as [`Mask::get`], with everything outside the image as background.
+    fn get_padded(&self, x: i64, y: i64) -> bool {
+        x >= 0
+            && y >= 0
+            && (x as usize) < self.width
+            && (y as usize) < self.height
+            && self.get(x as usize, y as usize)
+    }
+}
+
+pub struct TraceOptions {
+    /// Pixels darker than this gray level are foreground
+    pub threshold: u8,
+    /// Treat light pixels as foreground instead
+    pub invert: bool,
+    /// Drop contours enclosing less than this many square pixels
+    pub min_area: f64,
+    /// Keep every contour instead of only the largest one
+    pub all: bool,
+    /// Centerline mode: drop strokes shorter than this many pixels
+    pub min_length: f64,
+}
+
+impl Default for TraceOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 128,
+            invert: false,
+            min_area: 16.0,
+            all: false,
+            min_length: 5.0,
+        }
+    }
+}
+
+fn luminance(r: u8, g: u8, b: u8) -> u8 {
+    (0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64).round() as u8
+}
+
+/// Composite a gray level with alpha over a white background.
+fn over_white(v: u8, a: u8) -> u8 {
+    let a = a as u32;
+    ((v as u32 * a + 255 * (255 - a)) / 255) as u8
+}
+
+/// Read a PNG, or a PBM/PGM/PPM (P1–P6) file, as grayscale.
+pub fn read_image(path: &Path) -> Result<GrayImage, String> {
+    let data = fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    if data.starts_with(b"\x89PNG") {
+        decode_png(&data)
+    } else if data.first() == Some(&b'P') {
+        decode_pnm(&data)
+    } else {
+        Err(format!(
+            "{}: unsupported image format (expected PNG or PBM/PGM/PPM)",
+            path.display()
+        ))
+    }
+}
+
+pub fn decode_png(data: &[u8]) -> Result<GrayImage, String> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().map_err(|e| format!("PNG: {e}"))?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buf)
+        .map_err(|e| format!("PNG: {e}"))?;
+    let buf = &buf[..info.buffer_size()];
+    let pixels: Vec<u8> = match info.color_type {
+        png::ColorType::Grayscale => buf.to_vec(),
+        png::ColorType::GrayscaleAlpha => buf.chunks(2).map(|p| over_white(p[0], p[1])).collect(),
+        png::ColorType::Rgb => buf.chunks(3).map(|p| luminance(p[0], p[1], p[2])).collect(),
+        png::ColorType::Rgba => buf
+            .chunks(4)
+            .map(|p| over_white(luminance(p[0], p[1], p[2]), p[3]))
+            .collect(),
+        png::ColorType::Indexed => return Err("PNG: unexpanded indexed color".to_string()),
+    };
+    Ok(GrayImage {
+        width: info.width as usize,
+        height: info.height as usize,
+        pixels,
+    })
+}
+
+pub fn decode_pnm(data: &[u8]) -> Result<GrayImage, String> {
+    // Header: magic, width, height and (except for bitmaps) maxval, separated
+    // by whitespace, with `#` comments running to the end of the line.
+    let mut pos = 0;
+    let mut next_token = || -> Result<String, String> {
+        loop {
+            while pos < data.len() && data[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            if pos < data.len() && data[pos] == b'#' {
+                while pos < data.len() && data[pos] != b'\n' {
+                    pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+        let start = pos;
+        while pos < data.len() && !data[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        if start == pos {
+            return Err("PNM: truncated header".to_string());
+        }
+        Ok(String::from_utf8_lossy(&data[start..pos]).into_owned())
+    };
+    let number = |s: String| {
+        s.parse::<usize>()
+            .map_err(|_| format!("PNM: invalid number: {s}"))
+    };
+
+    let magic = next_token()?;
+    let width = number(next_token()?)?;
+    let height = number(next_token()?)?;
+    let maxval = match magic.as_str() {
+        "P1" | "P4" => 1,
+        "P2" | "P3" | "P5" | "P6" => number(next_token()?)?,
+        _ => return Err(format!("PNM: unsupported magic {magic}")),
+    };
+    if maxval == 0 || maxval > 65535 {
+        return Err(format!("PNM: invalid maxval {maxval}"));
+    }
+    let scale = |v: usize| ((v.min(maxval) * 255) / maxval) as u8;
+    let n = width * height;
+
+    let pixels: Vec<u8> = match magic.as_str() {
+        "P1" => (0..n)
+            .map(|_| {
+                // Bitmap digits may be packed without separators
+                loop {
+                    while pos < data.len() && data[pos].is_ascii_whitespace() {
+                        pos += 1;
+                    }
+                    match data.get(pos) {
+                        Some(b'0') => {
+                            pos += 1;
+                            return Ok(255);
+                        }
+                        Some(b'1') => {
+                            pos += 1;
+                            return Ok(0);
+                        }
+                        Some(b'#') => {
+                            while pos < data.len() && data[pos] != b'\n' {
+                                pos += 1;
+                            }
+                        }
+                        _ => return Err("PNM: truncated bitmap".to_string()),
+                    }
+                }
+            })
+            .collect::<Result<_, _>>()?,
+        "P2" => (0..n)
+            .map(|_| next_token().and_then(number).map(scale))
+            .collect::<Result<_, _>>()?,
+        "P3" => (0..n)
+            .map(|_| {
+                let r = scale(number(next_token()?)?);
+                let g = scale(number(next_token()?)?);
+                let b = scale(number(next_token()?)?);
+                Ok(luminance(r, g, b))
+            })
+            .collect::<Result<_, String>>()?,
+        _ => {
+            // Binary formats: exactly one whitespace byte after the header
+            let body = data.get(pos + 1..).unwrap_or(&[]);
+            let wide = maxval > 255;
+            let sample = |i: usize| -> usize {
+                if wide {
+                    (body[2 * i] as usize) << 8 | body[2 * i + 1] as usize
+                } else {
+                    body[i] as usize
+                }
+            };
+            let bytes_per_sample = if wide { 2 } else { 1 };
+            match magic.as_str() {
+                "P4" => {
+                    let row_bytes = width.div_ceil(8);
+                    if body.len() < row_bytes * height {
+                        return Err("PNM: truncated data".to_string());
+                    }
+                    (0..n)
+                        .map(|i| {
+                            let (x, y) = (i % width, i / width);
+                            let byte = body[y * row_bytes + x / 8];
+                            if byte & (0x80 >> (x % 8)) != 0 {
+                                0
+                            } else {
+                                255
+                            }
+                        })
+                        .collect()
+                }
+                "P5" => {
+                    if body.len() < n * bytes_per_sample {
+                        return Err("PNM: truncated data".to_string());
+                    }
+                    (0..n).map(|i| scale(sample(i))).collect()
+                }
+                _ => {
+                    if body.len() < 3 * n * bytes_per_sample {
+                        return Err("PNM: truncated data".to_string());
+                    }
+                    (0..n)
+                        .map(|i| {
+                            luminance(
+                                scale(sample(3 * i)),
+                                scale(sample(3 * i + 1)),
+                                scale(sample(3 * i + 2)),
+                            )
+                        })
+                        .collect()
+                }
+            }
+        }
+    };
+    Ok(GrayImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+pub fn threshold(image: &GrayImage, level: u8, invert: bool) -> Mask {
+    Mask {
+        width: image.width,
+        height: image.height,
+        bits: image
+            .pixels
+            .iter()
+            .map(|&v| (v < level) != invert)
+            .collect(),
+    }
+}
+
+/// Trace the boundaries between foreground and background with marching
+/// squares. Each boundary is returned as a closed contour (first point
+/// repeated at the end) in pixel coordinates, where pixel `(x, y)` is
+/// centered at `(x + 0.5, y + 0.5)`. Pixels outside the image count as
+/// background, so every boundary closes.
+pub fn marching_squares(mask: &Mask) -> Vec<Contour> {
+    // Edge midpoints in doubled integer coordinates: the cell whose top-left
+    // pixel is (i, j) has corners at (2i, 2j) .. (2i + 2, 2j + 2).
+    type Key = (i64, i64);
+    let mut adjacency: HashMap<Key, Vec<Key>> = HashMap::new();
+    let mut link = |a: Key, b: Key| {
+        adjacency.entry(a).or_default().push(b);
+        adjacency.entry(b).or_default().push(a);
+    };
+
+    for j in -1..mask.height as i64 {
+        for i in -1..mask.width as i64 {
+            let tl = mask.get_padded(i, j) as u8;
+            let tr = mask.get_padded(i + 1, j) as u8;
+            let br = mask.get_padded(i + 1, j + 1) as u8;
+            let bl = mask.get_padded(i, j + 1) as u8;
+            let case = tl << 3 | tr << 2 | br << 1 | bl;
+            let top = (2 * i + 1, 2 * j);
+            let right = (2 * i + 2, 2 * j + 1);
+            let bottom = (2 * i + 1, 2 * j + 2);
+            let left = (2 * i, 2 * j + 1);
+            match case {
+                1 | 14 => link(left, bottom),
+                2 | 13 => link(bottom, right),
+                3 | 12 => link(left, right),
+                4 | 11 => link(top, right),
+                6 | 9 => link(top, bottom),
+                7 | 8 => link(left, top),
+                // Saddles: keep diagonal foreground pixels apart
+                5 => {
+                    link(top, right);
+                    link(left, bottom);
+                }
+                10 => {
+                    link(left, top);
+                    link(bottom, right);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut starts: Vec<Key> = adjacency.keys().copied().collect();
+    starts.sort();
+    let mut visited: HashMap<Key, bool> = HashMap::new();
+    let mut contours = Vec::new();
+    for start in starts {
+        if visited.contains_key(&start) {
+            continue;
+        }
+        let mut loop_keys = vec![start];
+        visited.insert(start, true);
+        let mut prev = start;
+        let mut current = adjacency[&start][0];
+        while current != start {
+            visited.insert(current, true);
+            loop_keys.push(current);
+            let next = adjacency[&current]
+                .iter()
+                .copied()
+                .find(|&k| k != prev)
+                .unwrap_or(prev);
+            prev = current;
+            current = next;
+        }
+        let mut points: Vec<(f64, f64)> = loop_keys
+            .iter()
+            .map(|&(x, y)| (x as f64 / 2.0 + 0.5, y as f64 / 2.0 + 0.5))
+            .collect();
+        points.push(points[0]);
+        contours.push(Contour { points });
+    }
+    contours
+}
+
+/// Drop vertices lying on a straight line between their neighbours.
+/// Marching squares emits one vertex per pixel edge, so straight runs shrink
+/// to their two end points. The closing point of a closed contour is kept.
+pub fn remove_collinear(contour: &Contour) -> Contour {
+    let pts = &contour.points;
+    if pts.len() < 3 {
+        return Contour {
+            points: pts.clone(),
+        };
+    }
+    let mut out = vec![pts[0]];
+    for i in 1..pts.len() - 1 {
+        let (x0, y0) = *out.last().unwrap();
+        let (x1, y1) = pts[i];
+        let (x2, y2) = pts[i + 1];
+        let cross = (x1 - x0) * (y2 - y1) - (y1 - y0) * (x2 - x1);
+        if cross.abs() > 1e-9 {
+            out.push(pts[i]);
+        }
+    }
+    out.push(pts[pts.len() - 1]);
+    Contour { points: out }
+}
+
+/// Threshold `image`, trace its boundaries and simplify them. Returns the
+/// largest contour by enclosed area (or all of them with `opts.all`, largest
+/// first), skipping those smaller than `opts.min_area`.
+pub fn trace_image(image: &GrayImage, opts: &TraceOptions) -> Vec<Contour> {
+    let mask = threshold(image, opts.threshold, opts.invert);
+    let mut contours: Vec<(f64, Contour)> = marching_squares(&mask)
+        .iter()
+        .map(remove_collinear)
+        .map(|c| (polygon_area(&c.points).abs(), c))
+        .filter(|(area, _)| *area >= opts.min_area)
+        .collect();
+    contours.sort_by(|a, b| b.0.total_cmp(&a.0));
+    if !opts.all {
+        contours.truncate(1);
+    }
+    contours.into_iter().map(|(_, c)| c).collect()
+}
+
+/// Thin foreground regions to one-pixel-wide lines (Zhang–Suen).
+pub fn skeletonize(mask: &Mask) -> Mask {
+    let (w, h) = (mask.width as i64, mask.height as i64);
+    let mut bits = mask.bits.clone();
+    let at = |bits: &[bool], x: i64, y: i64| {
+        x >= 0 && y >= 0 && x < w && y < h && bits[(y * w + x) as usize]
+    };
+    loop {
+        let mut changed = false;
+        for pass in 0..2 {
+            let mut remove = Vec::new();
+            for y in 0..h {
+                for x in 0..w {
+                    if !at(&bits, x, y) {
+                        continue;
+                    }
+                    // Neighbours p2..p9, clockwise from north
+                    let p = [
+                        at(&bits, x, y - 1),
+                        at(&bits, x + 1, y - 1),
+                        at(&bits, x + 1, y),
+                        at(&bits, x + 1, y + 1),
+                        at(&bits, x, y + 1),
+                        at(&bits, x - 1, y + 1),
+                        at(&bits, x - 1, y),
+                        at(&bits, x - 1, y - 1),
+                    ];
+                    let count = p.iter().filter(|&&b| b).count();
+                    let transitions = (0..8).filter(|&i| !p[i] && p[(i + 1) % 8]).count();
+                    let (n, e, s, wst) = (p[0], p[2], p[4], p[6]);
+                    let cond = if pass == 0 {
+                        !(e && s && (n || wst))
+                    } else {
+                        !(n && wst && (e || s))
+                    };
+                    if (2..=6).contains(&count) && transitions == 1 && cond {
+                        remove.push((y * w + x) as usize);
+                    }
+                }
+            }
+            changed |= !remove.is_empty();
+            for i in remove {
+                bits[i] = false;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    Mask {
+        width: mask.width,
+        height: mask.height,
+        bits,
+    }
+}
+
+/// Neighbours of a skeleton pixel. Diagonal neighbours already reachable
+/// through a shared orthogonal neighbour are skipped, so staircases do not
+/// show up as junctions.
+fn skeleton_neighbours(mask: &Mask, x: i64, y: i64) -> Vec<(i64, i64)> {
+    let mut out = Vec::new();
+    for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+        if mask.get_padded(x + dx, y + dy) {
+            out.push((x + dx, y + dy));
+        }
+    }
+    for (dx, dy) in [(1, -1), (1, 1), (-1, 1), (-1, -1)] {
+        if mask.get_padded(x + dx, y + dy)
+            && !mask.get_padded(x + dx, y)
+            && !mask.get_padded(x, y + dy)
+        {
+            out.push((x + dx, y + dy));
+        }
+    }
+    out
+}
+
+/// Walk a one-pixel-wide skeleton into ordered paths in pixel-center
+/// coordinates. Paths run between end points and junctions; closed loops
+/// without any junction are returned with their first point repeated.
+pub fn skeleton_paths(skeleton: &Mask) -> Vec<Contour> {
+    type Key = (i64, i64);
+    let mut adjacency: HashMap<Key, Vec<Key>> = HashMap::new();
+    for y in 0..skeleton.height as i64 {
+        for x in 0..skeleton.width as i64 {
+            if skeleton.get(x as usize, y as usize) {
+                adjacency.insert((x, y), skeleton_neighbours(skeleton, x, y));
+            }
+        }
+    }
+    let mut pixels: Vec<Key> = adjacency.keys().copied().collect();
+    pixels.sort_by_key(|&(x, y)| (y, x));
+
+    let edge = |a: Key, b: Key| if a < b { (a, b) } else { (b, a) };
+    let mut visited = HashSet::new();
+    let mut paths = Vec::new();
+    let walk = |start: Key, first: Key, visited: &mut HashSet<_>| {
+        let mut path = vec![start];
+        let (mut prev, mut current) = (start, first);
+        visited.insert(edge(prev, current));
+        loop {
+            path.push(current);
+            let next = adjacency[&current]
+                .iter()
+                .copied()
+                .find(|&n| n != prev && !visited.contains(&edge(current, n)));
+            match next {
+                Some(n) if adjacency[&current].len() == 2 => {
+                    visited.insert(edge(current, n));
+                    prev = current;
+                    current = n;
+                }
+                _ => break,
+            }
+        }
+        Contour {
+            points: path
+                .iter()
+                .map(|&(x, y)| (x as f64 + 0.5, y as f64 + 0.5))
+                .collect(),
+        }
+    };
+
+    // Open paths start from end points and junctions
+    for &p in &pixels {
+        if adjacency[&p].len() == 2 {
+            continue;
+        }
+        for n in adjacency[&p].clone() {
+            if !visited.contains(&edge(p, n)) {
+                paths.push(walk(p, n, &mut visited));
+            }
+        }
+    }
+    // Whatever is left are loops made only of degree-2 pixels
+    for &p in &pixels {
+        if adjacency[&p].len() == 2 && !visited.contains(&edge(p, adjacency[&p][0])) {
+            let first = adjacency[&p][0];
+            paths.push(walk(p, first, &mut visited));
+        }
+    }
+    paths
+}
+
+fn path_length(points: &[(f64, f64)]) -> f64 {
+    points
+        .windows(2)
+        .map(|w| ((w[1].0 - w[0].0).powi(2) + (w[1].1 - w[0].1).powi(2)).sqrt())
+        .sum()
+}
+
+/// Order paths so each one starts near where the previous one ended,
+/// reversing paths when their far end is closer.
+fn chain_paths(mut paths: Vec<Contour>) -> Vec<Contour> {
+    let mut out: Vec<Contour> = Vec::with_capacity(paths.len());
+    while !paths.is_empty() {
+        let (px, py) = out
+            .last()
+            .and_then(|c| c.points.last().copied())
+            .unwrap_or((f64::MIN, f64::MIN));
+        let dist = |p: &(f64, f64)| (p.0 - px).powi(2) + (p.1 - py).powi(2);
+        let (best, reverse) = if out.is_empty() {
+            (0, false)
+        } else {
+            paths
+                .iter()
+                .enumerate()
+                .flat_map(|(i, c)| {
+                    [
+                        (i, false, dist(&c.points[0])),
+                        (i, true, dist(c.points.last().unwrap())),
+                    ]
+                })
+                .min_by(|a, b| a.2.total_cmp(&b.2))
+                .map(|(i, r, _)| (i, r))
+                .unwrap()
+        };
+        let mut c = paths.remove(best);
+        if reverse {
+            c.points.reverse();
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Threshold `image`, thin it to its skeleton and walk the skeleton into
+/// open strokes. Returns the longest stroke (or all of them with
+/// `opts.all`, chained so each starts near the previous end), skipping those
+/// shorter than `opts.min_length`.
+pub fn trace_centerlines(image: &GrayImage, opts: &TraceOptions) -> Vec<Contour> {
+    let mask = threshold(image, opts.threshold, opts.invert);
+    let mut strokes: Vec<(f64, Contour)> = skeleton_paths(&skeletonize(&mask))
+        .iter()
+        .map(remove_collinear)
+        .map(|c| (path_length(&c.points), c))
+        .filter(|(len, _)| *len >= opts.min_length)
+        .collect();
+    strokes.sort_by(|a, b| b.0.total_cmp(&a.0));
+    if !opts.all {
+        strokes.truncate(1);
+    }
+    chain_paths(strokes.into_iter().map(|(_, c)| c).collect())
+}
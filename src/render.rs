@@ -0,0 +1,1171 @@
+use std::collections::HashMap;
+
+use color_quant::NeuQuant;
+use tiny_skia::{
+    Color, FillRule, GradientStop, LineCap, LineJoin, Paint, Path, PathBuilder, Pixmap, Point,
+    RadialGradient, Rect, SpreadMode, Stroke, StrokeDash, Transform,
+};
+
+use crate::canvas::{
+    CIRCLE_COLORS, Chain, ChainJs, ChainTiming, Layout, chain_params, harmonics_schedule, layout,
+    loop_schedule, subpaths_of_svg_path,
+};
+use crate::model::{EmbedOptions, HarmonicSteps, WhenToShow};
+
+/// The animation of the pages stepped frame by frame and drawn without a
+/// browser: contour, epicycles (or the configured visualization), traces
+/// and sparks, without the harmonics label.
+pub struct Animation<'a> {
+    opts: &'a EmbedOptions,
+    chains: Vec<ChainJs<'a>>,
+    layout: Layout,
+    contour: Option<Path>,
+    /// Harmonics and speed of each loop
+    schedule: Vec<(usize, f64)>,
+    /// Harmonics of each loop for chains with their own steps
+    chain_schedules: Vec<Option<Vec<usize>>>,
+    trace_colors: Vec<Color>,
+    chain_colors: Vec<(Option<Vec<Color>>, Vec<Color>)>,
+    t: f64,
+    loops: usize,
+    histories: Vec<Vec<(f64, f64)>>,
+    seed: u64,
+}
+
+/// Initial state of the sparks' random number generator.
+const SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+impl<'a> Animation<'a> {
+    /// Animation of `chains` with the page options `opts`, following
+    /// `steps` instead of `opts.steps` when given.
+    pub fn new(
+        chains: &'a [Chain],
+        timing: ChainTiming,
+        opts: &'a EmbedOptions,
+        steps: Option<&HarmonicSteps>,
+    ) -> Result<Self, String> {
+        let (svg_path, points, chains) = chain_params(chains, timing);
+        let layout = layout(&points, &chains, opts);
+        let max_nh = chains
+            .iter()
+            .map(|c| c.fourier.coeffs.len())
+            .max()
+            .map_or(1, |n| n.min(opts.max_harmonics));
+        let mut schedule = loop_schedule(steps.unwrap_or(&opts.steps), max_nh);
+        for (_, speed) in &mut schedule {
+            if !(*speed >= 0.0 && speed.is_finite()) {
+                return Err("steps: speed must be >= 0".to_string());
+            }
+            // As the pages, which run a loop of speed 0 at speed 1
+            if *speed == 0.0 {
+                *speed = 1.0;
+            }
+        }
+        let chain_schedules = chains
+            .iter()
+            .map(|c| {
+                let steps = c.style.and_then(|s| s.steps.as_ref())?;
+                let max_nh = opts.max_harmonics.min(c.fourier.coeffs.len());
+                Some(harmonics_schedule(steps, max_nh))
+            })
+            .collect();
+        let colors = |names: &[String]| -> Result<Vec<Color>, String> {
+            names
+                .iter()
+                .map(|n| parse_color(n).ok_or_else(|| format!("unknown color: {n}")))
+                .collect()
+        };
+        let trace_colors = colors(&opts.trace_colors)?;
+        let circle_colors: Vec<String> = CIRCLE_COLORS.iter().map(|c| c.to_string()).collect();
+        let chain_colors = chains
+            .iter()
+            .map(|c| {
+                let style = c.style;
+                let trace = match style.and_then(|s| s.trace_colors.as_ref()) {
+                    Some(names) => Some(colors(names)?),
+                    None => None,
+                };
+                let circles = match style.and_then(|s| s.circle_colors.as_ref()) {
+                    Some(names) if !names.is_empty() => colors(names)?,
+                    _ => colors(&circle_colors)?,
+                };
+                Ok((trace, circles))
+            })
+            .collect::<Result<_, String>>()?;
+        let contour = path_of_polylines(
+            subpaths_of_svg_path(&svg_path)
+                .iter()
+                .map(|c| c.points.as_slice()),
+            false,
+        );
+        let histories = vec![vec![]; chains.len()];
+        Ok(Animation {
+            opts,
+            chains,
+            layout,
+            contour,
+            schedule,
+            chain_schedules,
+            trace_colors,
+            chain_colors,
+            t: 0.0,
+            loops: 0,
+            histories,
+            seed: SEED,
+        })
+    }
+
+    /// Go back to the first frame, with empty traces and the sparks'
+    /// random sequence restarted, as a new `Animation` would.
+    pub fn rewind(&mut self) {
+        self.t = 0.0;
+        self.loops = 0;
+        for history in &mut self.histories {
+            history.clear();
+        }
+        self.seed = SEED;
+    }
+
+    /// Number of loops in the harmonics schedule, after which it repeats.
+    pub fn schedule_len(&self) -> usize {
+        self.schedule.len()
+    }
+
+    /// Loops completed since the start.
+    pub fn completed_loops(&self) -> usize {
+        self.loops
+    }
+
+    fn loop_index(&self) -> usize {
+        self.loops % self.schedule.len()
+    }
+
+    /// Advance by `dt` seconds, as the page does between two frames.
+    pub fn advance(&mut self, dt: f64) {
+        let (_, speed) = self.schedule[self.loop_index()];
+        self.t += dt * speed * 0.1;
+        if self.t > 1.0 {
+            self.t -= 1.0;
+            self.loops += 1;
+            for history in &mut self.histories {
+                history.clear();
+            }
+        }
+    }
+
+    /// Number of frames `frames` yields from the current state.
+    pub fn frame_count(&self, loops: usize, fps: f64) -> usize {
+        let (mut t, mut done) = (self.t, self.loops);
+        let mut count = 0;
+        while done < loops {
+            t += self.schedule[done % self.schedule.len()].1 * 0.1 / fps;
+            if t > 1.0 {
+                t -= 1.0;
+                done += 1;
+            }
+            count += 1;
+        }
+        count
+    }
+
+    /// Frames at `fps` until `loops` loops are completed, or an error if
+    /// `size` is not a valid frame size.
+    pub fn frames(
+        &mut self,
+        loops: usize,
+        size: u32,
+        fps: f64,
+        background: Color,
+    ) -> Result<impl Iterator<Item = Pixmap> + '_, String> {
+        let blank = blank_frame(size, background)?;
+        Ok(std::iter::from_fn(move || {
+            if self.loops >= loops {
+                return None;
+            }
+            let mut pixmap = blank.clone();
+            self.draw(&mut pixmap);
+            self.advance(1.0 / fps);
+            Some(pixmap)
+        }))
+    }
+
+    /// Draw the current frame on a `size`×`size` pixmap filled with
+    /// `background`, and record the trace point of this frame.
+    pub fn frame(&mut self, size: u32, background: Color) -> Result<Pixmap, String> {
+        let mut pixmap = blank_frame(size, background)?;
+        self.draw(&mut pixmap);
+        Ok(pixmap)
+    }
+
+    /// Draw the current frame over `pixmap`, as `frame` does.
+    fn draw(&mut self, pixmap: &mut Pixmap) {
+        let size = pixmap.width();
+        let (vb_x, vb_y, vb_size) = self.layout.view;
+        let k = (size as f64 / vb_size) as f32;
+        let view = Transform::from_row(k, 0.0, 0.0, k, -vb_x as f32 * k, -vb_y as f32 * k);
+        let scale = (vb_size / 100.0) as f32;
+        let loop_index = self.loop_index();
+
+        if self.shown(&self.opts.show_contour)
+            && let Some(path) = &self.contour
+        {
+            let stroke = Stroke {
+                width: self.opts.contour_width as f32 * scale,
+                ..Stroke::default()
+            };
+            pixmap.stroke_path(path, &paint(Color::WHITE), &stroke, view, None);
+        }
+
+        let times: Vec<Option<f64>> = self.chains.iter().map(|c| chain_time(c, self.t)).collect();
+        if self.shown(&self.opts.show_fourier_circles) {
+            for (i, t) in times.iter().enumerate() {
+                if let Some(t) = t {
+                    self.draw_chain(pixmap, i, *t, view, scale);
+                }
+            }
+        }
+
+        self.record_traces();
+        if self.shown(&self.opts.show_trace) {
+            let stroke = Stroke {
+                width: self.opts.trace_width as f32 * scale,
+                line_join: LineJoin::Round,
+                ..Stroke::default()
+            };
+            for (i, history) in self.histories.iter().enumerate() {
+                let color = match &self.chain_colors[i].0 {
+                    Some(colors) if !colors.is_empty() => colors[loop_index % colors.len()],
+                    _ if self.trace_colors.is_empty() => Color::WHITE,
+                    _ => self.trace_colors[loop_index % self.trace_colors.len()],
+                };
+                let mut color = color;
+                color.apply_opacity(self.opts.opacity as f32);
+                if let Some(path) = path_of_polylines([history.as_slice()], false) {
+                    pixmap.stroke_path(&path, &paint(color), &stroke, view, None);
+                }
+            }
+        }
+
+        if self.opts.show_point {
+            for (i, t) in times.iter().enumerate() {
+                if let Some(t) = t {
+                    let (x, y) = self.eval(i, *t);
+                    self.draw_spark(pixmap, x, y, view, scale);
+                }
+            }
+        }
+    }
+
+    /// Update the traces as `frame` does, without drawing, for frames that
+    /// are not needed but whose trace points are.
+    pub fn skip_frame(&mut self) {
+        self.record_traces();
+    }
+
+    /// Add the pen position of the current frame to each drawing chain's
+    /// trace, or clear the traces in loops that hide them.
+    fn record_traces(&mut self) {
+        if !self.shown(&self.opts.show_trace) {
+            for history in &mut self.histories {
+                history.clear();
+            }
+            return;
+        }
+        for i in 0..self.chains.len() {
+            if let Some(t) = chain_time(&self.chains[i], self.t) {
+                let point = self.eval(i, t);
+                let history = &mut self.histories[i];
+                history.push(point);
+                let max_len = (self.opts.trace_length * self.chains[i].n as f64).round();
+                let max_len = max_len as usize;
+                if history.len() > max_len {
+                    history.drain(..history.len() - max_len);
+                }
+            }
+        }
+    }
+
+    /// Whether a part is shown in the current loop.
+    fn shown(&self, when: &WhenToShow) -> bool {
+        match when {
+            WhenToShow::Always => true,
+            WhenToShow::Never => false,
+            WhenToShow::Congruence(c) => c
+                .congruents
+                .contains(&(self.loop_index() % c.modulo.max(1))),
+        }
+    }
+
+    fn num_harmonics(&self, i: usize) -> usize {
+        let loop_index = self.loop_index();
+        let h = match &self.chain_schedules[i] {
+            Some(nh) => nh[loop_index % nh.len()],
+            None => self.schedule[loop_index].0,
+        };
+        h.clamp(1, self.chains[i].fourier.coeffs.len().max(1))
+    }
+
+    /// Pen position of chain `i` at its time `t`.
+    fn eval(&self, i: usize, t: f64) -> (f64, f64) {
+        let c = &self.chains[i];
+        let tt = t + c.style.map_or(0.0, |s| s.phase);
+        let n = self.num_harmonics(i);
+        let (dx, dy) = c.offset;
+        if let Some(axis) = self.layout.axes.get(i) {
+            let sum = |terms: &[crate::contour::RealCoeff], v: f64| {
+                terms.iter().take(n).fold(v, |v, c| {
+                    v + c.amp * (std::f64::consts::TAU * c.freq as f64 * tt + c.phase).cos()
+                })
+            };
+            return (dx + sum(&axis.x, axis.x0), dy + sum(&axis.y, axis.y0));
+        }
+        if let Some(series) = self.layout.ellipses.get(i) {
+            return series.ellipses.iter().take(n).fold(
+                (dx + series.center.0, dy + series.center.1),
+                |(x, y), e| {
+                    let (ex, ey) = e.eval(tt);
+                    (x + ex, y + ey)
+                },
+            );
+        }
+        let (x, y) = c.fourier.truncated(n).eval(tt);
+        (dx + x, dy + y)
+    }
+
+    /// Circles (or ellipses, or both axis chains) of chain `i` at its time
+    /// `t`, as `drawChain` in the pages.
+    fn draw_chain(&self, pixmap: &mut Pixmap, i: usize, t: f64, view: Transform, scale: f32) {
+        let c = &self.chains[i];
+        let colors = &self.chain_colors[i].1;
+        let tt = t + c.style.map_or(0.0, |s| s.phase);
+        let n = self.num_harmonics(i);
+        let tau = std::f64::consts::TAU;
+        let (mut cx, mut cy) = c.offset;
+        if let (Some(axis), Some((top, left))) = (self.layout.axes.get(i), self.layout.axis_lines) {
+            let mut tips = vec![];
+            for (terms, vertical) in [(&axis.x, false), (&axis.y, true)] {
+                let (mut x, mut y) = if vertical {
+                    (left, c.offset.1 + axis.y0)
+                } else {
+                    (c.offset.0 + axis.x0, top)
+                };
+                for (k, term) in terms.iter().take(n).enumerate() {
+                    let theta = tau * term.freq as f64 * tt + term.phase;
+                    let (along, across) = (term.amp * theta.cos(), term.amp * theta.sin());
+                    let (nx, ny) = if vertical {
+                        (x + across, y + along)
+                    } else {
+                        (x + along, y + across)
+                    };
+                    let color = colors[k % colors.len()];
+                    draw_term(pixmap, (x, y), term.amp, None, (nx, ny), color, view, scale);
+                    (x, y) = (nx, ny);
+                }
+                tips.push((x, y));
+            }
+            let pen = self.eval(i, t);
+            let stroke = Stroke {
+                width: 0.3 * scale,
+                ..Stroke::default()
+            };
+            let gray = Color::from_rgba8(128, 128, 128, 255);
+            for tip in tips {
+                if let Some(path) = path_of_polylines([[tip, pen].as_slice()], false) {
+                    pixmap.stroke_path(&path, &paint(gray), &stroke, view, None);
+                }
+            }
+            return;
+        }
+        if let Some(series) = self.layout.ellipses.get(i) {
+            (cx, cy) = (cx + series.center.0, cy + series.center.1);
+            for (k, e) in series.ellipses.iter().take(n).enumerate() {
+                let (dx, dy) = e.eval(tt);
+                let color = colors[k % colors.len()];
+                let shape = Some((e.semi_minor.abs(), e.tilt));
+                let next = (cx + dx, cy + dy);
+                draw_term(
+                    pixmap,
+                    (cx, cy),
+                    e.semi_major,
+                    shape,
+                    next,
+                    color,
+                    view,
+                    scale,
+                );
+                (cx, cy) = next;
+            }
+            return;
+        }
+        for (k, term) in c.fourier.coeffs.iter().take(n).enumerate() {
+            let theta = tau * term.freq as f64 * tt;
+            let nx = cx + term.re * theta.cos() - term.im * theta.sin();
+            let ny = cy + term.im * theta.cos() + term.re * theta.sin();
+            let color = colors[k % colors.len()];
+            draw_term(
+                pixmap,
+                (cx, cy),
+                term.radius(),
+                None,
+                (nx, ny),
+                color,
+                view,
+                scale,
+            );
+            (cx, cy) = (nx, ny);
+        }
+    }
+
+    /// Glowing dot with random rays and particles, as `drawSpark`.
+    fn draw_spark(&mut self, pixmap: &mut Pixmap, x: f64, y: f64, view: Transform, scale: f32) {
+        let view = view.pre_translate(x as f32, y as f32);
+        let dot_r = 0.7 * scale;
+        let glow_r = dot_r * 3.0;
+        let stops = [
+            (0.0, Color::WHITE),
+            (0.2, Color::from_rgba8(255, 255, 224, 255)),
+            (0.5, Color::from_rgba8(255, 165, 0, 255)),
+            (0.8, Color::from_rgba8(255, 69, 0, 255)),
+            (1.0, Color::TRANSPARENT),
+        ]
+        .into_iter()
+        .map(|(pos, mut color)| {
+            color.apply_opacity(0.9);
+            GradientStop::new(pos, color)
+        })
+        .collect();
+        let shader = RadialGradient::new(
+            Point::zero(),
+            Point::zero(),
+            glow_r,
+            stops,
+            SpreadMode::Pad,
+            Transform::identity(),
+        );
+        if let (Some(shader), Some(glow)) = (shader, PathBuilder::from_circle(0.0, 0.0, glow_r)) {
+            let glow_paint = Paint {
+                shader,
+                anti_alias: true,
+                ..Paint::default()
+            };
+            pixmap.fill_path(&glow, &glow_paint, FillRule::Winding, view, None);
+        }
+        if let Some(core) = PathBuilder::from_circle(0.0, 0.0, dot_r * 0.5) {
+            pixmap.fill_path(&core, &paint(Color::WHITE), FillRule::Winding, view, None);
+        }
+        for _ in 0..14 {
+            let angle = self.random() * std::f64::consts::TAU;
+            let len = (2.0 + self.random() * 6.0) as f32 * scale;
+            let inner = (0.2 + self.random() * 0.5) as f32 * scale;
+            let (sin, cos) = (angle.sin() as f32, angle.cos() as f32);
+            let color = if self.random() > 0.4 {
+                Color::from_rgba8(255, 215, 0, 255)
+            } else {
+                Color::from_rgba8(255, 140, 0, 255)
+            };
+            let stroke = Stroke {
+                width: (0.3 + self.random() * 0.5) as f32 * scale,
+                line_cap: LineCap::Round,
+                ..Stroke::default()
+            };
+            let mut color = color;
+            color.apply_opacity((0.3 + self.random() * 0.6) as f32);
+            let mut pb = PathBuilder::new();
+            pb.move_to(cos * inner, sin * inner);
+            pb.line_to(cos * len, sin * len);
+            if let Some(ray) = pb.finish() {
+                pixmap.stroke_path(&ray, &paint(color), &stroke, view, None);
+            }
+        }
+        for _ in 0..8 {
+            let angle = self.random() * std::f64::consts::TAU;
+            let dist = (2.0 + self.random() * 5.0) as f32 * scale;
+            let r = (0.1 + self.random() * 0.3) as f32 * scale;
+            let mut color = Color::from_rgba8(255, 215, 0, 255);
+            color.apply_opacity((0.3 + self.random() * 0.5) as f32);
+            let (x, y) = (angle.cos() as f32 * dist, angle.sin() as f32 * dist);
+            if let Some(particle) = PathBuilder::from_circle(x, y, r) {
+                pixmap.fill_path(&particle, &paint(color), FillRule::Winding, view, None);
+            }
+        }
+    }
+
+    /// Uniform in 0..1, from a fixed seed so that frames are reproducible.
+    fn random(&mut self) -> f64 {
+        // xorshift64*
+        self.seed ^= self.seed >> 12;
+        self.seed ^= self.seed << 25;
+        self.seed ^= self.seed >> 27;
+        (self.seed.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Local time of a chain, or `None` when it is not drawing at loop time
+/// `t`, as `chainTime` in the pages.
+/// A `size`×`size` pixmap filled with `background`.
+fn blank_frame(size: u32, background: Color) -> Result<Pixmap, String> {
+    let mut pixmap = Pixmap::new(size, size)
+        .ok_or_else(|| format!("frames must be between 1 and {} px wide", i32::MAX / 4))?;
+    pixmap.fill(background);
+    Ok(pixmap)
+}
+
+fn chain_time(c: &ChainJs, t: f64) -> Option<f64> {
+    if t < c.start || (t >= c.end && c.end < 1.0) {
+        return None;
+    }
+    Some(((t - c.start) / (c.end - c.start)).min(1.0))
+}
+
+pub(crate) fn paint(color: Color) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color(color);
+    paint.anti_alias = true;
+    paint
+}
+
+/// Path through each polyline of page coordinates, closed if `close`.
+pub(crate) fn path_of_polylines<'p>(
+    polylines: impl IntoIterator<Item = &'p [(f64, f64)]>,
+    close: bool,
+) -> Option<Path> {
+    let mut pb = PathBuilder::new();
+    for points in polylines {
+        let Some(&(x, y)) = points.first() else {
+            continue;
+        };
+        pb.move_to(x as f32, y as f32);
+        for &(x, y) in &points[1..] {
+            pb.line_to(x as f32, y as f32);
+        }
+        if close {
+            pb.close();
+        }
+    }
+    pb.finish()
+}
+
+/// One dashed circle of radius `r` around `center` (or an ellipse with
+/// semi-axes `r` and `shape.0`, turned by `shape.1`), its radius to `next`
+/// and a dot there, as `drawTerm` in the pages.
+#[allow(clippy::too_many_arguments)]
+fn draw_term(
+    pixmap: &mut Pixmap,
+    (cx, cy): (f64, f64),
+    r: f64,
+    shape: Option<(f64, f64)>,
+    (nx, ny): (f64, f64),
+    color: Color,
+    view: Transform,
+    scale: f32,
+) {
+    let paint = paint(color);
+    let dashed = Stroke {
+        width: 0.3 * scale,
+        dash: StrokeDash::new(vec![scale, scale], 0.0),
+        ..Stroke::default()
+    };
+    let (ry, tilt) = shape.unwrap_or((r, 0.0));
+    let outline = Rect::from_xywh(-r as f32, -ry as f32, 2.0 * r as f32, 2.0 * ry as f32)
+        .and_then(PathBuilder::from_oval);
+    if let Some(outline) = outline {
+        let place = view
+            .pre_translate(cx as f32, cy as f32)
+            .pre_rotate(tilt.to_degrees() as f32);
+        pixmap.stroke_path(&outline, &paint, &dashed, place, None);
+    }
+    let solid = Stroke {
+        width: 0.3 * scale,
+        ..Stroke::default()
+    };
+    if let Some(radius) = path_of_polylines([[(cx, cy), (nx, ny)].as_slice()], false) {
+        pixmap.stroke_path(&radius, &paint, &solid, view, None);
+    }
+    if let Some(dot) = PathBuilder::from_circle(nx as f32, ny as f32, 0.8 * scale) {
+        pixmap.fill_path(&dot, &paint, FillRule::Winding, view, None);
+    }
+}
+
+/// File format of `encode_animation`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum AnimationFormat {
+    Gif,
+    Apng,
+}
+
+impl AnimationFormat {
+    /// Extension of the written file: APNGs are `.png` files.
+    pub fn extension(self) -> &'static str {
+        match self {
+            AnimationFormat::Gif => "gif",
+            AnimationFormat::Apng => "png",
+        }
+    }
+}
+
+/// Where the colors of a palette-based animation come from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum PaletteMode {
+    /// One palette learned from frames across the whole animation
+    Global,
+    /// One palette per frame (GIF only)
+    Local,
+}
+
+/// Options of `encode_animation`.
+pub struct AnimationOptions {
+    pub format: AnimationFormat,
+    /// Width and height of the frames in px
+    pub size: u32,
+    pub fps: f64,
+    /// Loops to encode, the whole harmonics schedule when `None`
+    pub loops: Option<usize>,
+    pub background: Color,
+    /// Palette size. GIFs always use one, of 256 colors by default; APNGs
+    /// are true color unless it is set
+    pub colors: Option<usize>,
+    pub palette: PaletteMode,
+    /// Shrink the frames until the file fits in this many bytes
+    pub max_bytes: Option<usize>,
+}
+
+/// Smallest frame size tried to fit in `max_bytes`.
+const MIN_SIZE: u32 = 16;
+
+/// Looping GIF or APNG of the animation of `chains`, and the frame size
+/// used, which is smaller than `anim.size` when the file had to shrink to
+/// fit in `anim.max_bytes`.
+pub fn encode_animation(
+    chains: &[Chain],
+    timing: ChainTiming,
+    opts: &EmbedOptions,
+    steps: Option<&HarmonicSteps>,
+    anim: &AnimationOptions,
+) -> Result<(Vec<u8>, u32), String> {
+    let colors = match (anim.format, anim.colors) {
+        (AnimationFormat::Gif, None) => Some(256),
+        (_, colors) => colors,
+    };
+    if colors.is_some_and(|c| !(2..=256).contains(&c)) {
+        return Err("colors must be between 2 and 256".to_string());
+    }
+    if anim.size == 0 {
+        return Err("size must be > 0".to_string());
+    }
+    if !(anim.fps > 0.0 && anim.fps.is_finite()) {
+        return Err("fps must be a finite number > 0".to_string());
+    }
+    let mut animation = Animation::new(chains, timing, opts, steps)?;
+    let loops = anim.loops.unwrap_or(animation.schedule_len());
+    if animation.frame_count(loops, anim.fps) == 0 {
+        return Err("no frames to encode".to_string());
+    }
+    let mut size = anim.size;
+    loop {
+        let global = match (colors, anim.palette, anim.format) {
+            (Some(colors), PaletteMode::Global, _) | (Some(colors), _, AnimationFormat::Apng) => {
+                animation.rewind();
+                Some(global_palette(&mut animation, loops, size, anim, colors)?)
+            }
+            _ => None,
+        };
+        animation.rewind();
+        let bytes = match anim.format {
+            AnimationFormat::Gif => {
+                let colors = colors.expect("GIFs have a palette");
+                encode_gif(&mut animation, loops, size, anim, colors, global)?
+            }
+            AnimationFormat::Apng => encode_apng(&mut animation, loops, size, anim, global)?,
+        };
+        let Some(max) = anim.max_bytes.filter(|&max| bytes.len() > max) else {
+            return Ok((bytes, size));
+        };
+        // File sizes grow about with the frame area
+        let ratio = (max as f64 / bytes.len() as f64).sqrt() * 0.95;
+        let smaller = ((size as f64 * ratio) as u32).min(size - 1);
+        if smaller < MIN_SIZE {
+            return Err(format!(
+                "{} bytes at {size} px do not fit in {max} bytes",
+                bytes.len()
+            ));
+        }
+        size = smaller;
+    }
+}
+
+fn encode_gif(
+    animation: &mut Animation,
+    loops: usize,
+    size: u32,
+    anim: &AnimationOptions,
+    colors: usize,
+    mut global: Option<Palette>,
+) -> Result<Vec<u8>, String> {
+    let gif_error = |e: gif::EncodingError| format!("GIF encoding: {e}");
+    let side = u16::try_from(size).map_err(|_| "GIF frames must be at most 65535 px")?;
+    let transparent = anim.background.alpha() < 1.0;
+    let global_rgb = global.as_ref().map_or(vec![], Palette::rgb);
+    let mut encoder = gif::Encoder::new(vec![], side, side, &global_rgb).map_err(gif_error)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(gif_error)?;
+    // Delays are in hundredths of a second: round the frame times, not the
+    // delays, so that the error does not add up
+    let centiseconds = |i: usize| (i as f64 * 100.0 / anim.fps).round() as u64;
+    let mut previous: Option<Vec<u8>> = None;
+    let frames = animation.frames(loops, size, anim.fps, anim.background)?;
+    for (i, rgba) in frames.map(|p| straight_rgba(&p)).enumerate() {
+        // Over an opaque background, frames only repaint what changed
+        let rect = match &previous {
+            Some(previous) if !transparent => {
+                changed_rect(previous, &rgba, size).unwrap_or((0, 0, 1, 1))
+            }
+            _ => (0, 0, size, size),
+        };
+        let region = crop(&rgba, size, rect);
+        let mut local = None;
+        let palette = match &mut global {
+            Some(palette) => palette,
+            None => local.insert(Palette::new(&region, colors, anim.background)),
+        };
+        let buffer: Vec<u8> = region.chunks_exact(4).map(|p| palette.index(p)).collect();
+        let frame = gif::Frame {
+            delay: (centiseconds(i + 1) - centiseconds(i)).min(u16::MAX as u64) as u16,
+            dispose: if transparent {
+                gif::DisposalMethod::Background
+            } else {
+                gif::DisposalMethod::Keep
+            },
+            transparent: palette.transparent(),
+            left: rect.0 as u16,
+            top: rect.1 as u16,
+            width: rect.2 as u16,
+            height: rect.3 as u16,
+            palette: local.as_ref().map(Palette::rgb),
+            buffer: buffer.into(),
+            ..gif::Frame::default()
+        };
+        encoder.write_frame(&frame).map_err(gif_error)?;
+        previous = Some(rgba);
+    }
+    encoder
+        .into_inner()
+        .map_err(|e| format!("GIF encoding: {e}"))
+}
+
+fn encode_apng(
+    animation: &mut Animation,
+    loops: usize,
+    size: u32,
+    anim: &AnimationOptions,
+    mut palette: Option<Palette>,
+) -> Result<Vec<u8>, String> {
+    let png_error = |e: png::EncodingError| format!("APNG encoding: {e}");
+    let count = animation.frame_count(loops, anim.fps);
+    let mut bytes = vec![];
+    let mut encoder = png::Encoder::new(&mut bytes, size, size);
+    encoder.set_depth(png::BitDepth::Eight);
+    match &palette {
+        Some(palette) => {
+            encoder.set_color(png::ColorType::Indexed);
+            encoder.set_palette(palette.rgb());
+            if let Some(t) = palette.transparent() {
+                let mut alphas = vec![255; t as usize];
+                alphas.push(0);
+                encoder.set_trns(alphas);
+            }
+        }
+        None => encoder.set_color(png::ColorType::Rgba),
+    }
+    encoder.set_animated(count as u32, 0).map_err(png_error)?;
+    let fps_100 = (anim.fps * 100.0).round().clamp(1.0, u16::MAX as f64) as u16;
+    encoder.set_frame_delay(100, fps_100).map_err(png_error)?;
+    let mut writer = encoder.write_header().map_err(png_error)?;
+    let mut previous: Option<Vec<u8>> = None;
+    for pixmap in animation.frames(loops, size, anim.fps, anim.background)? {
+        let rgba = straight_rgba(&pixmap);
+        // Frames after the first replace only what changed
+        let rect = match &previous {
+            Some(previous) => changed_rect(previous, &rgba, size).unwrap_or((0, 0, 1, 1)),
+            None => (0, 0, size, size),
+        };
+        let region = crop(&rgba, size, rect);
+        let data = match &mut palette {
+            Some(palette) => region.chunks_exact(4).map(|p| palette.index(p)).collect(),
+            None => region,
+        };
+        writer.reset_frame_position().map_err(png_error)?;
+        writer
+            .set_frame_dimension(rect.2, rect.3)
+            .map_err(png_error)?;
+        writer
+            .set_frame_position(rect.0, rect.1)
+            .map_err(png_error)?;
+        writer.write_image_data(&data).map_err(png_error)?;
+        previous = Some(rgba);
+    }
+    writer.finish().map_err(png_error)?;
+    Ok(bytes)
+}
+
+/// Non-premultiplied RGBA bytes of `pixmap`.
+fn straight_rgba(pixmap: &Pixmap) -> Vec<u8> {
+    pixmap
+        .pixels()
+        .iter()
+        .flat_map(|p| {
+            let c = p.demultiply();
+            [c.red(), c.green(), c.blue(), c.alpha()]
+        })
+        .collect()
+}
+
+/// Smallest rectangle `(left, top, width, height)` holding the pixels that
+/// differ between two `size`×`size` RGBA frames.
+fn changed_rect(a: &[u8], b: &[u8], size: u32) -> Option<(u32, u32, u32, u32)> {
+    let size = size as usize;
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (size, size, 0, 0);
+    for (i, (p, q)) in a.chunks_exact(4).zip(b.chunks_exact(4)).enumerate() {
+        if p != q {
+            let (x, y) = (i % size, i / size);
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+    }
+    (min_x <= max_x).then(|| {
+        let rect = (min_x, min_y, max_x - min_x + 1, max_y - min_y + 1);
+        (rect.0 as u32, rect.1 as u32, rect.2 as u32, rect.3 as u32)
+    })
+}
+
+/// RGBA bytes of `rect` in a `size`×`size` RGBA frame.
+fn crop(rgba: &[u8], size: u32, (left, top, width, height): (u32, u32, u32, u32)) -> Vec<u8> {
+    let stride = size as usize * 4;
+    (top..top + height)
+        .flat_map(|y| {
+            let start = y as usize * stride + left as usize * 4;
+            &rgba[start..start + width as usize * 4]
+        })
+        .copied()
+        .collect()
+}
+
+/// Colors of a palette-based animation: an exact last entry for the
+/// background (transparent on transparent backgrounds) and a NeuQuant
+/// palette of the other pixels, so that the background does not crowd out
+/// the few colors of thin circles and traces.
+struct Palette {
+    quant: NeuQuant,
+    background: [u8; 3],
+    /// Index of the background entry, also the transparent one when
+    /// `transparent` is set
+    background_index: u8,
+    transparent: bool,
+    cache: HashMap<[u8; 3], u8>,
+}
+
+impl Palette {
+    /// Palette of `colors` entries learned from `pixels` (RGBA) drawn over
+    /// `background`.
+    fn new(pixels: &[u8], colors: usize, background: Color) -> Self {
+        let transparent = background.alpha() < 1.0;
+        let c = background.to_color_u8();
+        let background = [c.red(), c.green(), c.blue()];
+        let mut foreground: Vec<u8> = pixels
+            .chunks_exact(4)
+            .filter(|p| p[3] >= 128 && (transparent || p[..3] != background))
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect();
+        if foreground.is_empty() {
+            foreground.extend([background[0], background[1], background[2], 255]);
+        }
+        let quant_colors = colors - 1;
+        Palette {
+            quant: NeuQuant::new(10, quant_colors, &foreground),
+            background,
+            background_index: quant_colors as u8,
+            transparent,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Index of the transparent entry, if any.
+    fn transparent(&self) -> Option<u8> {
+        self.transparent.then_some(self.background_index)
+    }
+
+    /// RGB bytes of the entries.
+    fn rgb(&self) -> Vec<u8> {
+        let mut rgb = self.quant.color_map_rgb();
+        rgb.extend(self.background);
+        rgb
+    }
+
+    /// Entry of an RGBA pixel.
+    fn index(&mut self, p: &[u8]) -> u8 {
+        if self.transparent && p[3] < 128 || !self.transparent && p[..3] == self.background {
+            return self.background_index;
+        }
+        let quant = &self.quant;
+        *self
+            .cache
+            .entry([p[0], p[1], p[2]])
+            .or_insert_with(|| quant.index_of(&[p[0], p[1], p[2], 255]) as u8)
+    }
+}
+
+/// Frames a global palette is learned from.
+const PALETTE_FRAMES: usize = 16;
+
+/// Palette learned from frames spread over the whole animation.
+fn global_palette(
+    animation: &mut Animation,
+    loops: usize,
+    size: u32,
+    anim: &AnimationOptions,
+    colors: usize,
+) -> Result<Palette, String> {
+    let stride = animation
+        .frame_count(loops, anim.fps)
+        .div_ceil(PALETTE_FRAMES)
+        .max(1);
+    let mut pixels = Vec::new();
+    let mut index = 0;
+    while animation.completed_loops() < loops {
+        // Frames in between only move the traces along
+        if index % stride == 0 {
+            pixels.extend(straight_rgba(&animation.frame(size, anim.background)?));
+        } else {
+            animation.skip_frame();
+        }
+        animation.advance(1.0 / anim.fps);
+        index += 1;
+    }
+    Ok(Palette::new(&pixels, colors, anim.background))
+}
+
+/// Byte count written as a number with an optional `K` or `M` suffix
+/// (powers of 1024), e.g. `500K` or `1.5M`.
+pub fn parse_byte_size(s: &str) -> Result<usize, String> {
+    let s = s.trim();
+    let (number, unit) = match s.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&s[..i], 1024.0),
+        Some((i, 'm' | 'M')) => (&s[..i], 1024.0 * 1024.0),
+        _ => (s, 1.0),
+    };
+    match number.trim().parse::<f64>() {
+        Ok(n) if n > 0.0 && n.is_finite() => Ok((n * unit).round() as usize),
+        _ => Err(format!("invalid size: {s}")),
+    }
+}
+
+/// Color of a CSS color string: a named color, `transparent`, `#rgb`,
+/// `#rrggbb`, `#rrggbbaa`, `rgb(r, g, b)` or `rgba(r, g, b, a)`.
+pub fn parse_color(s: &str) -> Option<Color> {
+    let s = s.trim().to_ascii_lowercase();
+    if s == "transparent" {
+        return Some(Color::TRANSPARENT);
+    }
+    if let Some(hex) = s.strip_prefix('#') {
+        let digits: Vec<u8> = hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect::<Option<_>>()?;
+        let pair = |i: usize| digits[i] * 16 + digits[i + 1];
+        return match digits.len() {
+            3 => Some(Color::from_rgba8(
+                digits[0] * 17,
+                digits[1] * 17,
+                digits[2] * 17,
+                255,
+            )),
+            6 => Some(Color::from_rgba8(pair(0), pair(2), pair(4), 255)),
+            8 => Some(Color::from_rgba8(pair(0), pair(2), pair(4), pair(6))),
+            _ => None,
+        };
+    }
+    if let Some(args) = s
+        .strip_prefix("rgba(")
+        .or_else(|| s.strip_prefix("rgb("))
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        let v: Vec<f64> = args
+            .split(',')
+            .map(|a| a.trim().parse().ok())
+            .collect::<Option<_>>()?;
+        let channel = |x: f64| x.clamp(0.0, 255.0).round() as u8;
+        return match v[..] {
+            [r, g, b] => Some(Color::from_rgba8(channel(r), channel(g), channel(b), 255)),
+            [r, g, b, a] => Some(Color::from_rgba8(
+                channel(r),
+                channel(g),
+                channel(b),
+                channel(a * 255.0),
+            )),
+            _ => None,
+        };
+    }
+    let &(_, rgb) = NAMED_COLORS.iter().find(|(name, _)| *name == s)?;
+    Some(Color::from_rgba8(
+        (rgb >> 16) as u8,
+        (rgb >> 8) as u8,
+        rgb as u8,
+        255,
+    ))
+}
+
+/// CSS named colors.
+const NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
@@ -0,0 +1,176 @@
+use serde::Deserialize;
+
+use crate::canvas::{ChainStyle, ChainTiming};
+use crate::contour::{BoundingBox, Contour};
+use crate::model::HarmonicSteps;
+
+/// Several inputs drawn together on one canvas, each by its own epicycle
+/// chains.
+#[derive(Deserialize)]
+pub struct Scene {
+    /// How the shapes share the animation loop
+    #[serde(default = "default_timing")]
+    pub timing: ChainTiming,
+    pub shapes: Vec<SceneShape>,
+}
+
+/// One input of a scene and where and how it is drawn.
+#[derive(Deserialize)]
+pub struct SceneShape {
+    /// Input subcommand and its arguments, as on the command line, e.g.
+    /// `shape star --points 6` or `text --font Georgia 'Hello'`
+    pub input: String,
+    /// Move the center of the shape's bounding box to the origin before
+    /// scaling
+    #[serde(default)]
+    pub center: bool,
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    /// Offset added after scaling
+    #[serde(default)]
+    pub position: (f64, f64),
+    /// Fraction of a turn added to the shape's time
+    #[serde(default)]
+    pub phase: f64,
+    pub trace_colors: Option<Vec<String>>,
+    pub circle_colors: Option<Vec<String>>,
+    /// Harmonics schedule of this shape; speeds are taken from the page
+    /// schedule
+    pub steps: Option<HarmonicSteps>,
+}
+
+fn default_timing() -> ChainTiming {
+    ChainTiming::Parallel
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+impl Scene {
+    pub fn parse(text: &str) -> Result<Scene, String> {
+        let scene: Scene = serde_yaml::from_str(text).map_err(|e| format!("scene: {e}"))?;
+        scene.validate()?;
+        Ok(scene)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.shapes.is_empty() {
+            return Err("scene: no shapes".to_string());
+        }
+        for (i, shape) in self.shapes.iter().enumerate() {
+            if !(shape.scale > 0.0 && shape.scale.is_finite()) {
+                return Err(format!("shapes[{i}]: scale must be > 0"));
+            }
+            if !shape.phase.is_finite() {
+                return Err(format!("shapes[{i}]: phase must be a number"));
+            }
+            if let Some(steps) = &shape.steps {
+                steps.validate().map_err(|e| format!("shapes[{i}]: {e}"))?;
+            }
+            split_args(&shape.input).map_err(|e| format!("shapes[{i}]: {e}"))?;
+        }
+        Ok(())
+    }
+}
+
+impl SceneShape {
+    /// Arguments of `input`, split like a shell would.
+    pub fn args(&self) -> Vec<String> {
+        split_args(&self.input).expect("validated input")
+    }
+
+    /// Center, scale and move `(contour, offset)` groups read from the input.
+    pub fn place(&self, groups: Vec<(Contour, (f64, f64))>) -> Vec<(Contour, (f64, f64))> {
+        let shift = if self.center {
+            let points = groups
+                .iter()
+                .flat_map(|(c, (dx, dy))| c.points.iter().map(move |&(x, y)| (x + dx, y + dy)));
+            BoundingBox::of_points(points).map_or((0.0, 0.0), |b| b.center())
+        } else {
+            (0.0, 0.0)
+        };
+        let s = self.scale;
+        groups
+            .into_iter()
+            .map(|(contour, (dx, dy))| {
+                let points = contour
+                    .points
+                    .iter()
+                    .map(|&(x, y)| (x * s, y * s))
+                    .collect();
+                let offset = (
+                    (dx - shift.0) * s + self.position.0,
+                    (dy - shift.1) * s + self.position.1,
+                );
+                (Contour { points }, offset)
+            })
+            .collect()
+    }
+
+    pub fn style(&self) -> ChainStyle {
+        ChainStyle {
+            trace_colors: self.trace_colors.clone(),
+            circle_colors: self.circle_colors.clone(),
+            steps: self.steps.clone(),
+            phase: self.phase,
+        }
+    }
+}
+
+/// Split a command line into arguments. Single quotes keep their content
+/// as is; double quotes and backslashes work as in a POSIX shell.
+pub fn split_args(line: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some(arg) = current.take() {
+                    args.push(arg);
+                }
+            }
+            '\'' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => arg.push(c),
+                        None => return Err(format!("unclosed quote in: {line}")),
+                    }
+                }
+            }
+            '"' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => arg.push(c),
+                            Some(c) => {
+                                arg.push('\\');
+                                arg.push(c);
+                            }
+                            None => return Err(format!("unclosed quote in: {line}")),
+                        },
+                        Some(c) => arg.push(c),
+                        None => return Err(format!("unclosed quote in: {line}")),
+                    }
+                }
+            }
+            '\\' => {
+                let arg = current.get_or_insert_with(String::new);
+                if let Some(c) = chars.next() {
+                    arg.push(c);
+                }
+            }
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(current);
+    if args.is_empty() {
+        return Err("empty input".to_string());
+    }
+    Ok(args)
+}
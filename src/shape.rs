@@ -0,0 +1,371 @@
+use crate::contour::Contour;
+
+/// Built-in shape generators.
+///
+/// Shapes are produced in screen coordinates (y pointing down), upright and
+/// centered on the origin. Closed shapes repeat their first point at the end.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "cli", derive(clap::Subcommand))]
+pub enum Shape {
+    /// Regular polygon
+    Polygon {
+        /// Number of sides
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 6))]
+        sides: usize,
+        /// Circumradius
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 100.0))]
+        radius: f64,
+        /// Rotation in degrees
+        #[cfg_attr(
+            feature = "cli",
+            arg(long, default_value_t = 0.0, allow_hyphen_values = true)
+        )]
+        rotation: f64,
+    },
+    /// Star polygon alternating outer and inner vertices
+    Star {
+        /// Number of branches
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 5))]
+        points: usize,
+        /// Radius of the branch tips
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 100.0))]
+        outer: f64,
+        /// Radius of the inner vertices
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 40.0))]
+        inner: f64,
+        /// Rotation in degrees
+        #[cfg_attr(
+            feature = "cli",
+            arg(long, default_value_t = 0.0, allow_hyphen_values = true)
+        )]
+        rotation: f64,
+    },
+    /// Spirograph curve (hypotrochoid, or epitrochoid with --outside)
+    Spirograph {
+        /// Radius of the fixed circle
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 5))]
+        fixed: u32,
+        /// Radius of the rolling circle
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 3))]
+        rolling: u32,
+        /// Distance of the pen from the rolling circle's center
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 5.0))]
+        pen: f64,
+        /// Roll on the outside of the fixed circle
+        #[cfg_attr(feature = "cli", arg(long))]
+        outside: bool,
+        /// Scale factor applied to the curve
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 10.0))]
+        scale: f64,
+    },
+    /// Lissajous figure x = sin(a t + δ), y = sin(b t)
+    Lissajous {
+        /// Horizontal frequency
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 3))]
+        a: u32,
+        /// Vertical frequency
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 2))]
+        b: u32,
+        /// Phase shift δ in degrees
+        #[cfg_attr(
+            feature = "cli",
+            arg(long, default_value_t = 90.0, allow_hyphen_values = true)
+        )]
+        delta: f64,
+        /// Half width
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 100.0))]
+        width: f64,
+        /// Half height
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 100.0))]
+        height: f64,
+    },
+    /// Superellipse |x/a|^n + |y/b|^n = 1
+    Superellipse {
+        /// Horizontal semi-axis
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 100.0))]
+        a: f64,
+        /// Vertical semi-axis
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 100.0))]
+        b: f64,
+        /// Exponent (2 is an ellipse, larger values approach a rectangle)
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 4.0))]
+        n: f64,
+    },
+    /// Gear with trapezoidal teeth
+    Gear {
+        /// Number of teeth
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 12))]
+        teeth: usize,
+        /// Outer radius (tooth tips)
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 100.0))]
+        radius: f64,
+        /// Tooth depth
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 15.0))]
+        depth: f64,
+    },
+    /// Classic heart curve
+    Heart {
+        /// Scale factor (the curve spans about 32 × 30 units at scale 1)
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 6.0))]
+        scale: f64,
+    },
+    /// Koch snowflake
+    Koch {
+        /// Number of subdivision iterations
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 4))]
+        iterations: u32,
+        /// Side length of the initial triangle
+        #[cfg_attr(feature = "cli", arg(long, default_value_t = 200.0))]
+        size: f64,
+    },
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+fn closed(mut points: Vec<(f64, f64)>) -> Contour {
+    if let Some(&first) = points.first() {
+        points.push(first);
+    }
+    Contour { points }
+}
+
+/// Vertices of a regular polygon whose first vertex points up before rotation.
+fn ring(n: usize, radius: impl Fn(usize) -> f64, rotation: f64) -> Vec<(f64, f64)> {
+    let start = -std::f64::consts::FRAC_PI_2 + rotation.to_radians();
+    (0..n)
+        .map(|i| {
+            let a = start + std::f64::consts::TAU * i as f64 / n as f64;
+            let r = radius(i);
+            (r * a.cos(), r * a.sin())
+        })
+        .collect()
+}
+
+impl Shape {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shape::Polygon { .. } => "polygon",
+            Shape::Star { .. } => "star",
+            Shape::Spirograph { .. } => "spirograph",
+            Shape::Lissajous { .. } => "lissajous",
+            Shape::Superellipse { .. } => "superellipse",
+            Shape::Gear { .. } => "gear",
+            Shape::Heart { .. } => "heart",
+            Shape::Koch { .. } => "koch",
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let positive = |field: &str, v: f64| {
+            if v > 0.0 && v.is_finite() {
+                Ok(())
+            } else {
+                Err(format!("{}: {field} must be > 0", self.name()))
+            }
+        };
+        match *self {
+            Shape::Polygon { sides, radius, .. } => {
+                if sides < 3 {
+                    return Err("polygon: sides must be >= 3".to_string());
+                }
+                positive("radius", radius)
+            }
+            Shape::Star {
+                points,
+                outer,
+                inner,
+                ..
+            } => {
+                if points < 2 {
+                    return Err("star: points must be >= 2".to_string());
+                }
+                positive("outer", outer)?;
+                positive("inner", inner)
+            }
+            Shape::Spirograph {
+                fixed,
+                rolling,
+                scale,
+                ..
+            } => {
+                if fixed == 0 || rolling == 0 {
+                    return Err("spirograph: fixed and rolling must be > 0".to_string());
+                }
+                positive("scale", scale)
+            }
+            Shape::Lissajous {
+                a,
+                b,
+                width,
+                height,
+                ..
+            } => {
+                if a == 0 || b == 0 {
+                    return Err("lissajous: a and b must be > 0".to_string());
+                }
+                positive("width", width)?;
+                positive("height", height)
+            }
+            Shape::Superellipse { a, b, n } => {
+                positive("a", a)?;
+                positive("b", b)?;
+                positive("n", n)
+            }
+            Shape::Gear {
+                teeth,
+                radius,
+                depth,
+            } => {
+                if teeth < 3 {
+                    return Err("gear: teeth must be >= 3".to_string());
+                }
+                positive("radius", radius)?;
+                positive("depth", depth)?;
+                if depth >= radius {
+                    return Err(format!("gear: depth ({depth}) must be < radius ({radius})"));
+                }
+                Ok(())
+            }
+            Shape::Heart { scale } => positive("scale", scale),
+            Shape::Koch { iterations, size } => {
+                if iterations > 8 {
+                    return Err("koch: iterations must be <= 8".to_string());
+                }
+                positive("size", size)
+            }
+        }
+    }
+
+    /// Generate the shape's contour. Polygonal shapes (polygon, star, koch)
+    /// return their vertices; curved shapes are sampled at `samples` points.
+    pub fn contour(&self, samples: usize) -> Contour {
+        let samples = samples.max(3);
+        let tau = std::f64::consts::TAU;
+        let sample = |period: f64, f: &dyn Fn(f64) -> (f64, f64)| {
+            closed(
+                (0..samples)
+                    .map(|i| f(period * i as f64 / samples as f64))
+                    .collect(),
+            )
+        };
+        match *self {
+            Shape::Polygon {
+                sides,
+                radius,
+                rotation,
+            } => closed(ring(sides, |_| radius, rotation)),
+            Shape::Star {
+                points,
+                outer,
+                inner,
+                rotation,
+            } => closed(ring(
+                points * 2,
+                |i| if i % 2 == 0 { outer } else { inner },
+                rotation,
+            )),
+            Shape::Spirograph {
+                fixed,
+                rolling,
+                pen,
+                outside,
+                scale,
+            } => {
+                let (big_r, r) = (fixed as f64, rolling as f64);
+                // The curve closes after rolling/gcd(fixed, rolling) turns
+                let period = tau * (rolling / gcd(fixed, rolling)) as f64;
+                if outside {
+                    sample(period, &|t| {
+                        let k = (big_r + r) / r;
+                        (
+                            scale * ((big_r + r) * t.cos() - pen * (k * t).cos()),
+                            scale * ((big_r + r) * t.sin() - pen * (k * t).sin()),
+                        )
+                    })
+                } else {
+                    sample(period, &|t| {
+                        let k = (big_r - r) / r;
+                        (
+                            scale * ((big_r - r) * t.cos() + pen * (k * t).cos()),
+                            scale * ((big_r - r) * t.sin() - pen * (k * t).sin()),
+                        )
+                    })
+                }
+            }
+            Shape::Lissajous {
+                a,
+                b,
+                delta,
+                width,
+                height,
+            } => {
+                let delta = delta.to_radians();
+                sample(tau, &|t| {
+                    (
+                        width * (a as f64 * t + delta).sin(),
+                        height * (b as f64 * t).sin(),
+                    )
+                })
+            }
+            Shape::Superellipse { a, b, n } => sample(tau, &|t| {
+                let (c, s) = (t.cos(), t.sin());
+                (
+                    a * c.signum() * c.abs().powf(2.0 / n),
+                    b * s.signum() * s.abs().powf(2.0 / n),
+                )
+            }),
+            Shape::Gear {
+                teeth,
+                radius,
+                depth,
+            } => sample(tau, &|t| {
+                // Tooth profile over one pitch: rise, top land, fall, root
+                let phase = (t * teeth as f64 / tau).fract();
+                let h = if phase < 0.15 {
+                    phase / 0.15
+                } else if phase < 0.5 {
+                    1.0
+                } else if phase < 0.65 {
+                    1.0 - (phase - 0.5) / 0.15
+                } else {
+                    0.0
+                };
+                let r = radius - depth + depth * h;
+                (r * t.cos(), r * t.sin())
+            }),
+            Shape::Heart { scale } => sample(tau, &|t| {
+                (
+                    scale * 16.0 * t.sin().powi(3),
+                    -scale
+                        * (13.0 * t.cos()
+                            - 5.0 * (2.0 * t).cos()
+                            - 2.0 * (3.0 * t).cos()
+                            - (4.0 * t).cos()),
+                )
+            }),
+            Shape::Koch { iterations, size } => {
+                let r = size / 3f64.sqrt();
+                let mut points = ring(3, |_| r, 0.0);
+                for _ in 0..iterations {
+                    let mut next = Vec::with_capacity(points.len() * 4);
+                    for i in 0..points.len() {
+                        let (x0, y0) = points[i];
+                        let (x1, y1) = points[(i + 1) % points.len()];
+                        let (dx, dy) = ((x1 - x0) / 3.0, (y1 - y0) / 3.0);
+                        let a = (x0 + dx, y0 + dy);
+                        let b = (x0 + 2.0 * dx, y0 + 2.0 * dy);
+                        // Peak: rotate the middle third by -60° (outward for
+                        // clockwise-on-screen vertex order)
+                        let (c, s) = (0.5, -(3f64.sqrt()) / 2.0);
+                        let peak = (a.0 + dx * c - dy * s, a.1 + dx * s + dy * c);
+                        next.extend([(x0, y0), a, peak, b]);
+                    }
+                    points = next;
+                }
+                closed(points)
+            }
+        }
+    }
+}
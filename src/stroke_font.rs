@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::contour::Contour;
+use crate::text::{Align, GlyphPaths, TextLayout, word_indices};
+
+/// Built-in single-stroke font covering printable ASCII.
+///
+/// Each entry is `(char, advance, strokes)`. Glyphs are drawn on a grid with
+/// y pointing up, the baseline at 0, x-height 5, cap height 8 and descenders
+/// down to -3. Strokes are separated by `;` and made of points `x,y` and arcs
+/// `(cx,cy,rx,ry,from,to)` with angles in degrees, counterclockwise when
+/// `to > from`. An arc joins the previous point of its stroke with a line.
+const SIMPLEX: &[(char, f64, &str)] = &[
+    (' ', 4.0, ""),
+    ('!', 2.8, "0.5,8 0.5,2.5; 0.5,0.3 0.5,0"),
+    ('"', 4.3, "0.5,8 0.5,6; 2,8 2,6"),
+    (
+        '#',
+        7.8,
+        "1.5,0 2.5,8; 3.5,0 4.5,8; 0,2.7 5.5,2.7; 0.5,5.3 6,5.3",
+    ),
+    (
+        '$',
+        6.8,
+        "(2.5,6,2.5,2,30,270) (2.5,2,2.5,2,90,-150); 2.5,9 2.5,-1",
+    ),
+    ('%', 6.8, "0,0 5,8; (1,6.8,1,1,0,360); (4,1.2,1,1,0,360)"),
+    (
+        '&',
+        6.8,
+        "5,0 0.8,5.2 (2,6.5,1.4,1.5,223,-40) 0.3,2 (2,1.6,1.7,1.6,165,330) 5,3",
+    ),
+    ('\'', 2.8, "0.5,8 0.5,6"),
+    ('(', 4.3, "(3.2,4,3,5,125,235)"),
+    (')', 4.3, "(-1,4,3,5,55,-55)"),
+    ('*', 5.8, "2,7 2,3; 0.3,6 3.7,4; 3.7,6 0.3,4"),
+    ('+', 6.8, "2.5,1.5 2.5,6.5; 0,4 5,4"),
+    (',', 2.8, "0.8,0.5 0.8,0 0,-1.5"),
+    ('-', 5.3, "0,3.5 3.5,3.5"),
+    ('.', 2.8, "0.5,0.3 0.5,0"),
+    ('/', 5.8, "0,-1 4,9"),
+    ('0', 6.8, "(2.5,4,2.5,4,90,450)"),
+    ('1', 6.8, "0.8,6.5 2.5,8 2.5,0"),
+    ('2', 6.8, "(2.5,5.5,2.5,2.5,160,-40) 0,0 5,0"),
+    ('3', 6.8, "(2.5,6,2.5,2,150,-90) (2.5,2,2.5,2,90,-150)"),
+    ('4', 6.8, "3.5,0 3.5,8 0,2.5 5,2.5"),
+    ('5', 6.8, "4.5,8 0.5,8 (2.4,2.6,2.6,2.6,140,-150)"),
+    ('6', 6.8, "(2.5,4,2.5,4,60,180) (2.5,2.5,2.5,2.5,180,540)"),
+    ('7', 6.8, "0,8 5,8 1.5,0"),
+    ('8', 6.8, "(2.5,6,2.2,2,-90,270) (2.5,2,2.5,2,90,450)"),
+    ('9', 6.8, "(2.5,5.5,2.5,2.5,0,360) (2.5,4,2.5,4,0,-120)"),
+    (':', 2.8, "0.5,5 0.5,4.7; 0.5,0.3 0.5,0"),
+    (';', 2.8, "0.8,5 0.8,4.7; 0.8,0.5 0.8,0 0,-1.5"),
+    ('<', 6.8, "5,6.5 0,4 5,1.5"),
+    ('=', 6.8, "0,2.8 5,2.8; 0,5.2 5,5.2"),
+    ('>', 6.8, "0,6.5 5,4 0,1.5"),
+    (
+        '?',
+        6.8,
+        "(2.5,6,2.5,2,160,-60) 2.5,2.8 2.5,2.2; 2.5,0.3 2.5,0",
+    ),
+    (
+        '@',
+        9.0,
+        "(3.8,3.5,1.5,1.5,0,360); 5.3,5 5.3,2.5 (6,2.5,0.7,0.7,180,360) (3.8,3.8,3.4,3.8,10,320)",
+    ),
+    ('A', 7.8, "0,0 3,8 6,0; 1.1,3 4.9,3"),
+    (
+        'B',
+        8.3,
+        "0,0 0,8 4,8 (4,6,2,2,90,-90) 0,4; 0,4 4.5,4 (4.5,2,2,2,90,-90) 0,0",
+    ),
+    ('C', 8.8, "(3.5,4,3.5,4,45,315)"),
+    ('D', 7.8, "0,0 0,8 2,8 (2,4,4,4,90,-90) 0,0"),
+    ('E', 7.8, "6,8 0,8 0,0 6,0; 0,4 4.5,4"),
+    ('F', 7.8, "6,8 0,8 0,0; 0,4 4.5,4"),
+    ('G', 8.8, "(3.5,4,3.5,4,45,360) 4,4"),
+    ('H', 7.8, "0,0 0,8; 6,0 6,8; 0,4 6,4"),
+    ('I', 3.8, "0,8 2,8; 1,8 1,0; 0,0 2,0"),
+    ('J', 6.8, "5,8 5,2.5 (2.5,2.5,2.5,2.5,0,-180)"),
+    ('K', 7.8, "0,0 0,8; 6,8 0,2.5; 2,4.3 6,0"),
+    ('L', 6.8, "0,8 0,0 5,0"),
+    ('M', 8.8, "0,0 0,8 3.5,2 7,8 7,0"),
+    ('N', 7.8, "0,0 0,8 6,0 6,8"),
+    ('O', 8.8, "(3.5,4,3.5,4,90,450)"),
+    ('P', 7.8, "0,0 0,8 4,8 (4,6,2,2,90,-90) 0,4"),
+    ('Q', 8.8, "(3.5,4,3.5,4,90,450); 4,1.5 7,-1"),
+    ('R', 7.8, "0,0 0,8 4,8 (4,6,2,2,90,-90) 0,4; 4,4 6,0"),
+    ('S', 7.8, "(3,6,3,2,30,270) (3,2,3,2,90,-150)"),
+    ('T', 7.8, "0,8 6,8; 3,8 3,0"),
+    ('U', 7.8, "0,8 0,3 (3,3,3,3,180,360) 6,8"),
+    ('V', 7.8, "0,8 3,0 6,8"),
+    ('W', 9.8, "0,8 2,0 4,6 6,0 8,8"),
+    ('X', 7.8, "0,8 6,0; 6,8 0,0"),
+    ('Y', 7.8, "0,8 3,4 6,8; 3,4 3,0"),
+    ('Z', 7.8, "0,8 6,8 0,0 6,0"),
+    ('[', 3.8, "2,9 0,9 0,-1 2,-1"),
+    ('\\', 5.8, "0,9 4,-1"),
+    (']', 3.8, "0,9 2,9 2,-1 0,-1"),
+    ('^', 6.8, "0,5.5 2.5,8 5,5.5"),
+    ('_', 7.8, "0,-1 6,-1"),
+    ('`', 2.8, "0,8 1,6.5"),
+    ('a', 6.8, "5,5 5,0; (2.5,2.5,2.5,2.5,0,360)"),
+    ('b', 6.8, "0,8 0,0; (2.5,2.5,2.5,2.5,180,540)"),
+    ('c', 6.8, "(2.5,2.5,2.5,2.5,45,315)"),
+    ('d', 6.8, "5,8 5,0; (2.5,2.5,2.5,2.5,0,360)"),
+    ('e', 6.8, "0,2.5 5,2.5 (2.5,2.5,2.5,2.5,0,315)"),
+    ('f', 5.3, "(3,6.5,1.5,1.5,30,180) 1.5,0; 0,5 3.5,5"),
+    (
+        'g',
+        6.8,
+        "(2.5,2.5,2.5,2.5,0,360); 5,5 5,-1 (2.5,-1,2.5,2,0,-150)",
+    ),
+    ('h', 6.8, "0,8 0,0; 0,3 (2.5,3,2.5,2,180,0) 5,0"),
+    ('i', 2.8, "0.5,5 0.5,0; 0.5,6.5 0.5,7"),
+    ('j', 3.8, "2,5 2,-1.5 (0.5,-1.5,1.5,1.5,0,-180); 2,6.5 2,7"),
+    ('k', 6.3, "0,8 0,0; 4.5,5 0,1.8; 1.6,2.9 4.5,0"),
+    ('l', 3.3, "0.5,8 0.5,1 (1.5,1,1,1,180,270)"),
+    (
+        'm',
+        8.8,
+        "0,5 0,0; 0,3.5 (1.75,3.5,1.75,1.5,180,0) 3.5,0; 3.5,3.5 (5.25,3.5,1.75,1.5,180,0) 7,0",
+    ),
+    ('n', 6.3, "0,5 0,0; 0,3 (2.25,3,2.25,2,180,0) 4.5,0"),
+    ('o', 6.8, "(2.5,2.5,2.5,2.5,90,450)"),
+    ('p', 6.8, "0,5 0,-3; (2.5,2.5,2.5,2.5,180,540)"),
+    ('q', 6.8, "5,5 5,-3; (2.5,2.5,2.5,2.5,0,360)"),
+    ('r', 5.3, "0,5 0,0; 0,2.5 (2.5,2.5,2.5,2.5,180,60)"),
+    ('s', 5.8, "(2,3.75,2,1.25,30,270) (2,1.25,2,1.25,90,-150)"),
+    ('t', 5.3, "1.5,7 1.5,1 (2.5,1,1,1,180,300); 0,5 3.5,5"),
+    (
+        'u',
+        6.3,
+        "0,5 0,2.5 (2.25,2.5,2.25,2.5,180,360); 4.5,5 4.5,0",
+    ),
+    ('v', 6.8, "0,5 2.5,0 5,5"),
+    ('w', 7.8, "0,5 1.5,0 3,4 4.5,0 6,5"),
+    ('x', 6.3, "0,5 4.5,0; 4.5,5 0,0"),
+    ('y', 6.8, "0,5 2.6,0; 5,5 1.5,-3 0.5,-3"),
+    ('z', 6.3, "0,5 4.5,5 0,0 4.5,0"),
+    ('{', 4.3, "2.5,9 1.5,8.5 1.2,5 0,4 1.2,3 1.5,-0.5 2.5,-1"),
+    ('|', 2.8, "0.5,9 0.5,-1"),
+    ('}', 4.3, "0,9 1,8.5 1.3,5 2.5,4 1.3,3 1,-0.5 0,-1"),
+    ('~', 6.8, "0,3.5 1,4.3 2,4.3 3,3.7 4,3.7 5,4.5"),
+];
+
+/// One glyph of a stroke font: open polylines in screen orientation (y
+/// pointing down) relative to the glyph origin on the baseline.
+pub struct StrokeGlyph {
+    pub advance: f64,
+    pub strokes: Vec<Vec<(f64, f64)>>,
+}
+
+/// A single-line (Hershey-style) font whose glyphs are strokes rather than
+/// filled outlines.
+pub struct StrokeFont {
+    /// Size of the em in glyph units, used for line height and letter spacing
+    pub em: f64,
+    pub glyphs: HashMap<char, StrokeGlyph>,
+}
+
+fn parse_numbers(s: &str) -> Result<Vec<f64>, String> {
+    s.split(',')
+        .map(|n| {
+            n.trim()
+                .parse::<f64>()
+                .map_err(|_| format!("not a number: {n:?}"))
+        })
+        .collect()
+}
+
+/// Parse the strokes of a built-in glyph (see `SIMPLEX`).
+fn parse_strokes(def: &str) -> Result<Vec<Vec<(f64, f64)>>, String> {
+    let mut strokes = Vec::new();
+    for stroke in def.split(';').filter(|s| !s.trim().is_empty()) {
+        let mut points = Vec::new();
+        let mut rest = stroke.trim();
+        while !rest.is_empty() {
+            if let Some(arc) = rest.strip_prefix('(') {
+                let end = arc.find(')').ok_or("unclosed arc")?;
+                let [cx, cy, rx, ry, from, to] = parse_numbers(&arc[..end])?[..] else {
+                    return Err(format!("arc needs 6 numbers: {}", &arc[..end]));
+                };
+                let segments = ((to - from).abs() / 15.0).ceil().max(1.0) as usize;
+                points.extend((0..=segments).map(|i| {
+                    let a = (from + (to - from) * i as f64 / segments as f64).to_radians();
+                    (cx + rx * a.cos(), -(cy + ry * a.sin()))
+                }));
+                rest = arc[end + 1..].trim_start();
+            } else {
+                let end = rest.find([' ', '(']).unwrap_or(rest.len());
+                let [x, y] = parse_numbers(&rest[..end])?[..] else {
+                    return Err(format!("point must be x,y: {}", &rest[..end]));
+                };
+                points.push((x, -y));
+                rest = rest[end..].trim_start();
+            }
+        }
+        strokes.push(points);
+    }
+    Ok(strokes)
+}
+
+impl StrokeFont {
+    /// The built-in simplex font.
+    pub fn builtin() -> Self {
+        let glyphs = SIMPLEX
+            .iter()
+            .map(|&(ch, advance, def)| {
+                let strokes = parse_strokes(def)
+                    .unwrap_or_else(|e| panic!("built-in stroke font, glyph {ch:?}: {e}"));
+                (ch, StrokeGlyph { advance, strokes })
+            })
+            .collect();
+        StrokeFont { em: 12.0, glyphs }
+    }
+
+    /// Parse a Hershey font in the `.jhf` format. Glyphs are assigned to
+    /// characters in order, starting with the space character.
+    ///
+    /// Each glyph is a 5-column glyph number, a 3-column vertex count, then
+    /// the left and right bounds and the vertices as pairs of characters
+    /// offset from `R`; ` R` lifts the pen. Long glyphs may continue on the
+    /// following lines.
+    pub fn from_jhf(text: &str) -> Result<Self, String> {
+        let mut glyphs = HashMap::new();
+        let mut lines = text.lines().enumerate();
+        let mut ch = ' ';
+        while let Some((n, line)) = lines.next() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+            let count = line
+                .get(5..8)
+                .and_then(|c| c.trim().parse::<usize>().ok())
+                .ok_or_else(|| format!("line {}: invalid glyph header", n + 1))?;
+            // The count includes the left and right bounds
+            if count == 0 {
+                return Err(format!("line {}: glyph has no bounds", n + 1));
+            }
+            let mut data: Vec<u8> = line.as_bytes()[8..].to_vec();
+            while data.len() < count * 2 {
+                let (_, more) = lines
+                    .next()
+                    .ok_or_else(|| format!("line {}: truncated glyph", n + 1))?;
+                data.extend_from_slice(more.trim_end_matches('\r').as_bytes());
+            }
+            if data.len() < 2 {
+                return Err(format!("line {}: glyph has no bounds", n + 1));
+            }
+            let coord = |b: u8| b as f64 - b'R' as f64;
+            let (left, right) = (coord(data[0]), coord(data[1]));
+            let mut strokes = vec![];
+            let mut stroke = vec![];
+            for pair in data[2..count * 2].chunks(2) {
+                if pair == b" R" {
+                    strokes.push(std::mem::take(&mut stroke));
+                } else {
+                    // Hershey fonts put the baseline at y = 9
+                    stroke.push((coord(pair[0]) - left, coord(pair[1]) - 9.0));
+                }
+            }
+            strokes.push(stroke);
+            strokes.retain(|s| !s.is_empty());
+            glyphs.insert(
+                ch,
+                StrokeGlyph {
+                    advance: right - left,
+                    strokes,
+                },
+            );
+            ch = char::from_u32(ch as u32 + 1).ok_or("too many glyphs")?;
+        }
+        if glyphs.is_empty() {
+            return Err("no glyphs found".to_string());
+        }
+        Ok(StrokeFont { em: 32.0, glyphs })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        Self::from_jhf(&text).map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
+
+/// Strokes of a laid out text, in reading order.
+pub struct StrokeText {
+    pub strokes: Vec<Contour>,
+    /// The same strokes split by glyph
+    pub glyphs: Vec<GlyphPaths>,
+    /// Characters the font has no glyph for, in order of first appearance
+    pub missing: Vec<char>,
+}
+
+/// Lay out `text` with a stroke font, with the same line, alignment and
+/// spacing rules as `svg_path_of_text`. The first baseline is at y = 0.
+pub fn stroke_text(text: &str, font: &StrokeFont, layout: &TextLayout) -> StrokeText {
+    let spacing = layout.letter_spacing * font.em;
+    let mut missing = Vec::new();
+    let mut next_word = 0;
+    let lines: Vec<_> = text
+        .lines()
+        .map(|line| {
+            let (words, next) = word_indices(line, next_word);
+            next_word = next;
+            let mut placed = Vec::new();
+            let mut x = 0.0;
+            for (i, ch) in line.char_indices() {
+                match font.glyphs.get(&ch) {
+                    Some(glyph) => {
+                        placed.push((glyph, x, words[i]));
+                        x += glyph.advance + spacing;
+                    }
+                    None if !missing.contains(&ch) => missing.push(ch),
+                    None => {}
+                }
+            }
+            let width = if placed.is_empty() { 0.0 } else { x - spacing };
+            (placed, width)
+        })
+        .collect();
+    let max_width = lines.iter().map(|(_, w)| *w).fold(0.0, f64::max);
+
+    let mut strokes = Vec::new();
+    let mut glyphs = Vec::new();
+    for (n, (placed, width)) in lines.iter().enumerate() {
+        let x0 = match layout.align {
+            Align::Left => 0.0,
+            Align::Center => (max_width - width) / 2.0,
+            Align::Right => max_width - width,
+        };
+        let baseline = n as f64 * layout.line_height * font.em;
+        for &(glyph, x, word) in placed {
+            let origin = (x0 + x, baseline);
+            let paths: Vec<Contour> = glyph
+                .strokes
+                .iter()
+                .map(|stroke| Contour {
+                    points: stroke.clone(),
+                })
+                .collect();
+            strokes.extend(paths.iter().map(|c| {
+                Contour {
+                    points: c
+                        .points
+                        .iter()
+                        .map(|&(sx, sy)| (origin.0 + sx, origin.1 + sy))
+                        .collect(),
+                }
+            }));
+            glyphs.push(GlyphPaths {
+                origin,
+                word,
+                paths,
+            });
+        }
+    }
+    StrokeText {
+        strokes,
+        glyphs,
+        missing,
+    }
+}
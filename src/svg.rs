@@ -0,0 +1,436 @@
+use std::collections::HashMap;
+
+use crate::boolean::union;
+use crate::canvas::subpaths_of_svg_path;
+use crate::contour::{Contour, polygon_area};
+
+/// Shape of the corners where stroke segments meet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+/// Shape of the ends of open strokes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+pub struct StrokeStyle {
+    pub width: f64,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// Miters longer than this many stroke widths become bevels
+    pub miter_limit: f64,
+}
+
+/// A drawn element of an SVG file.
+pub struct SvgItem {
+    pub subpaths: Vec<Contour>,
+    pub filled: bool,
+    pub stroke: Option<StrokeStyle>,
+}
+
+/// How stroked SVG elements become contours.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum StrokeMode {
+    /// Use each element's path as it is, stroked or not
+    Path,
+    /// Offset strokes into closed outlines of their drawn width
+    Outline,
+    /// Keep the stroke centerlines; open ones are closed with `OpenCurve`
+    Centerline,
+}
+
+/// Properties inherited from ancestor elements.
+const INHERITED: [&str; 6] = [
+    "fill",
+    "stroke",
+    "stroke-width",
+    "stroke-linejoin",
+    "stroke-linecap",
+    "stroke-miterlimit",
+];
+
+/// Elements whose content is not drawn where it appears.
+const NOT_RENDERED: [&str; 8] = [
+    "defs",
+    "clipPath",
+    "mask",
+    "symbol",
+    "marker",
+    "pattern",
+    "linearGradient",
+    "radialGradient",
+];
+
+/// Samples used for circles, ellipses and round joins and caps.
+const ARC_SEGMENTS: usize = 64;
+
+/// Read the drawn shapes of an SVG document: `path`, `line`, `polyline`,
+/// `polygon`, `rect`, `circle` and `ellipse` elements, with the fill and
+/// stroke properties they get from their attributes, `style` and ancestors.
+/// Transforms are ignored.
+pub fn parse_svg(text: &str) -> Vec<SvgItem> {
+    let mut stack: Vec<(HashMap<String, String>, bool)> = vec![(HashMap::new(), false)];
+    let mut items = Vec::new();
+    let mut rest = text;
+    while let Some(lt) = rest.find('<') {
+        rest = &rest[lt..];
+        let skip_to = |rest: &str, end: &str| rest.find(end).map_or(rest.len(), |i| i + end.len());
+        if rest.starts_with("<!--") {
+            rest = &rest[skip_to(rest, "-->")..];
+            continue;
+        }
+        if rest.starts_with("<![CDATA[") {
+            rest = &rest[skip_to(rest, "]]>")..];
+            continue;
+        }
+        if rest.starts_with("<?") || rest.starts_with("<!") {
+            rest = &rest[skip_to(rest, ">")..];
+            continue;
+        }
+        if rest.starts_with("</") {
+            if stack.len() > 1 {
+                stack.pop();
+            }
+            rest = &rest[skip_to(rest, ">")..];
+            continue;
+        }
+        let end = tag_end(rest);
+        let tag = &rest[1..end];
+        rest = &rest[(end + 1).min(rest.len())..];
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let name_len = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+        let name = &tag[..name_len];
+        let attrs = parse_attributes(&tag[name_len..]);
+
+        let (parent, parent_hidden) = stack.last().expect("root entry");
+        let mut props = parent.clone();
+        for key in INHERITED {
+            if let Some(v) = attrs.get(key) {
+                props.insert(key.to_string(), v.clone());
+            }
+        }
+        let mut display_none = attrs.get("display").is_some_and(|v| v == "none");
+        if let Some(style) = attrs.get("style") {
+            for decl in style.split(';') {
+                if let Some((k, v)) = decl.split_once(':') {
+                    let (k, v) = (k.trim(), v.trim());
+                    if INHERITED.contains(&k) {
+                        props.insert(k.to_string(), v.to_string());
+                    } else if k == "display" && v == "none" {
+                        display_none = true;
+                    }
+                }
+            }
+        }
+        let hidden = *parent_hidden || display_none || NOT_RENDERED.contains(&name);
+
+        if !hidden && let Some(subpaths) = element_subpaths(name, &attrs) {
+            let paint =
+                |key: &str, default: &str| props.get(key).map_or(default, |v| v.as_str()) != "none";
+            let width = props.get("stroke-width").map_or(1.0, |v| length(v));
+            let stroke = (paint("stroke", "none") && width > 0.0).then(|| StrokeStyle {
+                width,
+                join: match props.get("stroke-linejoin").map(|v| v.as_str()) {
+                    Some("round") => LineJoin::Round,
+                    Some("bevel") => LineJoin::Bevel,
+                    _ => LineJoin::Miter,
+                },
+                cap: match props.get("stroke-linecap").map(|v| v.as_str()) {
+                    Some("round") => LineCap::Round,
+                    Some("square") => LineCap::Square,
+                    _ => LineCap::Butt,
+                },
+                miter_limit: props.get("stroke-miterlimit").map_or(4.0, |v| length(v)),
+            });
+            let filled = paint("fill", "black");
+            if (filled || stroke.is_some()) && !subpaths.is_empty() {
+                items.push(SvgItem {
+                    subpaths,
+                    filled,
+                    stroke,
+                });
+            }
+        }
+        if !self_closing {
+            stack.push((props, hidden));
+        }
+    }
+    items
+}
+
+/// Index of the `>` closing the tag at the start of `s`, skipping quoted
+/// attribute values.
+fn tag_end(s: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return i,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+fn parse_attributes(s: &str) -> HashMap<String, String> {
+    let mut attrs = HashMap::new();
+    let mut rest = s.trim_start();
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].trim().to_string();
+        let after = rest[eq + 1..].trim_start();
+        let Some(q) = after.chars().next().filter(|&c| c == '"' || c == '\'') else {
+            break;
+        };
+        let Some(close) = after[1..].find(q) else {
+            break;
+        };
+        attrs.insert(key, after[1..1 + close].to_string());
+        rest = after[close + 2..].trim_start();
+    }
+    attrs
+}
+
+/// A length in user units; units such as `px` are ignored.
+fn length(v: &str) -> f64 {
+    let v = v.trim();
+    let end = v
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E')))
+        .unwrap_or(v.len());
+    v[..end].parse().unwrap_or(0.0)
+}
+
+fn numbers(v: &str) -> Vec<f64> {
+    v.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(length)
+        .collect()
+}
+
+fn closed(mut points: Vec<(f64, f64)>) -> Contour {
+    if let Some(&first) = points.first() {
+        points.push(first);
+    }
+    Contour { points }
+}
+
+fn ellipse(cx: f64, cy: f64, rx: f64, ry: f64) -> Contour {
+    closed(
+        (0..ARC_SEGMENTS)
+            .map(|i| {
+                let a = std::f64::consts::TAU * i as f64 / ARC_SEGMENTS as f64;
+                (cx + rx * a.cos(), cy + ry * a.sin())
+            })
+            .collect(),
+    )
+}
+
+/// Subpaths of a shape element, or `None` for other elements.
+fn element_subpaths(name: &str, attrs: &HashMap<String, String>) -> Option<Vec<Contour>> {
+    let num = |k: &str| attrs.get(k).map_or(0.0, |v| length(v));
+    let pairs = |k: &str| {
+        numbers(attrs.get(k).map_or("", |v| v.as_str()))
+            .chunks_exact(2)
+            .map(|p| (p[0], p[1]))
+            .collect::<Vec<_>>()
+    };
+    let subpaths = match name {
+        "path" => subpaths_of_svg_path(attrs.get("d").map_or("", |v| v.as_str())),
+        "line" => vec![Contour {
+            points: vec![(num("x1"), num("y1")), (num("x2"), num("y2"))],
+        }],
+        "polyline" => vec![Contour {
+            points: pairs("points"),
+        }],
+        "polygon" => vec![closed(pairs("points"))],
+        "rect" => {
+            let (x, y, w, h) = (num("x"), num("y"), num("width"), num("height"));
+            vec![closed(vec![(x, y), (x + w, y), (x + w, y + h), (x, y + h)])]
+        }
+        "circle" => vec![ellipse(num("cx"), num("cy"), num("r"), num("r"))],
+        "ellipse" => vec![ellipse(num("cx"), num("cy"), num("rx"), num("ry"))],
+        _ => return None,
+    };
+    Some(
+        subpaths
+            .into_iter()
+            .filter(|c| c.points.len() > 1)
+            .collect(),
+    )
+}
+
+fn is_closed(points: &[(f64, f64)]) -> bool {
+    match (points.first(), points.last()) {
+        (Some(a), Some(b)) => points.len() > 2 && (a.0 - b.0).hypot(a.1 - b.1) < 1e-9,
+        _ => false,
+    }
+}
+
+/// Counterclockwise polygon (positive `polygon_area`), so that overlapping
+/// pieces add up under the nonzero rule.
+fn piece(points: Vec<(f64, f64)>) -> Contour {
+    let mut points = points;
+    if polygon_area(&points) < 0.0 {
+        points.reverse();
+    }
+    Contour { points }
+}
+
+/// Closed pieces whose union is the outline of `path` stroked with `style`:
+/// one quad per segment, plus joins and caps. Pass them to
+/// [`crate::boolean::flatten`] to get the outline itself.
+pub fn stroke_pieces(path: &Contour, style: &StrokeStyle) -> Vec<Contour> {
+    let mut pts: Vec<(f64, f64)> = Vec::with_capacity(path.points.len());
+    for &p in &path.points {
+        if pts
+            .last()
+            .is_none_or(|&q: &(f64, f64)| (p.0 - q.0).hypot(p.1 - q.1) > 1e-9)
+        {
+            pts.push(p);
+        }
+    }
+    let hw = style.width / 2.0;
+    let disk = |(x, y): (f64, f64)| piece(ellipse(x, y, hw, hw).points);
+    let closed = is_closed(&pts);
+    if closed {
+        pts.pop();
+    }
+    if pts.len() == 1 {
+        // A zero-length subpath only shows its caps
+        let (x, y) = pts[0];
+        return match style.cap {
+            LineCap::Butt => vec![],
+            LineCap::Round => vec![disk(pts[0])],
+            LineCap::Square => vec![piece(vec![
+                (x - hw, y - hw),
+                (x + hw, y - hw),
+                (x + hw, y + hw),
+                (x - hw, y + hw),
+            ])],
+        };
+    }
+
+    let n = pts.len();
+    let seg_count = if closed { n } else { n - 1 };
+    let dir = |i: usize| {
+        let (a, b) = (pts[i % n], pts[(i + 1) % n]);
+        let len = (b.0 - a.0).hypot(b.1 - a.1);
+        ((b.0 - a.0) / len, (b.1 - a.1) / len)
+    };
+    let at = |p: (f64, f64), v: (f64, f64), k: f64| (p.0 + v.0 * k, p.1 + v.1 * k);
+    let normal = |d: (f64, f64)| (-d.1, d.0);
+
+    let mut pieces = Vec::new();
+    for i in 0..seg_count {
+        let (a, b) = (pts[i], pts[(i + 1) % n]);
+        let nv = normal(dir(i));
+        pieces.push(piece(vec![
+            at(a, nv, hw),
+            at(b, nv, hw),
+            at(b, nv, -hw),
+            at(a, nv, -hw),
+        ]));
+    }
+
+    let joins: Vec<usize> = if closed {
+        (0..n).collect()
+    } else {
+        (1..n - 1).collect()
+    };
+    for i in joins {
+        let v = pts[i];
+        let (d0, d1) = (dir((i + n - 1) % n), dir(i));
+        let cross = d0.0 * d1.1 - d0.1 * d1.0;
+        if cross.abs() < 1e-12 && d0.0 * d1.0 + d0.1 * d1.1 > 0.0 {
+            continue;
+        }
+        // The outer side of the turn
+        let s = if cross > 0.0 { -hw } else { hw };
+        let (n0, n1) = (normal(d0), normal(d1));
+        let bevel = piece(vec![v, at(v, n0, s), at(v, n1, s)]);
+        match style.join {
+            LineJoin::Round => pieces.push(disk(v)),
+            LineJoin::Bevel => pieces.push(bevel),
+            LineJoin::Miter => {
+                let sum = (n0.0 + n1.0, n0.1 + n1.1);
+                let sq = sum.0 * sum.0 + sum.1 * sum.1;
+                // Miter length over stroke width is 2 / |n0 + n1|
+                if sq > 1e-12 && 2.0 / sq.sqrt() <= style.miter_limit {
+                    let m = at(v, sum, 2.0 * s / sq);
+                    pieces.push(piece(vec![v, at(v, n0, s), m, at(v, n1, s)]));
+                } else {
+                    pieces.push(bevel);
+                }
+            }
+        }
+    }
+
+    if !closed {
+        for (p, d) in [
+            (pts[0], {
+                let d = dir(0);
+                (-d.0, -d.1)
+            }),
+            (pts[n - 1], dir(n - 2)),
+        ] {
+            match style.cap {
+                LineCap::Butt => {}
+                LineCap::Round => pieces.push(disk(p)),
+                LineCap::Square => {
+                    let nv = normal(d);
+                    let q = at(p, d, hw);
+                    pieces.push(piece(vec![
+                        at(p, nv, hw),
+                        at(q, nv, hw),
+                        at(q, nv, -hw),
+                        at(p, nv, -hw),
+                    ]));
+                }
+            }
+        }
+    }
+    pieces
+}
+
+/// Contours drawn by `items`, as closed outlines and open centerlines.
+/// Filled subpaths are outlines as they are; strokes are kept as they are,
+/// turned into outlines merged with the item's fill, or kept as
+/// centerlines, depending on `mode`.
+pub fn svg_contours(items: &[SvgItem], mode: StrokeMode) -> (Vec<Contour>, Vec<Contour>) {
+    let mut outlines = Vec::new();
+    let mut centerlines = Vec::new();
+    for item in items {
+        match (&item.stroke, mode) {
+            (Some(style), StrokeMode::Outline) => {
+                let pieces: Vec<Contour> = item
+                    .subpaths
+                    .iter()
+                    .flat_map(|p| stroke_pieces(p, style))
+                    .collect();
+                // The fill keeps its own winding, so it is merged separately
+                let fill = if item.filled { &item.subpaths[..] } else { &[] };
+                outlines.extend(union(fill, &pieces));
+            }
+            (Some(_), StrokeMode::Centerline) if !item.filled => {
+                for p in &item.subpaths {
+                    if is_closed(&p.points) {
+                        outlines.push(p.clone());
+                    } else {
+                        centerlines.push(p.clone());
+                    }
+                }
+            }
+            _ => outlines.extend(item.subpaths.iter().cloned()),
+        }
+    }
+    (outlines, centerlines)
+}
@@ -1,10 +1,46 @@
 #[cfg(test)]
 mod tests {
+    use crate::boolean::{BooleanOp, boolean, difference, flatten, intersection, union};
     use crate::canvas::{
-        embed_html_of_svg_path_with_fourier, html_of_svg_path, svg_path_of_contour,
+        Chain, ChainStyle, ChainTiming, embed_html_of_chains, embed_html_of_svg_path_with_fourier,
+        html_of_chains, html_of_svg_path, subpaths_of_svg_path, svg_path_of_contour,
     };
-    use crate::contour::{Contour, ContourFunction, f_of_contour, fourier_decomposition};
-    use crate::model::EmbedOptions;
+    use crate::contour::{
+        BoundingBox, ComplexCoeff, Contour, ContourFunction, FourierDecomposition, Intersection,
+        OpenCurve, Orientation, StartAnchor, close_open_paths, contour_of_function, f_of_contour,
+        fourier_decomposition, interpolate, simplify_douglas_peucker, simplify_visvalingam,
+        smooth_gaussian, smooth_low_pass, smooth_moving_average, smooth_savitzky_golay,
+    };
+    use crate::equation::{Complex, ComplexMap, CurveForm, EquationCurve, Expr, parse_param};
+    use crate::export::{
+        AnimatedSvgOptions, StaticSvgOptions, SvgLayout, animated_svg, static_svg,
+    };
+    use crate::geojson::{GeoOptions, Projection, parse_geojson, parse_selector};
+    use crate::model::{
+        Congruence, EmbedOptions, HarmonicRange, HarmonicSteps, Preprocess, Visualization,
+        WhenToShow,
+    };
+    use crate::points::{CsvOptions, PointsFormat, parse_points};
+    use crate::poster::{ErrorMetric, PosterOptions, poster_png, poster_svg, poster_tiles};
+    use crate::raster::{
+        GrayImage, Mask, TraceOptions, decode_pnm, marching_squares, remove_collinear,
+        skeleton_paths, skeletonize, trace_centerlines, trace_image,
+    };
+    use crate::render::{
+        Animation, AnimationFormat, AnimationOptions, PaletteMode, encode_animation,
+        parse_byte_size, parse_color,
+    };
+    use crate::scene::{Scene, split_args};
+    use crate::shape::Shape;
+    use crate::stroke_font::{StrokeFont, stroke_text};
+    use crate::svg::{
+        LineCap, LineJoin, StrokeMode, StrokeStyle, parse_svg, stroke_pieces, svg_contours,
+    };
+    use crate::text::{
+        Align, FontSource, FontSpec, TextChains, TextLayout, group_glyphs, parse_variation,
+        svg_path_of_text, word_indices,
+    };
+    use std::collections::HashMap;
 
     #[test]
     fn test_square() {
@@ -187,4 +223,1915 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_html_of_chains() {
+        let chain = |size: f64, offset: (f64, f64)| {
+            let contour = Contour {
+                points: vec![
+                    (0.0, 0.0),
+                    (size, 0.0),
+                    (size, size),
+                    (0.0, size),
+                    (0.0, 0.0),
+                ],
+            };
+            let fourier = fourier_decomposition(&contour, 4);
+            Chain {
+                contour,
+                fourier,
+                offset,
+                style: ChainStyle::default(),
+            }
+        };
+        let chains = [chain(1.0, (0.0, 0.0)), chain(3.0, (10.0, 5.0))];
+        let opts = EmbedOptions::default();
+
+        // Sequential: loop time is shared in proportion to contour length
+        let html = html_of_chains(&chains, ChainTiming::Sequential, &opts, None);
+        assert!(html.contains("dx:0,dy:0,start:0,end:0.25,n:5"));
+        assert!(html.contains("dx:10,dy:5,start:0.25,end:1,n:5"));
+        // Contours are drawn at their offsets
+        assert!(html.contains("M 10 5 L 13 5"));
+
+        let html = embed_html_of_chains(&chains, ChainTiming::Parallel, &opts);
+        assert_eq!(html.matches("start:0,end:1,").count(), 2);
+    }
+
+    #[test]
+    fn test_chain_style() {
+        let contour = Contour {
+            points: vec![(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 0.0)],
+        };
+        let fourier = fourier_decomposition(&contour, 8);
+        let chain = Chain {
+            contour,
+            fourier,
+            offset: (0.0, 0.0),
+            style: ChainStyle {
+                trace_colors: Some(vec!["gold".into()]),
+                circle_colors: None,
+                steps: Some(HarmonicSteps {
+                    ranges: vec![HarmonicRange {
+                        from: 1,
+                        step: 2,
+                        to: 6,
+                        speed: 1.0,
+                    }],
+                }),
+                phase: 0.25,
+            },
+        };
+        let html = html_of_chains(
+            &[chain],
+            ChainTiming::Parallel,
+            &EmbedOptions::default(),
+            None,
+        );
+        assert!(html.contains(",n:4,ph:0.25,colors:[\"gold\"],nh:[1,3,5,7]}"));
+    }
+
+    #[test]
+    fn test_scene() {
+        let scene = Scene::parse(
+            r#"
+shapes:
+  - input: shape polygon --sides 4
+  - input: text --stroke-font simplex 'Hello world'
+    center: true
+    scale: 2
+    position: [10, -5]
+    phase: 0.5
+"#,
+        )
+        .unwrap();
+        assert_eq!(scene.timing, ChainTiming::Parallel);
+        assert_eq!(
+            scene.shapes[1].args(),
+            ["text", "--stroke-font", "simplex", "Hello world"]
+        );
+        assert_eq!(scene.shapes[1].style().phase, 0.5);
+
+        let groups = vec![
+            (
+                Contour {
+                    points: vec![(0.0, 0.0), (2.0, 0.0)],
+                },
+                (0.0, 0.0),
+            ),
+            (
+                Contour {
+                    points: vec![(0.0, 0.0), (0.0, 2.0)],
+                },
+                (4.0, 0.0),
+            ),
+        ];
+        // Bounding box (0,0)-(4,2) is centered on (2,1), then scaled by 2
+        let placed = scene.shapes[1].place(groups);
+        assert_eq!(placed[0].0.points, vec![(0.0, 0.0), (4.0, 0.0)]);
+        assert_eq!(placed[0].1, (6.0, -7.0));
+        assert_eq!(placed[1].1, (14.0, -7.0));
+
+        assert_eq!(
+            split_args(r#"text "a \"b\"" c\ d"#).unwrap(),
+            ["text", "a \"b\"", "c d"]
+        );
+        assert!(split_args("text 'open").is_err());
+        assert!(Scene::parse("shapes: []").is_err());
+        assert!(Scene::parse("shapes:\n  - input: shape heart\n    scale: 0").is_err());
+    }
+
+    #[test]
+    fn test_boolean_ops() {
+        let square = |x: f64, y: f64, size: f64| Contour {
+            points: vec![(x, y), (x + size, y), (x + size, y + size), (x, y + size)],
+        };
+        let area = |contours: &[Contour]| -> f64 {
+            contours
+                .iter()
+                .map(|c| {
+                    let p = &c.points;
+                    (0..p.len())
+                        .map(|i| {
+                            let (a, b) = (p[i], p[(i + 1) % p.len()]);
+                            a.0 * b.1 - b.0 * a.1
+                        })
+                        .sum::<f64>()
+                        / 2.0
+                })
+                .sum::<f64>()
+                .abs()
+        };
+        let a = [square(0.0, 0.0, 2.0)];
+        let b = [square(1.0, 1.0, 2.0)];
+
+        // Results are exact, and corners are kept as vertices
+        let u = union(&a, &b);
+        assert_eq!(u.len(), 1);
+        assert!((area(&u) - 7.0).abs() < 1e-9, "{}", area(&u));
+        let i = intersection(&a, &b);
+        assert!((area(&i) - 1.0).abs() < 1e-9, "{}", area(&i));
+        for corner in [(1.0, 1.0), (2.0, 1.0), (2.0, 2.0), (1.0, 2.0)] {
+            assert!(i[0].points.contains(&corner));
+        }
+        let d = difference(&a, &b);
+        assert!((area(&d) - 3.0).abs() < 1e-9, "{}", area(&d));
+        assert_eq!(boolean(&a, &b, BooleanOp::Xor).len(), 2);
+        assert!(intersection(&a, &[square(5.0, 5.0, 1.0)]).is_empty());
+
+        // Overlapping outlines merge; a reversed inner contour stays a hole
+        let mut hole = square(0.5, 0.5, 1.0);
+        hole.points.reverse();
+        let flat = flatten(&[square(0.0, 0.0, 2.0), square(1.0, 0.0, 2.0), hole]);
+        assert_eq!(flat.len(), 2);
+        assert!((area(&flat[..1]).abs() - 6.0).abs() < 1e-9);
+
+        // A shared edge and a T-junction: the squares merge into one
+        // rectangle, and squares touching at a corner stay apart
+        let side = union(&a, &[square(2.0, 0.0, 2.0), square(2.0, 2.0, 1.0)]);
+        assert_eq!(side.len(), 1);
+        assert!((area(&side) - 9.0).abs() < 1e-9);
+        assert_eq!(union(&a, &[square(2.0, 2.0, 1.0)]).len(), 2);
+        // A self-crossing bow tie fills both lobes
+        let bow = Contour {
+            points: vec![(0.0, 0.0), (2.0, 2.0), (2.0, 0.0), (0.0, 2.0)],
+        };
+        let lobes = flatten(&[bow]);
+        assert_eq!(lobes.len(), 2);
+        assert!((area(&lobes[..1]) - 1.0).abs() < 1e-9);
+
+        let subpaths = subpaths_of_svg_path("M 0 0 L 1 0 L 1 1 Z m 2 0 l 1 0 l 0 1 z");
+        assert_eq!(subpaths.len(), 2);
+        assert_eq!(subpaths[1].points[0], (2.0, 0.0));
+    }
+
+    #[test]
+    fn test_svg_strokes() {
+        let items = parse_svg(
+            r#"<?xml version="1.0"?>
+<svg fill="none" stroke="black" stroke-width="2">
+  <defs><circle cx="0" cy="0" r="5"/></defs>
+  <line x1="0" y1="0" x2="10" y2="0" stroke-linecap="square"/>
+  <g style="stroke: none; fill: red"><rect x="0" y="5" width="4" height="2"/></g>
+  <path d="M 0 10 L 1 10" stroke="none"/>
+</svg>"#,
+        );
+        assert_eq!(items.len(), 2);
+        let style = items[0].stroke.as_ref().unwrap();
+        assert_eq!((style.width, style.cap), (2.0, LineCap::Square));
+        assert!(!items[0].filled);
+        assert!(items[1].filled && items[1].stroke.is_none());
+
+        let area = |contours: &[Contour]| -> f64 {
+            contours
+                .iter()
+                .map(|c| {
+                    let p = &c.points;
+                    (0..p.len())
+                        .map(|i| {
+                            let (a, b) = (p[i], p[(i + 1) % p.len()]);
+                            a.0 * b.1 - b.0 * a.1
+                        })
+                        .sum::<f64>()
+                        / 2.0
+                })
+                .sum::<f64>()
+                .abs()
+        };
+        // A 10 × 2 stroke, extended by 1 at each end by square caps
+        let (outlines, centerlines) = svg_contours(&items, StrokeMode::Outline);
+        assert!(centerlines.is_empty());
+        assert!(
+            (area(&outlines[..1]) - 24.0).abs() < 1e-9,
+            "{}",
+            area(&outlines[..1])
+        );
+
+        // A right-angle polyline with a miter join covers its full corner
+        let corner = Contour {
+            points: vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)],
+        };
+        let miter = StrokeStyle {
+            width: 2.0,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 4.0,
+        };
+        let merged = flatten(&stroke_pieces(&corner, &miter));
+        assert!((area(&merged) - 40.0).abs() < 1e-9, "{}", area(&merged));
+
+        let (_, centerlines) = svg_contours(&items, StrokeMode::Centerline);
+        assert_eq!(centerlines.len(), 1);
+
+        // By default paths are kept as drawn
+        let (outlines, centerlines) = svg_contours(&items, StrokeMode::Path);
+        assert!(centerlines.is_empty());
+        assert_eq!(outlines[0].points, items[0].subpaths[0].points);
+        assert_eq!(outlines.len(), 2);
+
+        // Arcs: a half circle of radius 5 from (0, 0) to (10, 0)
+        let arc = subpaths_of_svg_path("M0 0A5 5 0 0 1 10 0");
+        let mid = arc[0].points[arc[0].points.len() / 2];
+        assert!(
+            (mid.0 - 5.0).abs() < 0.5 && (mid.1 + 5.0).abs() < 0.5,
+            "{mid:?}"
+        );
+        assert_eq!(
+            subpaths_of_svg_path("M1.5.5l1e1 0")[0].points,
+            [(1.5, 0.5), (11.5, 0.5)]
+        );
+    }
+
+    #[test]
+    fn test_expr_precedence() {
+        let vars = HashMap::from([("t".to_string(), 2.0)]);
+        let eval = |s: &str| Expr::parse(s).unwrap().eval(&vars);
+        assert_eq!(eval("1 + 2 * 3"), 7.0);
+        assert_eq!(eval("-t^2"), -4.0);
+        assert_eq!(eval("2^3^2"), 512.0);
+        assert_eq!(eval("3t"), 6.0);
+        assert_eq!(eval("2(t + 1)"), 6.0);
+        assert_eq!(eval("t t / 4"), 1.0);
+        assert_eq!(eval("max(t, 5) - min(t, 5)"), 3.0);
+        assert_eq!(eval("1.5e1"), 15.0);
+        assert!((eval("cos(pi)") + 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_expr_errors() {
+        assert!(Expr::parse("cos(t").is_err());
+        assert!(Expr::parse("foo(t)").is_err());
+        assert!(Expr::parse("atan2(t)").is_err());
+        assert!(Expr::parse("t $ 2").is_err());
+        let e = Expr::parse("a * t").unwrap();
+        assert_eq!(e.variables(), vec!["a".to_string(), "t".to_string()]);
+        assert!(e.check_bound(&HashMap::new()).is_err());
+        assert_eq!(
+            parse_param("k = pi/2").unwrap().1,
+            std::f64::consts::FRAC_PI_2
+        );
+        assert!(parse_param("k").is_err());
+    }
+
+    #[test]
+    fn test_equation_curve_polar_circle() {
+        let params = HashMap::from([("a".to_string(), 3.0)]);
+        let curve = EquationCurve::new(
+            CurveForm::Polar {
+                r: Expr::parse("a").unwrap(),
+            },
+            0.0,
+            2.0 * std::f64::consts::PI,
+            params,
+        )
+        .unwrap();
+        let contour = contour_of_function(&curve, 65);
+        assert_eq!(contour.points.len(), 65);
+        for &(x, y) in &contour.points {
+            assert!(((x * x + y * y).sqrt() - 3.0).abs() < 1e-12);
+        }
+        assert!((curve.x(0.25)).abs() < 1e-12);
+        assert!((curve.y(0.25) - 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_equation_curve_rejects_unknown_variable() {
+        let form = CurveForm::Parametric {
+            x: Expr::parse("cos(t)").unwrap(),
+            y: Expr::parse("b sin(t)").unwrap(),
+        };
+        assert!(EquationCurve::new(form, 0.0, 1.0, HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_equation_curve_drops_poles() {
+        // ln(θ) and 1/t are infinite at the start of the range
+        let tau = std::f64::consts::TAU;
+        let forms = [
+            CurveForm::Polar {
+                r: Expr::parse("ln(t)").unwrap(),
+            },
+            CurveForm::Parametric {
+                x: Expr::parse("1/t").unwrap(),
+                y: Expr::parse("t").unwrap(),
+            },
+        ];
+        for form in forms {
+            let curve = EquationCurve::new(form, 0.0, tau, HashMap::new()).unwrap();
+            let contour = curve.sample(100).unwrap();
+            assert_eq!(contour.points.len(), 99);
+            assert!(
+                contour
+                    .points
+                    .iter()
+                    .all(|&(x, y)| x.is_finite() && y.is_finite())
+            );
+            assert!(!fourier_decomposition(&contour, 5).coeffs.is_empty());
+        }
+        let nowhere = CurveForm::Polar {
+            r: Expr::parse("ln(-1 - t)").unwrap(),
+        };
+        let curve = EquationCurve::new(nowhere, 0.0, tau, HashMap::new()).unwrap();
+        assert!(curve.sample(100).is_err());
+    }
+
+    #[test]
+    fn test_shape_polygon_and_star() {
+        let hexagon = Shape::Polygon {
+            sides: 6,
+            radius: 10.0,
+            rotation: 0.0,
+        }
+        .contour(1000);
+        assert_eq!(hexagon.points.len(), 7);
+        assert_eq!(hexagon.points[0], hexagon.points[6]);
+        // First vertex points up (negative y on screen)
+        assert!(hexagon.points[0].0.abs() < 1e-12);
+        assert!((hexagon.points[0].1 + 10.0).abs() < 1e-12);
+
+        let star = Shape::Star {
+            points: 5,
+            outer: 10.0,
+            inner: 4.0,
+            rotation: 0.0,
+        }
+        .contour(1000);
+        assert_eq!(star.points.len(), 11);
+        for (i, &(x, y)) in star.points[..10].iter().enumerate() {
+            let expected = if i % 2 == 0 { 10.0 } else { 4.0 };
+            assert!(((x * x + y * y).sqrt() - expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_shape_curves_are_closed() {
+        let shapes = [
+            Shape::Spirograph {
+                fixed: 5,
+                rolling: 3,
+                pen: 5.0,
+                outside: false,
+                scale: 1.0,
+            },
+            Shape::Lissajous {
+                a: 3,
+                b: 2,
+                delta: 90.0,
+                width: 1.0,
+                height: 1.0,
+            },
+            Shape::Superellipse {
+                a: 1.0,
+                b: 2.0,
+                n: 4.0,
+            },
+            Shape::Gear {
+                teeth: 10,
+                radius: 10.0,
+                depth: 2.0,
+            },
+            Shape::Heart { scale: 1.0 },
+        ];
+        for shape in &shapes {
+            shape.validate().unwrap();
+            let c = shape.contour(200);
+            assert_eq!(c.points.len(), 201, "{}", shape.name());
+            assert_eq!(c.points[0], c.points[200], "{}", shape.name());
+        }
+    }
+
+    #[test]
+    fn test_shape_koch() {
+        let koch = Shape::Koch {
+            iterations: 2,
+            size: 30.0,
+        }
+        .contour(0);
+        assert_eq!(koch.points.len(), 3 * 16 + 1);
+        // Peaks grow outward, so no point falls inside the initial incircle
+        let inradius = 30.0 / (2.0 * 3f64.sqrt());
+        for &(x, y) in &koch.points {
+            assert!((x * x + y * y).sqrt() >= inradius - 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_shape_validate() {
+        let bad = [
+            Shape::Polygon {
+                sides: 2,
+                radius: 1.0,
+                rotation: 0.0,
+            },
+            Shape::Gear {
+                teeth: 10,
+                radius: 5.0,
+                depth: 5.0,
+            },
+            Shape::Superellipse {
+                a: 1.0,
+                b: 1.0,
+                n: 0.0,
+            },
+            Shape::Spirograph {
+                fixed: 5,
+                rolling: 0,
+                pen: 1.0,
+                outside: true,
+                scale: 1.0,
+            },
+        ];
+        for shape in &bad {
+            assert!(shape.validate().is_err(), "{}", shape.name());
+        }
+    }
+
+    #[test]
+    fn test_marching_squares_single_pixel() {
+        let mask = Mask {
+            width: 3,
+            height: 3,
+            bits: vec![false, false, false, false, true, false, false, false, false],
+        };
+        let contours = marching_squares(&mask);
+        assert_eq!(contours.len(), 1);
+        let pts = &contours[0].points;
+        // A diamond through the midpoints between the pixel center (1.5, 1.5)
+        // and its 4 neighbours, closed
+        assert_eq!(pts.len(), 5);
+        assert_eq!(pts[0], pts[4]);
+        for &(x, y) in &pts[..4] {
+            assert_eq!((x - 1.5).abs() + (y - 1.5).abs(), 0.5);
+        }
+    }
+
+    #[test]
+    fn test_remove_collinear_block() {
+        // 4x4 foreground block: its traced boundary has 16 edge midpoints,
+        // straight runs collapse into an octagon with cut corners
+        let mut bits = vec![false; 36];
+        for y in 1..5 {
+            for x in 1..5 {
+                bits[y * 6 + x] = true;
+            }
+        }
+        let mask = Mask {
+            width: 6,
+            height: 6,
+            bits,
+        };
+        let contours = marching_squares(&mask);
+        assert_eq!(contours.len(), 1);
+        assert_eq!(contours[0].points.len(), 17);
+        let simplified = remove_collinear(&contours[0]);
+        let first = simplified.points[0];
+        assert_eq!(simplified.points.last(), Some(&first));
+        assert!(simplified.points.len() <= 10);
+    }
+
+    #[test]
+    fn test_decode_pnm_ascii() {
+        let pgm = b"P2\n# comment\n3 2\n4\n0 4 2\n4 4 0\n";
+        let img = decode_pnm(pgm).unwrap();
+        assert_eq!((img.width, img.height), (3, 2));
+        assert_eq!(img.pixels, vec![0, 255, 127, 255, 255, 0]);
+
+        let pbm = b"P1 2 2 1001";
+        let img = decode_pnm(pbm).unwrap();
+        assert_eq!(img.pixels, vec![0, 255, 255, 0]);
+
+        assert!(decode_pnm(b"P5 2 2 255\n\x00").is_err());
+    }
+
+    #[test]
+    fn test_trace_image_largest_and_all() {
+        // White 10x10 image with a 4x4 and a 2x2 black square
+        let mut pixels = vec![255u8; 100];
+        for y in 1..5 {
+            for x in 1..5 {
+                pixels[y * 10 + x] = 0;
+            }
+        }
+        for y in 7..9 {
+            for x in 7..9 {
+                pixels[y * 10 + x] = 0;
+            }
+        }
+        let image = GrayImage {
+            width: 10,
+            height: 10,
+            pixels,
+        };
+        let mut opts = TraceOptions {
+            min_area: 0.0,
+            ..TraceOptions::default()
+        };
+        let largest = trace_image(&image, &opts);
+        assert_eq!(largest.len(), 1);
+        assert!(largest[0].points.iter().all(|&(x, _)| x < 6.0));
+
+        opts.all = true;
+        assert_eq!(trace_image(&image, &opts).len(), 2);
+
+        opts.min_area = 8.0;
+        assert_eq!(trace_image(&image, &opts).len(), 1);
+
+        opts.invert = true;
+        opts.min_area = 0.0;
+        // Background touches the border, plus two holes
+        assert_eq!(trace_image(&image, &opts).len(), 3);
+    }
+
+    #[test]
+    fn test_close_open_paths() {
+        let a = Contour {
+            points: vec![(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)],
+        };
+        let b = Contour {
+            points: vec![(5.0, 0.0), (5.0, 1.0)],
+        };
+        let retraced = close_open_paths(&[a.clone(), b.clone()], OpenCurve::Retrace);
+        assert_eq!(
+            retraced.points,
+            vec![
+                (0.0, 0.0),
+                (1.0, 0.0),
+                (2.0, 0.0),
+                (1.0, 0.0),
+                (0.0, 0.0),
+                (5.0, 0.0),
+                (5.0, 1.0),
+                (5.0, 0.0)
+            ]
+        );
+        let closed = close_open_paths(&[a], OpenCurve::Close);
+        assert_eq!(
+            closed.points,
+            vec![(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (0.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_skeleton_of_bar_is_one_path() {
+        // 20x5 bar in a 24x9 image thins down to a horizontal line
+        let (w, h) = (24, 9);
+        let mut bits = vec![false; w * h];
+        for y in 2..7 {
+            for x in 2..22 {
+                bits[y * w + x] = true;
+            }
+        }
+        let mask = Mask {
+            width: w,
+            height: h,
+            bits,
+        };
+        let skeleton = skeletonize(&mask);
+        let count = skeleton.bits.iter().filter(|&&b| b).count();
+        assert!(count > 0 && count < 20, "{count} skeleton pixels");
+        let paths = skeleton_paths(&skeleton);
+        assert_eq!(paths.len(), 1);
+        for &(_, y) in &paths[0].points {
+            assert!((y - 4.5).abs() <= 1.0);
+        }
+    }
+
+    #[test]
+    fn test_trace_centerlines_keeps_strokes_apart() {
+        // Two separate vertical strokes, 3 pixels wide
+        let (w, h) = (20, 20);
+        let mut pixels = vec![255u8; w * h];
+        for y in 2..18 {
+            for x in [3, 4, 5, 13, 14, 15] {
+                pixels[y * w + x] = 0;
+            }
+        }
+        let image = GrayImage {
+            width: w,
+            height: h,
+            pixels,
+        };
+        let opts = TraceOptions {
+            all: true,
+            ..TraceOptions::default()
+        };
+        let strokes = trace_centerlines(&image, &opts);
+        assert_eq!(strokes.len(), 2);
+        for s in &strokes {
+            // Simplified down to the two stroke ends
+            assert_eq!(s.points.len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_parse_points_csv() {
+        let csv = CsvOptions::default();
+        let c = parse_points("1,2\n3,4\n", PointsFormat::Csv, &csv).unwrap();
+        assert_eq!(c.points, vec![(1.0, 2.0), (3.0, 4.0)]);
+
+        // Header with x/y columns picked by name, semicolons, comments
+        let text = "# export\nid;y;x\n0;2;1\n1;4;3\n";
+        let c = parse_points(text, PointsFormat::Csv, &csv).unwrap();
+        assert_eq!(c.points, vec![(1.0, 2.0), (3.0, 4.0)]);
+
+        // Explicit columns by name and index, whitespace separated
+        let text = "t lon lat\n0 5 6\n1 7 8\n";
+        let csv = CsvOptions {
+            x_column: Some("LON".to_string()),
+            y_column: Some("2".to_string()),
+        };
+        let c = parse_points(text, PointsFormat::Csv, &csv).unwrap();
+        assert_eq!(c.points, vec![(5.0, 6.0), (7.0, 8.0)]);
+
+        let csv = CsvOptions {
+            x_column: Some("nope".to_string()),
+            y_column: None,
+        };
+        assert!(parse_points(text, PointsFormat::Csv, &csv).is_err());
+        assert!(parse_points("1,2\n3,x\n", PointsFormat::Csv, &CsvOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_parse_points_json_and_yaml() {
+        let csv = CsvOptions::default();
+        for text in [
+            "[[1, 2], [3, 4.5]]",
+            r#"[{"x": 1, "y": 2}, {"x": 3, "y": 4.5}]"#,
+            r#"{"points": [[1, 2], [3, 4.5]]}"#,
+        ] {
+            let c = parse_points(text, PointsFormat::Json, &csv).unwrap();
+            assert_eq!(c.points, vec![(1.0, 2.0), (3.0, 4.5)], "{text}");
+        }
+        assert!(parse_points(r#"[{"x": 1}]"#, PointsFormat::Json, &csv).is_err());
+        assert!(parse_points("[]", PointsFormat::Json, &csv).is_err());
+
+        let c = parse_points("points:\n  - [1, 2]\n", PointsFormat::Yaml, &csv).unwrap();
+        assert_eq!(c.points, vec![(1.0, 2.0)]);
+    }
+
+    #[test]
+    fn test_points_format_detection() {
+        use std::path::Path;
+        assert_eq!(
+            PointsFormat::from_path(Path::new("a/b.CSV")),
+            Some(PointsFormat::Csv)
+        );
+        assert_eq!(
+            PointsFormat::from_path(Path::new("b.yaml")),
+            Some(PointsFormat::Yaml)
+        );
+        assert_eq!(PointsFormat::from_path(Path::new("-")), None);
+        assert_eq!(PointsFormat::sniff("  [[0,0]]"), PointsFormat::Json);
+        assert_eq!(PointsFormat::sniff("points:\n"), PointsFormat::Yaml);
+        assert_eq!(PointsFormat::sniff("x,y\n"), PointsFormat::Csv);
+    }
+
+    const GEOJSON: &str = r#"{"type": "FeatureCollection", "features": [
+        {"type": "Feature", "properties": {"name": "Box", "id": 1},
+         "geometry": {"type": "Polygon", "coordinates": [
+            [[0, 40], [10, 40], [10, 50], [0, 50], [0, 40]],
+            [[2, 42], [3, 42], [3, 43], [2, 42]]]}},
+        {"type": "Feature", "properties": {"name": "Coast", "id": 2},
+         "geometry": {"type": "LineString", "coordinates": [[20, 40], [25, 45]]}},
+        {"type": "Feature", "properties": {"name": "Empty"}, "geometry": null}
+    ]}"#;
+
+    #[test]
+    fn test_geojson_select_and_holes() {
+        let all = parse_geojson(GEOJSON, &GeoOptions::default()).unwrap();
+        assert_eq!(all.len(), 3);
+        assert!(all[0].closed && all[1].closed && !all[2].closed);
+
+        let opts = GeoOptions {
+            select: vec![parse_selector("name=Box").unwrap()],
+            outer_only: true,
+            ..GeoOptions::default()
+        };
+        let parts = parse_geojson(GEOJSON, &opts).unwrap();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].contour.points.len(), 5);
+
+        // Numeric properties match their JSON text
+        let opts = GeoOptions {
+            select: vec![("id".to_string(), "2".to_string())],
+            ..GeoOptions::default()
+        };
+        let parts = parse_geojson(GEOJSON, &opts).unwrap();
+        assert_eq!(parts.len(), 1);
+        assert!(!parts[0].closed);
+
+        assert!(parse_selector("name").is_err());
+        assert!(parse_geojson(r#"{"type": "Circle"}"#, &GeoOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_geojson_projection() {
+        let opts = GeoOptions {
+            select: vec![("name".to_string(), "Box".to_string())],
+            origin: Some((0.0, 60.0)),
+            outer_only: true,
+            ..GeoOptions::default()
+        };
+        let parts = parse_geojson(GEOJSON, &opts).unwrap();
+        // Equirectangular: longitudes shrink by cos(60°) = 0.5, north is up
+        let (x, y) = parts[0].contour.points[1];
+        assert!((x - 5.0).abs() < 1e-9);
+        assert!((y - 20.0).abs() < 1e-9);
+
+        // Mercator stretches latitudes away from the equator
+        let p = Projection::Mercator;
+        let (_, y10) = p.project((0.0, 10.0), (0.0, 0.0));
+        let (_, y60) = p.project((0.0, 60.0), (0.0, 0.0));
+        assert!(y10 < 0.0 && -y10 > 10.0);
+        assert!(-y60 > 6.0 * -y10);
+        assert_eq!(p.project((7.0, 0.0), (2.0, 0.0)), (5.0, -0.0));
+    }
+
+    #[test]
+    fn test_font_spec() {
+        assert_eq!(
+            parse_variation("wght=700").unwrap(),
+            ("wght".to_string(), 700.0)
+        );
+        assert_eq!(
+            parse_variation(" wdth = 87.5 ").unwrap(),
+            ("wdth".to_string(), 87.5)
+        );
+        assert!(parse_variation("wght").is_err());
+        assert!(parse_variation("weight=700").is_err());
+        assert!(parse_variation("wght=bold").is_err());
+
+        let font = FontSpec {
+            source: FontSource::File {
+                path: "does-not-exist.ttf".into(),
+                index: 0,
+            },
+            variations: vec![],
+        };
+        assert!(svg_path_of_text("A", &font, &TextLayout::default()).is_err());
+    }
+
+    /// A TrueType font with 1000 units per em and three glyphs: a 500 × 700
+    /// square `A` and a triangle `V`, both advancing 600, and a 300 wide
+    /// space. The kern table moves `V` 100 units closer after `A`.
+    fn synthetic_font() -> Vec<u8> {
+        fn be(out: &mut Vec<u8>, values: &[i32], width: usize) {
+            for v in values {
+                out.extend_from_slice(&v.to_be_bytes()[4 - width..]);
+            }
+        }
+        // Simple glyph of one polygon with on-curve points
+        fn glyph(points: &[(i32, i32)]) -> Vec<u8> {
+            let xs = points.iter().map(|p| p.0);
+            let ys = points.iter().map(|p| p.1);
+            let bounds = [
+                xs.clone().min().unwrap(),
+                ys.clone().min().unwrap(),
+                xs.max().unwrap(),
+                ys.max().unwrap(),
+            ];
+            let mut out = vec![];
+            be(&mut out, &[1], 2);
+            be(&mut out, &bounds, 2);
+            be(&mut out, &[points.len() as i32 - 1, 0], 2);
+            out.extend(points.iter().map(|_| 0x01));
+            let mut last = (0, 0);
+            for axis in 0..2 {
+                for p in points {
+                    let (v, l) = if axis == 0 {
+                        (p.0, last.0)
+                    } else {
+                        (p.1, last.1)
+                    };
+                    be(&mut out, &[v - l], 2);
+                    if axis == 0 {
+                        last.0 = p.0;
+                    } else {
+                        last.1 = p.1;
+                    }
+                }
+            }
+            if out.len() % 2 == 1 {
+                out.push(0);
+            }
+            out
+        }
+
+        let glyphs = [
+            vec![],
+            glyph(&[(0, 0), (0, 700), (500, 700), (500, 0)]),
+            glyph(&[(0, 700), (300, 0), (600, 700)]),
+            vec![],
+        ];
+        let mut glyf = vec![];
+        let mut loca = vec![];
+        for g in &glyphs {
+            be(&mut loca, &[glyf.len() as i32 / 2], 2);
+            glyf.extend(g);
+        }
+        be(&mut loca, &[glyf.len() as i32 / 2], 2);
+
+        let mut head = vec![];
+        be(&mut head, &[0x10000, 0x10000, 0, 0x5F0F3CF5], 4);
+        be(&mut head, &[0, 1000], 2);
+        head.extend([0; 16]);
+        be(&mut head, &[0, 0, 600, 700, 0, 8, 2, 0, 0], 2);
+        let mut hhea = vec![];
+        be(&mut hhea, &[0x10000], 4);
+        be(
+            &mut hhea,
+            &[800, -200, 0, 600, 0, 0, 600, 1, 0, 0, 0, 0, 0, 0, 0, 4],
+            2,
+        );
+        let mut maxp = vec![];
+        be(&mut maxp, &[0x5000], 4);
+        be(&mut maxp, &[4], 2);
+        let mut hmtx = vec![];
+        be(&mut hmtx, &[0, 0, 600, 0, 600, 0, 300, 0], 2);
+        let mut cmap = vec![];
+        be(&mut cmap, &[0, 1, 3, 10], 2);
+        be(&mut cmap, &[12], 4);
+        be(&mut cmap, &[12, 0], 2);
+        be(&mut cmap, &[16 + 3 * 12, 0, 3], 4);
+        be(
+            &mut cmap,
+            &[' ' as i32, ' ' as i32, 3, 'A' as i32, 'A' as i32, 1],
+            4,
+        );
+        be(&mut cmap, &['V' as i32, 'V' as i32, 2], 4);
+        let mut kern = vec![];
+        be(&mut kern, &[0, 1, 0, 20, 1, 1, 6, 0, 0, 1, 2, -100], 2);
+
+        let tables: [(&[u8; 4], Vec<u8>); 8] = [
+            (b"cmap", cmap),
+            (b"glyf", glyf),
+            (b"head", head),
+            (b"hhea", hhea),
+            (b"hmtx", hmtx),
+            (b"kern", kern),
+            (b"loca", loca),
+            (b"maxp", maxp),
+        ];
+        let mut font = vec![];
+        be(&mut font, &[0x10000], 4);
+        be(&mut font, &[tables.len() as i32, 128, 3, 0], 2);
+        let mut offset = 12 + 16 * tables.len();
+        let mut data = vec![];
+        for (tag, table) in &tables {
+            font.extend_from_slice(*tag);
+            be(&mut font, &[0, offset as i32, table.len() as i32], 4);
+            data.extend(table);
+            data.resize(data.len().next_multiple_of(4), 0);
+            offset = 12 + 16 * tables.len() + data.len();
+        }
+        font.extend(data);
+        font
+    }
+
+    #[test]
+    fn test_text_layout() {
+        let path = std::env::temp_dir().join("circles-sketch-test-layout.ttf");
+        std::fs::write(&path, synthetic_font()).unwrap();
+        let font = FontSpec {
+            source: FontSource::File { path, index: 0 },
+            variations: vec![],
+        };
+        let origins = |text: &str, layout: &TextLayout| -> Vec<(f64, f64)> {
+            let outline = svg_path_of_text(text, &font, layout).unwrap();
+            outline.glyphs.iter().map(|g| g.origin).collect()
+        };
+
+        // Kerning pulls V in; A is not kerned before A
+        let layout = TextLayout::default();
+        assert_eq!(origins("AV", &layout), vec![(0.0, 0.0), (500.0, 0.0)]);
+        assert_eq!(origins("AA", &layout), vec![(0.0, 0.0), (600.0, 0.0)]);
+        let outline = svg_path_of_text("A", &font, &layout).unwrap();
+        // Outlines are in screen coordinates: the top of A is at y = -700
+        assert!(outline.path.contains("L 0 -700"));
+        assert_eq!(outline.glyphs[0].paths.len(), 1);
+
+        let spaced = TextLayout {
+            letter_spacing: 0.1,
+            ..TextLayout::default()
+        };
+        assert_eq!(
+            origins("A A", &spaced),
+            vec![(0.0, 0.0), (700.0, 0.0), (1100.0, 0.0)]
+        );
+
+        // Line two is twice as wide as line one
+        let lines = |align| {
+            let layout = TextLayout {
+                line_height: 1.5,
+                align,
+                letter_spacing: 0.0,
+            };
+            origins("A\nAA", &layout)
+        };
+        assert_eq!(
+            lines(Align::Left),
+            vec![(0.0, 0.0), (0.0, 1500.0), (600.0, 1500.0)]
+        );
+        assert_eq!(lines(Align::Center)[0], (300.0, 0.0));
+        assert_eq!(lines(Align::Right)[0], (600.0, 0.0));
+        assert_eq!(lines(Align::Right)[1], (0.0, 1500.0));
+
+        let outline = svg_path_of_text("A?\u{e9}?V", &font, &layout).unwrap();
+        assert_eq!(outline.missing, vec!['?', '\u{e9}']);
+        assert_eq!(outline.glyphs.len(), 2);
+    }
+
+    #[test]
+    fn test_stroke_font() {
+        let font = StrokeFont::builtin();
+        assert!((' '..='~').all(|c| font.glyphs.contains_key(&c)));
+
+        let layout = TextLayout::default();
+        let text = stroke_text("Hi\u{e9}", &font, &layout);
+        // H has 3 strokes, i has 2 (stem and dot)
+        assert_eq!(text.strokes.len(), 5);
+        assert_eq!(text.missing, vec!['\u{e9}']);
+        // Screen coordinates: the top of H is above the baseline
+        assert_eq!(text.strokes[0].points[1], (0.0, -8.0));
+
+        let centered = TextLayout {
+            align: Align::Center,
+            ..TextLayout::default()
+        };
+        let text = stroke_text("i\nii", &font, &centered);
+        let first_stem = text.strokes[0].points[0];
+        assert!((first_stem.0 - 0.5 - 2.8 / 2.0).abs() < 1e-9);
+        assert!((text.strokes[2].points[0].1 - 0.0).abs() > 1.0);
+    }
+
+    #[test]
+    fn test_jhf_font() {
+        let jhf = "12345  1JZ\n  501  9I[RFJ[ RRFZ[ RMTWT\n";
+        let font = StrokeFont::from_jhf(jhf).unwrap();
+        assert_eq!(font.glyphs[&' '].advance, 16.0);
+        // Glyphs map to characters in file order: this "A" outline lands on '!'
+        let a = &font.glyphs[&'!'];
+        assert_eq!(a.advance, 18.0);
+        assert_eq!(a.strokes.len(), 3);
+        // x relative to the left bound, baseline at y = 0
+        assert_eq!(a.strokes[0], vec![(9.0, -21.0), (1.0, 0.0)]);
+
+        assert!(StrokeFont::from_jhf("").is_err());
+        assert!(StrokeFont::from_jhf("  501  9I[RFJ").is_err());
+        // A count of 0, with or without data after the header
+        assert!(StrokeFont::from_jhf("  501  0").is_err());
+        assert!(StrokeFont::from_jhf("  501  0I[").is_err());
+    }
+
+    #[test]
+    fn test_text_chains() {
+        let (words, next) = word_indices("ab  cd", 3);
+        assert_eq!(words, vec![3, 3, 3, 3, 4, 4]);
+        assert_eq!(next, 5);
+        assert_eq!(word_indices("  ", 2).1, 2);
+
+        let font = StrokeFont::builtin();
+        let text = stroke_text("ab c\nd", &font, &TextLayout::default());
+        let words: Vec<usize> = text.glyphs.iter().map(|g| g.word).collect();
+        // The space glyph belongs to the preceding word
+        assert_eq!(words, vec![0, 0, 0, 1, 2]);
+
+        let glyphs = group_glyphs(&text.glyphs, TextChains::Glyph);
+        // The space has no strokes and gets no chain
+        assert_eq!(glyphs.len(), 4);
+        assert_eq!(glyphs[1].1, (6.8, 0.0));
+        let words = group_glyphs(&text.glyphs, TextChains::Word);
+        assert_eq!(words.len(), 3);
+        // "ab": b's strokes are moved relative to a's origin
+        assert_eq!(words[0].0.len(), 4);
+        assert_eq!(words[0].0[2].points[0], (6.8, -8.0));
+        let single = group_glyphs(&text.glyphs, TextChains::Single);
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].0.len(), text.strokes.len());
+    }
+
+    #[test]
+    fn test_simplify() {
+        // 10×10 square sampled every unit, with a 0.3 bump on the top edge
+        let mut points = Vec::new();
+        for i in 0..10 {
+            points.push((i as f64, if i == 5 { -0.3 } else { 0.0 }));
+        }
+        for i in 0..10 {
+            points.push((10.0, i as f64));
+        }
+        for i in 0..10 {
+            points.push((10.0 - i as f64, 10.0));
+        }
+        for i in 0..10 {
+            points.push((0.0, 10.0 - i as f64));
+        }
+        let square = Contour { points };
+        let corners = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+
+        assert_eq!(simplify_douglas_peucker(&square, 0.5).points, corners);
+        let kept = simplify_douglas_peucker(&square, 0.25).points;
+        assert_eq!(kept.len(), 5);
+        assert!(kept.contains(&(5.0, -0.3)));
+
+        assert_eq!(simplify_visvalingam(&square, 2.0).points, corners);
+        let kept = simplify_visvalingam(&square, 0.1).points;
+        assert!(kept.contains(&(5.0, -0.3)));
+        assert!(kept.len() < 10);
+        // Collinear points go first, but a triangle is always kept
+        let line = Contour {
+            points: vec![(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0)],
+        };
+        assert_eq!(simplify_visvalingam(&line, 1.0).points.len(), 3);
+
+        // Retraced strokes keep their tips and ends, and each stroke is
+        // simplified to the same line both ways
+        let strokes = [
+            Contour {
+                points: vec![(0.0, 0.0), (5.0, 0.1), (10.0, 0.0)],
+            },
+            Contour {
+                points: vec![(0.0, 20.0), (5.0, 20.1), (10.0, 20.0)],
+            },
+        ];
+        let retraced = close_open_paths(&strokes, OpenCurve::Retrace);
+        let expected = vec![
+            (0.0, 0.0),
+            (10.0, 0.0),
+            (0.0, 0.0),
+            (0.0, 20.0),
+            (10.0, 20.0),
+            (0.0, 20.0),
+        ];
+        assert_eq!(simplify_visvalingam(&retraced, 1.0).points, expected);
+        assert_eq!(simplify_douglas_peucker(&retraced, 0.5).points, expected);
+        assert_eq!(simplify_visvalingam(&retraced, 0.1).points, retraced.points);
+
+        // Steps are read from the config and validated
+        let yaml = serde_yaml::to_string(&EmbedOptions::default()).unwrap()
+            + "preprocess:\n- !DouglasPeucker\n  tolerance: 0.5\n- !Visvalingam\n  tolerance: 2.0\n";
+        let yaml = yaml.replace("preprocess: []\n", "");
+        let opts: EmbedOptions = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(opts.preprocess.len(), 2);
+        assert!(matches!(
+            opts.preprocess[0],
+            Preprocess::DouglasPeucker { tolerance } if tolerance == 0.5
+        ));
+        assert_eq!(opts.preprocess[1].apply(&square).unwrap().points, corners);
+        let opts = EmbedOptions {
+            preprocess: vec![Preprocess::Visvalingam { tolerance: -1.0 }],
+            ..EmbedOptions::default()
+        };
+        assert!(opts.validate().is_err());
+    }
+
+    #[test]
+    fn test_smooth() {
+        let close = |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).abs() + (a.1 - b.1).abs() < 1e-9;
+        let zigzag = Contour {
+            points: (0..20)
+                .map(|i| (i as f64, if i % 2 == 0 { 1.0 } else { -1.0 }))
+                .collect(),
+        };
+        let smooth = smooth_moving_average(&zigzag, 3);
+        assert_eq!(smooth.points.len(), 20);
+        assert!(close(smooth.points[4], (4.0, -1.0 / 3.0)));
+        // The jump back to the start splits the loop, and the ends stay
+        assert_eq!(smooth.points[0], zigzag.points[0]);
+        assert_eq!(smooth.points[19], zigzag.points[19]);
+
+        let gauss = smooth_gaussian(&zigzag, 2.0);
+        assert!(gauss.points[10].1.abs() < 0.1);
+        assert!(close(gauss.points[10], (10.0, gauss.points[10].1)));
+
+        // Subpaths are smoothed on their own
+        let mut two = zigzag.clone();
+        two.points
+            .extend(zigzag.points.iter().map(|&(x, y)| (x, y + 100.0)));
+        let gauss = smooth_gaussian(&two, 2.0);
+        for (i, &(_, y)) in gauss.points.iter().enumerate() {
+            let center = if i < 20 { 0.0 } else { 100.0 };
+            assert!((y - center).abs() <= 1.0, "{i}: {y}");
+        }
+        assert_eq!(gauss.points[20], two.points[20]);
+
+        // A circle with alternating gaps of 1.4 and 0.6 steps: weights
+        // follow the arc length, so points do not slide towards the closer
+        // neighbour
+        let uneven = Contour {
+            points: (0..40)
+                .map(|j| {
+                    let t = std::f64::consts::TAU * (j as f64 + 0.4 * (j % 2) as f64) / 40.0;
+                    (100.0 * t.cos(), 100.0 * t.sin())
+                })
+                .collect(),
+        };
+        let smooth = smooth_moving_average(&uneven, 3);
+        for (a, b) in uneven.points.iter().zip(&smooth.points) {
+            let turn = (a.0 * b.1 - a.1 * b.0).atan2(a.0 * b.0 + a.1 * b.1);
+            assert!(turn.abs() < 1e-3, "{turn}");
+        }
+
+        // Savitzky–Golay keeps the radius of an evenly sampled circle much
+        // better than a moving average of the same window
+        let circle = Contour {
+            points: (0..40)
+                .map(|j| {
+                    let t = std::f64::consts::TAU * j as f64 / 40.0;
+                    (100.0 * t.cos(), 100.0 * t.sin())
+                })
+                .collect(),
+        };
+        let radius_error = |c: &Contour| {
+            c.points
+                .iter()
+                .map(|&(x, y)| (x.hypot(y) - 100.0).abs())
+                .fold(0.0, f64::max)
+        };
+        let sg = radius_error(&smooth_savitzky_golay(&circle, 5, 2));
+        let average = radius_error(&smooth_moving_average(&circle, 5));
+        assert!(average > 2.0 && sg < average / 20.0, "{sg} {average}");
+
+        // A circle with a frequency-20 wiggle: the low-pass keeps the circle
+        let n = 200;
+        let wiggly = Contour {
+            points: (0..n)
+                .map(|j| {
+                    let t = std::f64::consts::TAU * j as f64 / n as f64;
+                    (
+                        100.0 * t.cos() + 5.0 * (20.0 * t).cos(),
+                        100.0 * t.sin() + 5.0 * (20.0 * t).sin(),
+                    )
+                })
+                .collect(),
+        };
+        let low = smooth_low_pass(&wiggly, 5);
+        assert_eq!(low.points.len(), n);
+        for &(x, y) in &low.points {
+            assert!((x.hypot(y) - 100.0).abs() < 1e-6);
+        }
+
+        let yaml = serde_yaml::to_string(&EmbedOptions::default())
+            .unwrap()
+            .replace(
+                "preprocess: []\n",
+                "preprocess:\n- !SavitzkyGolay\n  window: 5\n  order: 2\n- !LowPass\n  cutoff: 5\n",
+            );
+        let opts: EmbedOptions = serde_yaml::from_str(&yaml).unwrap();
+        assert!(opts.validate().is_ok());
+        let out = opts
+            .preprocess
+            .iter()
+            .fold(wiggly.clone(), |c, step| step.apply(&c).unwrap());
+        // Savitzky–Golay follows the wiggle's arc length, which nearly
+        // stops at its cusps, so the circle is only approximately recovered
+        assert!((out.points[0].0 - 100.0).abs() < 0.5, "{:?}", out.points[0]);
+        for step in [
+            Preprocess::MovingAverage { window: 4 },
+            Preprocess::SavitzkyGolay {
+                window: 5,
+                order: 5,
+            },
+            Preprocess::Gaussian { sigma: 0.0 },
+            Preprocess::LowPass { cutoff: 0 },
+        ] {
+            assert!(step.validate().is_err(), "{}", step.name());
+        }
+    }
+
+    #[test]
+    fn test_contour_geometry() {
+        let square = Contour {
+            points: vec![(0.0, 0.0), (4.0, 0.0), (4.0, 2.0), (0.0, 2.0)],
+        };
+        assert_eq!(square.signed_area(), 8.0);
+        // Clockwise on the page, where y points down
+        assert_eq!(square.orientation(), Some(Orientation::Clockwise));
+        assert_eq!(square.centroid(), Some((2.0, 1.0)));
+        assert_eq!(square.perimeter(), 12.0);
+        let bbox = square.bounding_box().unwrap();
+        assert_eq!(
+            bbox,
+            BoundingBox {
+                min: (0.0, 0.0),
+                max: (4.0, 2.0)
+            }
+        );
+        assert_eq!(
+            (bbox.width(), bbox.height(), bbox.center()),
+            (4.0, 2.0, (2.0, 1.0))
+        );
+        assert!(square.self_intersections().is_empty());
+
+        let reversed = Contour {
+            points: square.points.iter().rev().copied().collect(),
+        };
+        assert_eq!(reversed.signed_area(), -8.0);
+        assert_eq!(reversed.orientation(), Some(Orientation::CounterClockwise));
+
+        // Figure of eight: balanced lobes, one crossing
+        let eight = Contour {
+            points: vec![(0.0, 0.0), (2.0, 2.0), (2.0, 0.0), (0.0, 2.0)],
+        };
+        assert_eq!(eight.orientation(), None);
+        assert_eq!(
+            eight.self_intersections(),
+            vec![Intersection {
+                segments: (0, 2),
+                point: (1.0, 1.0)
+            }]
+        );
+        // A retraced stroke encloses nothing; its centroid is the mean point
+        let stroke = Contour {
+            points: vec![(0.0, 0.0), (3.0, 0.0), (6.0, 0.0), (3.0, 0.0)],
+        };
+        assert_eq!(stroke.orientation(), None);
+        assert_eq!(stroke.centroid(), Some((3.0, 0.0)));
+        assert!(stroke.self_intersections().is_empty());
+
+        let empty = Contour { points: vec![] };
+        assert!(empty.bounding_box().is_none());
+        assert!(empty.centroid().is_none());
+        assert_eq!(empty.perimeter(), 0.0);
+    }
+
+    #[test]
+    fn test_orient_and_start() {
+        let square = Contour {
+            points: vec![(0.0, 0.0), (4.0, 0.0), (4.0, 2.0), (0.0, 2.0)],
+        };
+        let ccw = square.oriented(Orientation::CounterClockwise);
+        assert_eq!(
+            ccw.points,
+            vec![(0.0, 0.0), (0.0, 2.0), (4.0, 2.0), (4.0, 0.0)]
+        );
+        assert_eq!(
+            square.oriented(Orientation::Clockwise).points,
+            square.points
+        );
+        // An explicitly closed loop stays closed
+        let mut closed = square.clone();
+        closed.points.push((0.0, 0.0));
+        assert_eq!(
+            closed.oriented(Orientation::CounterClockwise).points,
+            vec![(0.0, 0.0), (0.0, 2.0), (4.0, 2.0), (4.0, 0.0), (0.0, 0.0)]
+        );
+        assert_eq!(
+            closed.with_start(StartAnchor::Index(2)).points,
+            vec![(4.0, 2.0), (0.0, 2.0), (0.0, 0.0), (4.0, 0.0), (4.0, 2.0)]
+        );
+
+        // y points down: the top is the smallest y
+        let diamond = Contour {
+            points: vec![(1.0, 0.0), (0.0, 1.0), (-1.0, 0.0), (0.0, -1.0)],
+        };
+        assert_eq!(diamond.anchor_index(StartAnchor::Top), Some(3));
+        assert_eq!(diamond.anchor_index(StartAnchor::Left), Some(2));
+        assert_eq!(diamond.anchor_index(StartAnchor::Angle(90.0)), Some(3));
+        assert_eq!(diamond.anchor_index(StartAnchor::Angle(-100.0)), Some(1));
+        assert_eq!(diamond.anchor_index(StartAnchor::Angle(190.0)), Some(2));
+        assert_eq!(diamond.anchor_index(StartAnchor::Index(5)), Some(1));
+        assert_eq!(
+            diamond.with_start(StartAnchor::Top).points,
+            vec![(0.0, -1.0), (1.0, 0.0), (0.0, 1.0), (-1.0, 0.0)]
+        );
+        assert_eq!(
+            Contour { points: vec![] }.anchor_index(StartAnchor::Top),
+            None
+        );
+
+        let yaml = "- !Orient {direction: CounterClockwise}\n- !Start {at: !Angle 90}\n";
+        let steps: Vec<Preprocess> = serde_yaml::from_str(yaml).unwrap();
+        let out = steps
+            .iter()
+            .fold(square.clone(), |c, step| step.apply(&c).unwrap());
+        assert_eq!(out.orientation(), Some(Orientation::CounterClockwise));
+        assert_eq!(out.points[0], (0.0, 0.0));
+
+        // With flip_y, y-up input is flipped onto the page first: the
+        // square, counter-clockwise with y up, stays counter-clockwise as
+        // drawn, and its top is the point with the largest input y
+        let opts = EmbedOptions {
+            flip_y: true,
+            preprocess: vec![
+                Preprocess::Orient {
+                    direction: Orientation::Clockwise,
+                },
+                Preprocess::Start {
+                    at: StartAnchor::Top,
+                },
+            ],
+            ..EmbedOptions::default()
+        };
+        let flipped = Contour {
+            points: square.points.iter().map(|&p| opts.page_point(p)).collect(),
+        };
+        assert_eq!(flipped.orientation(), Some(Orientation::CounterClockwise));
+        let out = opts
+            .preprocess
+            .iter()
+            .fold(flipped, |c, step| step.apply(&c).unwrap());
+        assert_eq!(out.orientation(), Some(Orientation::Clockwise));
+        assert_eq!(out.points[0], (0.0, -2.0));
+        let bad = Preprocess::Start {
+            at: StartAnchor::Angle(f64::NAN),
+        };
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn test_complex_map() {
+        let near = |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).hypot(a.1 - b.1) < 1e-9;
+        let eval = |s: &str| {
+            let w = Expr::parse(s).unwrap().eval_complex(&HashMap::new());
+            (w.re, w.im)
+        };
+        assert_eq!(eval("i^2"), (-1.0, 0.0));
+        assert_eq!(eval("(1+2i)(3-i)"), (5.0, 5.0));
+        assert!(near(eval("exp(i pi)"), (-1.0, 0.0)));
+        assert!(near(eval("sqrt(-4)"), (0.0, 2.0)));
+        assert!(near(eval("sin(asin(0.3+0.2i))"), (0.3, 0.2)));
+        assert_eq!(eval("conj(3+4i) + abs(3+4i)"), (8.0, -4.0));
+        assert_eq!(Complex::ZERO.pow(Complex::real(2.0)), Complex::ZERO);
+        // The real evaluation still works, with the new functions
+        let vars = HashMap::new();
+        assert_eq!(Expr::parse("re(2) + im(2)").unwrap().eval(&vars), 2.0);
+
+        let params = HashMap::from([("a".to_string(), 1.0)]);
+        let square = ComplexMap::new("z^2 + a", params).unwrap();
+        assert_eq!(square.map((1.0, 1.0)), (1.0, 2.0));
+        assert!(ComplexMap::new("z + b", HashMap::new()).is_err());
+        assert!(ComplexMap::new("z +", HashMap::new()).is_err());
+        // z and i cannot be rebound
+        for name in ["z", "i"] {
+            let params = HashMap::from([(name.to_string(), 1.0)]);
+            assert!(ComplexMap::new("i z", params).is_err());
+        }
+
+        // Inversion in the unit circle sends the circle of radius 2 to the
+        // one of radius 1/2
+        let inversion = ComplexMap::new("1/conj(z)", HashMap::new()).unwrap();
+        let circle = contour_of_function(
+            &EquationCurve::new(
+                CurveForm::Polar {
+                    r: Expr::parse("2").unwrap(),
+                },
+                0.0,
+                std::f64::consts::TAU,
+                HashMap::new(),
+            )
+            .unwrap(),
+            1000,
+        );
+        let image = inversion.apply(&circle);
+        assert!(image.points.len() >= 1000);
+        assert!(
+            image
+                .points
+                .iter()
+                .all(|&(x, y)| (x.hypot(y) - 0.5).abs() < 1e-9)
+        );
+        // Poles are dropped
+        let through_origin = Contour {
+            points: vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)],
+        };
+        let image = inversion.apply(&through_origin);
+        assert_eq!(image.points.len(), 999);
+        assert!(
+            image
+                .points
+                .iter()
+                .all(|&(x, y)| x.is_finite() && y.is_finite())
+        );
+
+        // As a ContourFunction: multiplying by i turns a quarter turn
+        let segment = Contour {
+            points: vec![(1.0, 0.0), (2.0, 0.0)],
+        };
+        let rotate = ComplexMap::new("i z", HashMap::new()).unwrap();
+        let f = f_of_contour(&segment).map_complex(rotate);
+        assert!(near((f.x(1.0), f.y(1.0)), (0.0, 2.0)));
+        assert!(near(f.point(1.0), (0.0, 2.0)));
+
+        let step: Preprocess =
+            serde_yaml::from_str("!Map {expr: 'z^2/r', params: {r: 10}}").unwrap();
+        assert!(step.validate().is_ok());
+        let bad: Preprocess = serde_yaml::from_str("!Map {expr: 'z^2/r'}").unwrap();
+        assert!(bad.validate().is_err());
+        assert!(bad.apply(&segment).is_err());
+
+        // Centered, a square far from the origin is rotated about its center
+        let square = Contour {
+            points: vec![(10.0, 10.0), (12.0, 10.0), (12.0, 12.0), (10.0, 12.0)],
+        };
+        let step: Preprocess = serde_yaml::from_str("!Map {expr: 'i z', center: true}").unwrap();
+        let image = step.apply(&square).unwrap();
+        assert!(near(image.centroid().unwrap(), (0.0, 0.0)));
+        assert!(near(image.points[0], (1.0, -1.0)));
+        let mut pair = [square.clone(), square.clone()];
+        pair[1].points.iter_mut().for_each(|p| p.0 += 10.0);
+        let [a, b] = &mut pair;
+        step.apply_each(&mut [a, b]).unwrap();
+        assert!(near(pair[0].points[0], (1.0, -6.0)));
+        assert!(near(pair[1].points[0], (1.0, 4.0)));
+    }
+
+    /// Irregular pentagon and its decomposition with 2 frequencies.
+    fn pentagon() -> (Contour, FourierDecomposition) {
+        let contour = Contour {
+            points: vec![(0.0, 0.0), (3.0, 1.0), (4.0, 4.0), (1.0, 5.0), (-1.0, 2.0)],
+        };
+        let fourier = fourier_decomposition(&contour, 2);
+        (contour, fourier)
+    }
+
+    #[test]
+    fn test_axis_series() {
+        let (contour, fourier) = pentagon();
+        let axes = fourier.axis_series();
+        for i in 0..20 {
+            let t = i as f64 / 20.0;
+            let (a, b) = (fourier.eval(t), axes.eval(t));
+            assert!((a.0 - b.0).hypot(a.1 - b.1) < 1e-9);
+        }
+        assert!(axes.x.windows(2).all(|w| w[0].amp >= w[1].amp));
+        assert!(axes.y.iter().all(|c| c.amp > 0.0 && c.freq > 0));
+
+        let path = svg_path_of_contour(&contour);
+        let mut opts = EmbedOptions::default();
+        let html =
+            embed_html_of_svg_path_with_fourier(&path, &contour.points, Some(&fourier), &opts);
+        assert!(!html.contains("axis:{"));
+        opts.visualization = Visualization::TwoAxis;
+        let html =
+            embed_html_of_svg_path_with_fourier(&path, &contour.points, Some(&fourier), &opts);
+        assert!(html.contains("axis:{"));
+    }
+
+    #[test]
+    fn test_ellipses() {
+        // Sparse terms: frequency 2 is missing and 3 has no -3 partner, so
+        // its ellipse is a circle
+        let term = |freq, re, im| ComplexCoeff { freq, re, im };
+        let fourier = FourierDecomposition {
+            coeffs: vec![
+                term(3, 2.0, 1.0),
+                term(1, 1.0, 0.5),
+                term(-1, 0.5, -0.25),
+                term(0, 4.0, 3.0),
+            ],
+        };
+        let series = fourier.ellipses();
+        assert_eq!(series.center, (4.0, 3.0));
+        let freqs: Vec<u32> = series.ellipses.iter().map(|e| e.freq).collect();
+        assert_eq!(freqs, vec![3, 1]);
+        let circle = &series.ellipses[0];
+        assert!((circle.semi_major - circle.semi_minor).abs() < 1e-12);
+        for i in 0..20 {
+            let t = i as f64 / 20.0;
+            let (a, b) = (fourier.eval(t), series.eval(t));
+            assert!((a.0 - b.0).hypot(a.1 - b.1) < 1e-9);
+        }
+
+        // Traversed with decreasing angle, the square through the unit axis
+        // points is a circle whose semi-minor axis is negative
+        let square = Contour {
+            points: vec![(1.0, 0.0), (0.0, -1.0), (-1.0, 0.0), (0.0, 1.0)],
+        };
+        let series = fourier_decomposition(&square, 1).ellipses();
+        let e = &series.ellipses[0];
+        assert!((e.semi_major - 1.0).abs() < 1e-9 && (e.semi_minor + 1.0).abs() < 1e-9);
+
+        let (contour, fourier) = pentagon();
+        let path = svg_path_of_contour(&contour);
+        let opts = EmbedOptions {
+            visualization: Visualization::Ellipses,
+            ..EmbedOptions::default()
+        };
+        let html =
+            embed_html_of_svg_path_with_fourier(&path, &contour.points, Some(&fourier), &opts);
+        assert!(html.contains("ellipse:{") && !html.contains("axis:{"));
+    }
+
+    #[test]
+    fn test_static_svg() {
+        let contour = interpolate(
+            &Contour {
+                points: vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)],
+            },
+            400,
+        );
+        let fourier = fourier_decomposition(&contour, 50);
+        assert_eq!(fourier.truncated(3).coeffs.len(), 3);
+        let chains = [Chain {
+            contour,
+            fourier,
+            offset: (5.0, 0.0),
+            style: ChainStyle::default(),
+        }];
+        let opts = EmbedOptions::default();
+        let mut svg_opts = StaticSvgOptions {
+            harmonics: vec![1, 5, 40],
+            layout: SvgLayout::Layers,
+            columns: None,
+            circles_at: None,
+            size: 300.0,
+            background: "none".into(),
+        };
+        let svg = static_svg(&chains, &opts, &svg_opts);
+        assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("inkscape:groupmode=\"layer\"").count(), 4);
+        assert!(svg.contains("inkscape:label=\"N = 40\""));
+        assert!(!svg.contains("<rect") && !svg.contains("<circle"));
+        // The contour is placed at its offset
+        assert!(svg.contains("M 5 0"));
+
+        svg_opts.layout = SvgLayout::Grid;
+        svg_opts.circles_at = Some(0.25);
+        let svg = static_svg(&chains, &opts, &svg_opts);
+        assert!(svg.contains("width=\"600\" height=\"600\""));
+        assert_eq!(svg.matches("<svg id=\"harmonics-").count(), 3);
+        assert!(svg.contains(">N = 5</text>"));
+        // One dashed circle per term and the pen
+        assert_eq!(svg.matches("stroke-dasharray").count(), 1 + 5 + 40);
+
+        // Colors are escaped, and traces are rounded to a ten-thousandth of
+        // the view (about 12 units wide here)
+        svg_opts.background = "#000\" onload=\"alert(1)".into();
+        let opts = EmbedOptions {
+            trace_colors: vec!["<red>".into()],
+            ..EmbedOptions::default()
+        };
+        let svg = static_svg(&chains, &opts, &svg_opts);
+        assert!(svg.contains(r##"fill="#000&quot; onload=&quot;alert(1)""##));
+        assert!(svg.contains(r#"stroke="&lt;red&gt;""#));
+        let trace = svg
+            .split(r#"" fill="none" stroke="&lt;red&gt;""#)
+            .next()
+            .unwrap();
+        let d = &trace[trace.rfind("d=\"").unwrap() + 3..];
+        assert!(d.starts_with('M') && d.ends_with('Z'));
+        let decimals = |n: &str| n.split_once('.').map_or(0, |(_, f)| f.len());
+        assert!(d.split([' ', 'M', 'L']).all(|n| decimals(n) <= 3));
+    }
+
+    #[test]
+    fn test_animated_svg() {
+        let contour = interpolate(
+            &Contour {
+                points: vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)],
+            },
+            400,
+        );
+        let chains: Vec<Chain> = [(0.0, 0.0), (20.0, 0.0)]
+            .into_iter()
+            .map(|offset| Chain {
+                contour: contour.clone(),
+                fourier: fourier_decomposition(&contour, 50),
+                offset,
+                style: ChainStyle::default(),
+            })
+            .collect();
+        let opts = EmbedOptions::default();
+        let svg_opts = AnimatedSvgOptions {
+            harmonics: 7,
+            duration: 2.5,
+            size: 400.0,
+            background: "black".into(),
+        };
+        let svg = animated_svg(&chains, ChainTiming::Sequential, &opts, &svg_opts);
+        assert!(!svg.contains("<script"));
+        assert_eq!(svg.matches("<g").count(), svg.matches("</g>").count());
+        assert_eq!(svg.matches("type=\"rotate\"").count(), 2 * 7);
+        assert_eq!(
+            svg.matches("attributeName=\"stroke-dashoffset\"").count(),
+            2
+        );
+        assert_eq!(svg.matches("dur=\"2.5s\"").count(), 2 * 7 + 2 + 2);
+        // Every animation covers the whole loop, the second chain from its
+        // half
+        for key_times in svg.split("keyTimes=\"").skip(1) {
+            let times: Vec<f64> = key_times[..key_times.find('"').unwrap()]
+                .split(';')
+                .map(|t| t.parse().unwrap())
+                .collect();
+            assert_eq!(times[0], 0.0);
+            assert!(times.windows(2).all(|w| w[0] <= w[1]));
+            assert!(times.last() == Some(&1.0) || times.len() == 3);
+        }
+        assert!(svg.contains("keyTimes=\"0;0.5;1\""));
+
+        let parallel = animated_svg(&chains, ChainTiming::Parallel, &opts, &svg_opts);
+        assert!(!parallel.contains("visibility"));
+
+        // A congruence cannot pick loops that all look alike: it shows the
+        // contour, in white over the black background
+        let opts = EmbedOptions {
+            show_contour: WhenToShow::Congruence(Congruence {
+                modulo: 2,
+                congruents: vec![1],
+            }),
+            ..EmbedOptions::default()
+        };
+        let svg = animated_svg(&chains, ChainTiming::Parallel, &opts, &svg_opts);
+        assert!(svg.contains(r#"fill="none" stroke="gray""#));
+    }
+
+    #[test]
+    fn test_render_frames() {
+        assert_eq!(
+            parse_color("gold"),
+            Some(tiny_skia::Color::from_rgba8(255, 215, 0, 255))
+        );
+        assert_eq!(
+            parse_color("#f80"),
+            Some(tiny_skia::Color::from_rgba8(255, 136, 0, 255))
+        );
+        assert_eq!(
+            parse_color("rgb(1, 2, 3)"),
+            Some(tiny_skia::Color::from_rgba8(1, 2, 3, 255))
+        );
+        assert_eq!(parse_color("nope"), None);
+        assert!(HarmonicSteps::parse("1 1 3").is_err());
+        assert!(HarmonicSteps::parse("5 1 3 1").is_err());
+        let steps = HarmonicSteps::parse("1 1 3 5 ;").unwrap();
+        assert_eq!(steps.ranges.len(), 1);
+
+        let contour = contour_of_function(
+            &EquationCurve::new(
+                CurveForm::Polar {
+                    r: Expr::parse("10").unwrap(),
+                },
+                0.0,
+                std::f64::consts::TAU,
+                HashMap::new(),
+            )
+            .unwrap(),
+            200,
+        );
+        let chains = vec![Chain {
+            fourier: fourier_decomposition(&contour, 10),
+            contour,
+            offset: (0.0, 0.0),
+            style: ChainStyle::default(),
+        }];
+        let opts = EmbedOptions {
+            show_point: false,
+            show_fourier_circles: WhenToShow::Never,
+            opacity: 1.0,
+            trace_length: 1.0,
+            ..EmbedOptions::default()
+        };
+        let mut animation =
+            Animation::new(&chains, ChainTiming::Parallel, &opts, Some(&steps)).unwrap();
+        // Harmonics 1 and 2 at speed 5, then 3 at speed 1 as in the pages
+        assert_eq!(animation.schedule_len(), 3);
+        let black = tiny_skia::Color::BLACK;
+        let count = |pixmap: &tiny_skia::Pixmap, rgb: (u8, u8, u8)| {
+            pixmap
+                .pixels()
+                .iter()
+                .filter(|p| (p.red(), p.green(), p.blue()) == rgb)
+                .count()
+        };
+        let mut frames = 0;
+        let mut last = None;
+        while animation.completed_loops() == 0 {
+            last = Some(animation.frame(100, black).unwrap());
+            animation.advance(0.1);
+            frames += 1;
+        }
+        assert!((20..=21).contains(&frames));
+        // The first loop is traced in red, the second in lime after its
+        // trace restarts
+        let last = last.unwrap();
+        assert!(count(&last, (255, 0, 0)) > 100);
+        assert_eq!(count(&last, (0, 255, 0)), 0);
+        animation.frame(100, black).unwrap();
+        animation.advance(0.1);
+        let next = animation.frame(100, black).unwrap();
+        assert_eq!(count(&next, (255, 0, 0)), 0);
+        assert!(count(&next, (0, 255, 0)) > 0);
+
+        // Skipped frames still grow the trace
+        let mut drawn = Animation::new(&chains, ChainTiming::Parallel, &opts, None).unwrap();
+        let mut skipped = Animation::new(&chains, ChainTiming::Parallel, &opts, None).unwrap();
+        for _ in 0..10 {
+            drawn.frame(100, black).unwrap();
+            drawn.advance(0.1);
+            skipped.skip_frame();
+            skipped.advance(0.1);
+        }
+        let frame = drawn.frame(100, black).unwrap();
+        assert!(count(&frame, (255, 0, 0)) > 20);
+        assert!(frame == skipped.frame(100, black).unwrap());
+        // Rewound, it starts over as a new animation
+        let mut fresh = Animation::new(&chains, ChainTiming::Parallel, &opts, None).unwrap();
+        skipped.rewind();
+        assert!(skipped.frame(100, black).unwrap() == fresh.frame(100, black).unwrap());
+
+        // Empty frames are an error, not a panic
+        assert!(drawn.frame(0, black).is_err());
+        assert!(drawn.frames(1, 0, 10.0, black).is_err());
+        let rest = drawn.frame_count(1, 10.0);
+        assert_eq!(drawn.frames(1, 10, 10.0, black).unwrap().count(), rest);
+    }
+
+    #[test]
+    fn test_encode_animation() {
+        assert_eq!(parse_byte_size("1.5K"), Ok(1536));
+        assert_eq!(parse_byte_size("2M"), Ok(2 * 1024 * 1024));
+        assert_eq!(parse_byte_size("300"), Ok(300));
+        assert!(parse_byte_size("big").is_err());
+
+        let contour = interpolate(
+            &Contour {
+                points: vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)],
+            },
+            200,
+        );
+        let chains = vec![Chain {
+            fourier: fourier_decomposition(&contour, 10),
+            contour,
+            offset: (0.0, 0.0),
+            style: ChainStyle::default(),
+        }];
+        let opts = EmbedOptions::default();
+        let steps = HarmonicSteps::parse("1 1 3 5").unwrap();
+        let frames = Animation::new(&chains, ChainTiming::Parallel, &opts, Some(&steps))
+            .unwrap()
+            .frame_count(2, 10.0);
+        let mut anim = AnimationOptions {
+            format: AnimationFormat::Gif,
+            size: 64,
+            fps: 10.0,
+            loops: Some(2),
+            background: tiny_skia::Color::BLACK,
+            colors: None,
+            palette: PaletteMode::Global,
+            max_bytes: None,
+        };
+        let encode = |anim: &AnimationOptions| {
+            encode_animation(&chains, ChainTiming::Parallel, &opts, Some(&steps), anim).unwrap()
+        };
+
+        let (gif, size) = encode(&anim);
+        assert_eq!(size, 64);
+        assert!(gif.starts_with(b"GIF89a"));
+        assert!(gif.windows(11).any(|w| w == b"NETSCAPE2.0"));
+        let mut decoder = gif::DecodeOptions::new().read_info(gif.as_slice()).unwrap();
+        let mut delays = vec![];
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            delays.push(frame.delay);
+        }
+        assert_eq!(delays.len(), frames);
+        assert!(delays.iter().all(|&d| d == 10));
+
+        anim.palette = PaletteMode::Local;
+        anim.colors = Some(16);
+        let (local, _) = encode(&anim);
+        assert!(local.starts_with(b"GIF89a"));
+
+        anim.format = AnimationFormat::Apng;
+        anim.colors = None;
+        let (apng, _) = encode(&anim);
+        let reader = png::Decoder::new(apng.as_slice()).read_info().unwrap();
+        let control = reader.info().animation_control.unwrap();
+        assert_eq!(control.num_frames as usize, frames);
+        assert_eq!(control.num_plays, 0);
+
+        // Too large files shrink until they fit
+        anim.format = AnimationFormat::Gif;
+        anim.palette = PaletteMode::Global;
+        anim.max_bytes = Some(gif.len() / 2);
+        let (small, size) = encode(&anim);
+        assert!(size < 64);
+        assert!(small.len() <= gif.len() / 2);
+        anim.max_bytes = Some(10);
+        assert!(
+            encode_animation(&chains, ChainTiming::Parallel, &opts, Some(&steps), &anim).is_err()
+        );
+
+        // Invalid sizes and frame rates are errors, not panics or endless loops
+        anim.max_bytes = None;
+        for (size, fps) in [(0, 10.0), (64, 0.0), (64, f64::NAN), (64, f64::INFINITY)] {
+            anim.size = size;
+            anim.fps = fps;
+            assert!(
+                encode_animation(&chains, ChainTiming::Parallel, &opts, Some(&steps), &anim)
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_poster() {
+        let contour = contour_of_function(
+            &EquationCurve::new(
+                CurveForm::Polar {
+                    r: Expr::parse("10 + 3 * cos(5 * t)").unwrap(),
+                },
+                0.0,
+                std::f64::consts::TAU,
+                HashMap::new(),
+            )
+            .unwrap(),
+            300,
+        );
+        let chains = vec![Chain {
+            fourier: fourier_decomposition(&contour, 20),
+            contour,
+            offset: (0.0, 0.0),
+            style: ChainStyle::default(),
+        }];
+        let opts = EmbedOptions {
+            trace_colors: ["red", "lime", "blue", "orange"].map(String::from).to_vec(),
+            ..EmbedOptions::default()
+        };
+        // Counts past the available harmonics collapse into one tile
+        let steps = HarmonicSteps::parse("1 1 4 3 ; 4 12 80 3").unwrap();
+        let tiles = poster_tiles(&chains, &opts, &steps, ErrorMetric::Rms);
+        let counts: Vec<usize> = tiles.iter().map(|t| t.harmonics).collect();
+        assert_eq!(counts, vec![1, 2, 3, 4, 16, 28, 40, 41]);
+        let loops: Vec<usize> = tiles.iter().map(|t| t.loop_index).collect();
+        assert_eq!(loops, (0..8).collect::<Vec<_>>());
+        assert!(tiles.windows(2).all(|w| w[1].error <= w[0].error + 1e-12));
+        assert!(tiles[0].error > 0.01);
+        assert!(tiles.last().unwrap().error < 1e-3);
+        let max = poster_tiles(&chains, &opts, &steps, ErrorMetric::Max);
+        assert!(max.iter().zip(&tiles).all(|(m, r)| m.error >= r.error));
+
+        let poster = PosterOptions {
+            metric: ErrorMetric::Rms,
+            columns: Some(3),
+            size: 60,
+            background: "black".into(),
+        };
+        let svg = poster_svg(&chains, &opts, &tiles, &poster).unwrap();
+        assert!(svg.contains(r#"width="180" height="180""#));
+        assert!(svg.contains(r#"id="harmonics-41""#));
+        // Each tile has the trace color of its loop
+        for (n, color) in [(2, "lime"), (16, "red")] {
+            let tile = svg.split(&format!(r#"id="harmonics-{n}""#)).nth(1).unwrap();
+            let tile = &tile[..tile.find("</svg>").unwrap()];
+            assert!(tile.contains(&format!(r#"stroke="{color}""#)));
+        }
+        assert!(svg.contains(">N = 16<"));
+        assert!(svg.contains(">RMS 6.16%<"));
+        let png = poster_png(&chains, &opts, &tiles, &poster).unwrap();
+        let decoder = png::Decoder::new(std::io::Cursor::new(png));
+        let info = decoder.read_info().unwrap().info().clone();
+        assert_eq!((info.width, info.height), (180, 180));
+        let poster = PosterOptions {
+            background: "nope".into(),
+            ..poster
+        };
+        assert!(poster_png(&chains, &opts, &tiles, &poster).is_err());
+        let poster = PosterOptions {
+            size: u32::MAX / 2,
+            ..poster
+        };
+        assert!(poster_svg(&chains, &opts, &tiles, &poster).is_err());
+        assert!(poster_png(&chains, &opts, &tiles, &poster).is_err());
+    }
 }
@@ -1,48 +1,55 @@
+use std::path::PathBuf;
+
+use font_kit::handle::Handle;
+
+use crate::canvas::points_of_svg_path;
+use crate::contour::Contour;
 use font_kit::source::SystemSource;
 use ttf_parser::OutlineBuilder;
 
 struct SvgPathBuilder {
     path: String,
     x_offset: f64,
+    y_offset: f64,
 }
 
 impl SvgPathBuilder {
-    fn new(x_offset: f64) -> Self {
+    /// `(x_offset, y_offset)` is the glyph origin in screen coordinates
+    /// (y pointing down).
+    fn new(x_offset: f64, y_offset: f64) -> Self {
         Self {
             path: String::new(),
             x_offset,
+            y_offset,
         }
     }
+
+    fn point(&self, x: f32, y: f32) -> (f64, f64) {
+        (x as f64 + self.x_offset, self.y_offset - y as f64)
+    }
 }
 
 impl OutlineBuilder for SvgPathBuilder {
     fn move_to(&mut self, x: f32, y: f32) {
-        let x = x as f64 + self.x_offset;
-        let y = -(y as f64);
+        let (x, y) = self.point(x, y);
         self.path.push_str(&format!("M {x} {y} "));
     }
 
     fn line_to(&mut self, x: f32, y: f32) {
-        let x = x as f64 + self.x_offset;
-        let y = -(y as f64);
+        let (x, y) = self.point(x, y);
         self.path.push_str(&format!("L {x} {y} "));
     }
 
     fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
-        let x1 = x1 as f64 + self.x_offset;
-        let y1 = -(y1 as f64);
-        let x = x as f64 + self.x_offset;
-        let y = -(y as f64);
+        let (x1, y1) = self.point(x1, y1);
+        let (x, y) = self.point(x, y);
         self.path.push_str(&format!("Q {x1} {y1} {x} {y} "));
     }
 
     fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
-        let x1 = x1 as f64 + self.x_offset;
-        let y1 = -(y1 as f64);
-        let x2 = x2 as f64 + self.x_offset;
-        let y2 = -(y2 as f64);
-        let x = x as f64 + self.x_offset;
-        let y = -(y as f64);
+        let (x1, y1) = self.point(x1, y1);
+        let (x2, y2) = self.point(x2, y2);
+        let (x, y) = self.point(x, y);
         self.path
             .push_str(&format!("C {x1} {y1} {x2} {y2} {x} {y} "));
     }
@@ -52,33 +59,306 @@ impl OutlineBuilder for SvgPathBuilder {
     }
 }
 
-pub fn svg_path_of_text(text: &str, font_name: &str) -> String {
-    let font = SystemSource::new()
-        .select_by_postscript_name(font_name)
-        .unwrap_or_else(|_| panic!("font not found: {font_name}"))
-        .load()
-        .unwrap_or_else(|_| panic!("failed to load font: {font_name}"));
+/// Horizontal alignment of the lines of a multi-line text.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
 
-    let font_data = font.copy_font_data().expect("failed to copy font data");
-    let face = ttf_parser::Face::parse(&font_data, 0).expect("failed to parse font");
+/// Layout settings for `svg_path_of_text`.
+pub struct TextLayout {
+    /// Distance between baselines, in ems
+    pub line_height: f64,
+    pub align: Align,
+    /// Extra space added after each character, in ems (may be negative)
+    pub letter_spacing: f64,
+}
 
-    let mut path = String::new();
-    let mut x: f64 = 0.0;
+impl Default for TextLayout {
+    fn default() -> Self {
+        Self {
+            line_height: 1.2,
+            align: Align::Left,
+            letter_spacing: 0.0,
+        }
+    }
+}
 
-    for ch in text.chars() {
-        let glyph_id = match face.glyph_index(ch) {
-            Some(id) => id,
-            None => continue,
+/// Outline of a laid out text, in font units with y pointing down.
+pub struct TextOutline {
+    pub path: String,
+    /// The same outline split by glyph
+    pub glyphs: Vec<GlyphPaths>,
+    /// Characters the font has no glyph for, in order of first appearance
+    pub missing: Vec<char>,
+}
+
+/// The paths of one placed glyph, relative to its origin on the baseline.
+pub struct GlyphPaths {
+    pub origin: (f64, f64),
+    /// Index of the word the glyph belongs to, counted across lines
+    pub word: usize,
+    pub paths: Vec<Contour>,
+}
+
+/// How a text is split into epicycle chains.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum TextChains {
+    /// The whole text as one contour
+    Single,
+    /// One chain per glyph
+    Glyph,
+    /// One chain per word
+    Word,
+}
+
+/// Group glyphs into chains. Each group is returned with its origin and its
+/// paths relative to that origin, in reading order.
+pub fn group_glyphs(glyphs: &[GlyphPaths], split: TextChains) -> Vec<(Vec<Contour>, (f64, f64))> {
+    let mut groups: Vec<(Vec<Contour>, (f64, f64))> = Vec::new();
+    let mut last_key = None;
+    for glyph in glyphs.iter().filter(|g| !g.paths.is_empty()) {
+        let key = match split {
+            TextChains::Single => 0,
+            TextChains::Glyph => groups.len(),
+            TextChains::Word => glyph.word,
         };
+        if last_key != Some(key) {
+            let origin = match split {
+                TextChains::Single => (0.0, 0.0),
+                _ => glyph.origin,
+            };
+            groups.push((Vec::new(), origin));
+            last_key = Some(key);
+        }
+        let (paths, origin) = groups.last_mut().expect("group was just pushed");
+        let (dx, dy) = (glyph.origin.0 - origin.0, glyph.origin.1 - origin.1);
+        paths.extend(glyph.paths.iter().map(|c| Contour {
+            points: c.points.iter().map(|&(x, y)| (x + dx, y + dy)).collect(),
+        }));
+    }
+    groups
+}
 
-        let mut builder = SvgPathBuilder::new(x);
-        face.outline_glyph(glyph_id, &mut builder);
-        path.push_str(&builder.path);
+/// Word index of each byte of `line`, continuing from `first`. Whitespace
+/// belongs to the preceding word.
+pub(crate) fn word_indices(line: &str, first: usize) -> (Vec<usize>, usize) {
+    let mut words = Vec::with_capacity(line.len());
+    let mut word = first;
+    let mut in_space = true;
+    let mut started = false;
+    for ch in line.chars() {
+        let space = ch.is_whitespace();
+        if !space && in_space && started {
+            word += 1;
+        }
+        started |= !space;
+        in_space = space;
+        words.extend(std::iter::repeat_n(word, ch.len_utf8()));
+    }
+    (words, if started { word + 1 } else { word })
+}
 
-        if let Some(advance) = face.glyph_hor_advance(glyph_id) {
-            x += advance as f64;
+/// Where to load a font from.
+pub enum FontSource {
+    /// An installed font, by PostScript name
+    System(String),
+    /// A `.ttf`, `.otf` or `.ttc` file; `index` selects the face in a collection
+    File { path: PathBuf, index: u32 },
+}
+
+/// A font and the variable-font axis settings to apply to it.
+pub struct FontSpec {
+    pub source: FontSource,
+    /// Axis tag and value pairs, e.g. `("wght", 700.0)`
+    pub variations: Vec<(String, f32)>,
+}
+
+/// Parse a variation setting of the form `TAG=VALUE`, e.g. `wght=700`.
+pub fn parse_variation(s: &str) -> Result<(String, f32), String> {
+    let (tag, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("variation must be TAG=VALUE: {s}"))?;
+    let tag = tag.trim();
+    if tag.len() != 4 || !tag.is_ascii() {
+        return Err(format!(
+            "variation axis tag must be 4 ASCII characters: {tag:?}"
+        ));
+    }
+    let value = value
+        .trim()
+        .parse::<f32>()
+        .map_err(|_| format!("variation value is not a number: {value:?}"))?;
+    Ok((tag.to_string(), value))
+}
+
+/// Load the raw font data and the face index within it.
+fn load_font_data(source: &FontSource) -> Result<(Vec<u8>, u32), String> {
+    match source {
+        FontSource::System(name) => {
+            let handle = SystemSource::new()
+                .select_by_postscript_name(name)
+                .map_err(|_| format!("font not found: {name}"))?;
+            match handle {
+                Handle::Path { path, font_index } => std::fs::read(&path)
+                    .map(|data| (data, font_index))
+                    .map_err(|e| format!("{}: {e}", path.display())),
+                Handle::Memory { bytes, font_index } => Ok((bytes.to_vec(), font_index)),
+            }
+        }
+        FontSource::File { path, index } => std::fs::read(path)
+            .map(|data| (data, *index))
+            .map_err(|e| format!("{}: {e}", path.display())),
+    }
+}
+
+/// Parse the selected face and apply the variation settings.
+fn with_face<T>(font: &FontSpec, f: impl FnOnce(&ttf_parser::Face) -> T) -> Result<T, String> {
+    let (data, index) = load_font_data(&font.source)?;
+    let faces = ttf_parser::fonts_in_collection(&data).unwrap_or(1);
+    if index >= faces {
+        return Err(format!(
+            "face index {index} out of range (the font has {faces} face(s))"
+        ));
+    }
+    let mut face =
+        ttf_parser::Face::parse(&data, index).map_err(|e| format!("failed to parse font: {e}"))?;
+
+    for (tag, value) in &font.variations {
+        let axis_tag = ttf_parser::Tag::from_bytes_lossy(tag.as_bytes());
+        if face.set_variation(axis_tag, *value).is_none() {
+            let axes: Vec<String> = face
+                .variation_axes()
+                .into_iter()
+                .map(|a| format!("{} ({}..{})", a.tag, a.min_value, a.max_value))
+                .collect();
+            return Err(if axes.is_empty() {
+                format!("font is not a variable font, cannot set {tag}")
+            } else {
+                format!("font has no {tag} axis (axes: {})", axes.join(", "))
+            });
+        }
+    }
+    Ok(f(&face))
+}
+
+/// A glyph placed on a line, relative to the line's start and baseline.
+struct PlacedGlyph {
+    id: ttf_parser::GlyphId,
+    x: f64,
+    y: f64,
+    word: usize,
+}
+
+/// Shape one line with kerning, ligatures, mark positioning and the script's
+/// direction. Returns the placed glyphs and the line's advance width.
+fn shape_line(
+    face: &rustybuzz::Face,
+    line: &str,
+    letter_spacing: f64,
+    words: &[usize],
+    missing: &mut Vec<char>,
+) -> (Vec<PlacedGlyph>, f64) {
+    let mut buffer = rustybuzz::UnicodeBuffer::new();
+    buffer.push_str(line);
+    buffer.guess_segment_properties();
+    let shaped = rustybuzz::shape(face, &[], buffer);
+    let infos = shaped.glyph_infos();
+    let positions = shaped.glyph_positions();
+
+    let mut glyphs = Vec::with_capacity(infos.len());
+    let mut x = 0.0;
+    for (i, (info, pos)) in infos.iter().zip(positions).enumerate() {
+        if info.glyph_id == 0 {
+            let ch = line[info.cluster as usize..].chars().next();
+            if let Some(ch) = ch.filter(|c| !missing.contains(c)) {
+                missing.push(ch);
+            }
+        } else {
+            glyphs.push(PlacedGlyph {
+                id: ttf_parser::GlyphId(info.glyph_id as u16),
+                x: x + pos.x_offset as f64,
+                y: pos.y_offset as f64,
+                word: words[info.cluster as usize],
+            });
+        }
+        x += pos.x_advance as f64;
+        // Space characters, not glyphs: marks and ligature parts share a cluster
+        let last_of_cluster = infos.get(i + 1).is_none_or(|n| n.cluster != info.cluster);
+        if last_of_cluster && i + 1 < infos.len() {
+            x += letter_spacing;
         }
     }
+    (glyphs, x)
+}
+
+/// Lay out `text` (lines separated by `\n`) and return its outline. The
+/// first baseline is at y = 0.
+pub fn svg_path_of_text(
+    text: &str,
+    font: &FontSpec,
+    layout: &TextLayout,
+) -> Result<TextOutline, String> {
+    with_face(font, |face| {
+        let face = rustybuzz::Face::from_face(face.clone());
+        let em = face.units_per_em() as f64;
+        let mut missing = Vec::new();
+        let mut next_word = 0;
+        let lines: Vec<_> = text
+            .lines()
+            .map(|line| {
+                let (words, next) = word_indices(line, next_word);
+                next_word = next;
+                shape_line(
+                    &face,
+                    line,
+                    layout.letter_spacing * em,
+                    &words,
+                    &mut missing,
+                )
+            })
+            .collect();
+        let max_width = lines.iter().map(|(_, w)| *w).fold(0.0, f64::max);
+
+        let mut path = String::new();
+        let mut placed = Vec::new();
+        for (n, (glyphs, width)) in lines.iter().enumerate() {
+            let x0 = match layout.align {
+                Align::Left => 0.0,
+                Align::Center => (max_width - width) / 2.0,
+                Align::Right => max_width - width,
+            };
+            let baseline = n as f64 * layout.line_height * em;
+            for glyph in glyphs {
+                let origin = (x0 + glyph.x, baseline - glyph.y);
+                let mut builder = SvgPathBuilder::new(origin.0, origin.1);
+                face.outline_glyph(glyph.id, &mut builder);
+                path.push_str(&builder.path);
 
-    path.trim_end().to_string()
+                let mut local = SvgPathBuilder::new(0.0, 0.0);
+                face.outline_glyph(glyph.id, &mut local);
+                let points = points_of_svg_path(&local.path);
+                placed.push(GlyphPaths {
+                    origin,
+                    word: glyph.word,
+                    paths: if points.is_empty() {
+                        vec![]
+                    } else {
+                        vec![Contour { points }]
+                    },
+                });
+            }
+        }
+
+        TextOutline {
+            path: path.trim_end().to_string(),
+            glyphs: placed,
+            missing,
+        }
+    })
 }